@@ -0,0 +1,420 @@
+use crate::analyzer::linear::{linearize, LinearExpression};
+use crate::analyzer::polyhedron::{
+    combine, dot, format_constraint, normalize, unit, Constraint, ConstraintKind, Vector,
+};
+use crate::analyzer::rational::Rational;
+use crate::analyzer::{AbstractDomain, AbstractState, Comparison};
+use crate::ast::arithmetic::ArithmeticExpression;
+use crate::ast::State;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+// Dominio delle uguaglianze affini (Karr): lo stato è un sottospazio affine,
+// descritto da righe [a₁ … aₙ | b] che valgono Σ aᵢ·xᵢ + b = 0.
+// Le righe sono tenute in forma ridotta di Gauss–Jordan, che è canonica.
+// Il reticolo ha altezza finita (la dimensione cala a ogni passo stretto),
+// quindi il join fa anche da widening.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AffineEqualities {
+    vars: Vec<String>,
+    rows: Vec<Vector>,
+    bottom: bool,
+}
+
+// Forma ridotta di Gauss–Jordan: restituisce le righe non nulle e le colonne pivot,
+// None in caso di overflow
+fn reduce(mut rows: Vec<Vector>) -> Option<(Vec<Vector>, Vec<usize>)> {
+    let width = rows.first().map_or(0, |r| r.len());
+    let mut pivots = Vec::new();
+    for col in 0..width {
+        let rank = pivots.len();
+        let Some(r) = (rank..rows.len()).find(|r| !rows[*r][col].is_zero()) else {
+            continue;
+        };
+        rows.swap(rank, r);
+        let factor = rows[rank][col];
+        rows[rank] = rows[rank]
+            .iter()
+            .map(|x| x.checked_div(factor))
+            .collect::<Option<Vector>>()?;
+        for r in 0..rows.len() {
+            let value = rows[r][col];
+            if r != rank && !value.is_zero() {
+                rows[r] = combine(Rational::one(), &rows[r], -value, &rows[rank])?;
+            }
+        }
+        pivots.push(col);
+    }
+    rows.truncate(pivots.len());
+    Some((rows, pivots))
+}
+
+// Base del nucleo { v | r·v = 0 per ogni riga r } di dimensione width
+fn kernel(rows: Vec<Vector>, width: usize) -> Option<Vec<Vector>> {
+    let (rows, pivots) = reduce(rows)?;
+    let basis = (0..width)
+        .filter(|col| !pivots.contains(col))
+        .map(|free| {
+            let mut v = unit(width, free);
+            for (row, pivot) in rows.iter().zip(pivots.iter()) {
+                v[*pivot] = -row[free];
+            }
+            v
+        })
+        .collect();
+    Some(basis)
+}
+
+impl AffineEqualities {
+    fn index_of(&self, var: &str) -> Option<usize> {
+        self.vars.iter().position(|v| v == var)
+    }
+
+    // Indice della variabile, aggiungendola senza vincoli se non c'è.
+    // La colonna del termine noto resta l'ultima.
+    fn ensure_var(&mut self, var: &str) -> usize {
+        if let Some(k) = self.index_of(var) {
+            return k;
+        }
+        let k = self.vars.len();
+        for row in &mut self.rows {
+            row.insert(k, Rational::zero());
+        }
+        self.vars.push(var.to_string());
+        k
+    }
+
+    // Porta entrambi gli stati sullo stesso insieme di variabili, nello stesso ordine
+    fn unify(&self, other: &AffineEqualities) -> (AffineEqualities, AffineEqualities) {
+        let mut left = self.clone();
+        for var in &other.vars {
+            left.ensure_var(var);
+        }
+        let mut rows = Vec::new();
+        for row in &other.rows {
+            let mut permuted = vec![Rational::zero(); left.vars.len() + 1];
+            for (k, var) in other.vars.iter().enumerate() {
+                permuted[left.index_of(var).unwrap()] = row[k];
+            }
+            permuted[left.vars.len()] = row[other.vars.len()];
+            rows.push(permuted);
+        }
+        let right = AffineEqualities::from_rows(left.vars.clone(), rows);
+        (left, right)
+    }
+
+    // Riduce le righe; un pivot sulla colonna del termine noto è la riga 0 = b ≠ 0.
+    // In caso di overflow si tolgono le uguaglianze a partire dalle ultime:
+    // il sottospazio ottenuto contiene quello esatto.
+    fn from_rows(vars: Vec<String>, mut rows: Vec<Vector>) -> AffineEqualities {
+        loop {
+            if rows.is_empty() {
+                return AffineEqualities {
+                    vars,
+                    rows,
+                    bottom: false,
+                };
+            }
+            if let Some((reduced, pivots)) = reduce(rows.clone()) {
+                if pivots.contains(&vars.len()) {
+                    return AffineEqualities::bottom();
+                }
+                return AffineEqualities {
+                    vars,
+                    rows: reduced,
+                    bottom: false,
+                };
+            }
+            rows.pop();
+        }
+    }
+
+    // Rappresentazione per generatori: un punto e una base delle direzioni.
+    // Le righe sono già ridotte, quindi si leggono senza fare conti.
+    fn generators(&self) -> (Vector, Vec<Vector>) {
+        let n = self.vars.len();
+        let rows = &self.rows;
+        let pivots: Vec<usize> = rows
+            .iter()
+            .map(|row| row.iter().position(|x| !x.is_zero()).unwrap())
+            .collect();
+        let mut point = vec![Rational::zero(); n];
+        for (row, pivot) in rows.iter().zip(pivots.iter()) {
+            point[*pivot] = -row[n];
+        }
+        let directions = (0..n)
+            .filter(|col| !pivots.contains(col))
+            .map(|free| {
+                let mut d = unit(n, free);
+                for (row, pivot) in rows.iter().zip(pivots.iter()) {
+                    d[*pivot] = -row[free];
+                }
+                d
+            })
+            .collect();
+        (point, directions)
+    }
+
+    // Le uguaglianze soddisfatte da tutti i generatori formano il nucleo della matrice
+    // che ha per righe [p | 1] e [d | 0]. In caso di overflow non resta nessuna
+    // uguaglianza.
+    fn from_generators(vars: Vec<String>, point: Vector, directions: Vec<Vector>) -> Self {
+        let mut matrix = Vec::new();
+        let mut p = point;
+        p.push(Rational::one());
+        matrix.push(p);
+        for mut d in directions {
+            d.push(Rational::zero());
+            matrix.push(d);
+        }
+        let width = vars.len() + 1;
+        let rows = kernel(matrix, width).unwrap_or_default();
+        AffineEqualities::from_rows(vars, rows)
+    }
+
+    // Valore costante di e sul sottospazio, se e non varia lungo nessuna direzione
+    fn constant_value(&self, e: &LinearExpression) -> Option<Rational> {
+        let coefficients: Vector = self
+            .vars
+            .iter()
+            .map(|v| Rational::from(e.coefficient(v)))
+            .collect();
+        let (point, directions) = self.generators();
+        for d in &directions {
+            if !dot(&coefficients, d)?.is_zero() {
+                return None;
+            }
+        }
+        dot(&coefficients, &point)?.checked_add(Rational::from(e.constant))
+    }
+
+    fn row_of(&mut self, e: &LinearExpression) -> Vector {
+        for var in e.coefficients.keys() {
+            self.ensure_var(var);
+        }
+        let mut row: Vector = self
+            .vars
+            .iter()
+            .map(|v| Rational::from(e.coefficient(v)))
+            .collect();
+        row.push(Rational::from(e.constant));
+        row
+    }
+
+    // La variabile può assumere qualsiasi valore
+    pub fn havoc(&mut self, var: &str) {
+        if self.bottom {
+            return;
+        }
+        let k = self.ensure_var(var);
+        let (point, mut directions) = self.generators();
+        directions.push(unit(self.vars.len(), k));
+        *self = AffineEqualities::from_generators(self.vars.clone(), point, directions);
+    }
+}
+
+impl AbstractDomain for AffineEqualities {
+    fn bottom() -> Self {
+        AffineEqualities {
+            vars: Vec::new(),
+            rows: Vec::new(),
+            bottom: true,
+        }
+    }
+
+    fn top() -> Self {
+        AffineEqualities {
+            vars: Vec::new(),
+            rows: Vec::new(),
+            bottom: false,
+        }
+    }
+
+    fn is_bottom(&self) -> bool {
+        self.bottom
+    }
+
+    // Ogni uguaglianza di other deve valere su tutti i generatori di self.
+    // Se i conti vanno in overflow si confronta il join con other: le righe
+    // sono in forma canonica, e il join ripiega su ⊤ quando non si può calcolare.
+    fn leq(&self, other: &Self) -> bool {
+        if self.bottom {
+            return true;
+        }
+        if other.bottom {
+            return false;
+        }
+        let (left, right) = self.unify(other);
+        let n = left.vars.len();
+        let (point, directions) = left.generators();
+        let holds = |row: &Vector| -> Option<bool> {
+            if !dot(&row[..n], &point)?.checked_add(row[n])?.is_zero() {
+                return Some(false);
+            }
+            for d in &directions {
+                if !dot(&row[..n], d)?.is_zero() {
+                    return Some(false);
+                }
+            }
+            Some(true)
+        };
+        let mut overflow = false;
+        for row in &right.rows {
+            match holds(row) {
+                Some(true) => {}
+                Some(false) => return false,
+                None => overflow = true,
+            }
+        }
+        !overflow || self.join(other) == right
+    }
+
+    // Inviluppo affine esatto: p₁ più le direzioni di entrambi e p₂ - p₁
+    fn join(&self, other: &Self) -> Self {
+        if self.bottom {
+            return other.clone();
+        }
+        if other.bottom {
+            return self.clone();
+        }
+        let (left, right) = self.unify(other);
+        let (p1, mut directions) = left.generators();
+        let (p2, others) = right.generators();
+        directions.extend(others);
+        match combine(Rational::one(), &p2, -Rational::one(), &p1) {
+            Some(d) => directions.push(d),
+            None => return AffineEqualities::from_rows(left.vars, Vec::new()),
+        }
+        AffineEqualities::from_generators(left.vars, p1, directions)
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        if self.bottom || other.bottom {
+            return AffineEqualities::bottom();
+        }
+        let (left, right) = self.unify(other);
+        let mut rows = left.rows;
+        rows.extend(right.rows);
+        AffineEqualities::from_rows(left.vars, rows)
+    }
+}
+
+impl AbstractState for AffineEqualities {
+    fn from_state(state: &State) -> Self {
+        // ordine stabile delle variabili, lo stato concreto è una HashMap
+        let mut entries: Vec<(&String, &i32)> = state.iter().collect();
+        entries.sort();
+        let vars: Vec<String> = entries.iter().map(|(name, _)| name.to_string()).collect();
+        let rows = entries
+            .iter()
+            .enumerate()
+            .map(|(k, (_, value))| {
+                let mut row = unit(vars.len() + 1, k);
+                row[vars.len()] = -Rational::from(**value as i64);
+                row
+            })
+            .collect();
+        AffineEqualities::from_rows(vars, rows)
+    }
+
+    // Gli assegnamenti lineari trasformano esattamente i generatori,
+    // quelli non lineari rendono la variabile libera
+    fn assign(&mut self, var: &str, expr: &dyn ArithmeticExpression) {
+        if self.bottom {
+            return;
+        }
+        let Some(e) = linearize(expr) else {
+            self.havoc(var);
+            return;
+        };
+        let k = self.ensure_var(var);
+        for v in e.coefficients.keys() {
+            self.ensure_var(v);
+        }
+        let coefficients: Vector = self
+            .vars
+            .iter()
+            .map(|v| Rational::from(e.coefficient(v)))
+            .collect();
+        let (mut point, mut directions) = self.generators();
+        let image = |point: &mut Vector, directions: &mut Vec<Vector>| -> Option<()> {
+            point[k] = dot(&coefficients, point)?.checked_add(Rational::from(e.constant))?;
+            for d in directions {
+                d[k] = dot(&coefficients, d)?;
+            }
+            Some(())
+        };
+        // immagine non rappresentabile: la variabile diventa libera
+        if image(&mut point, &mut directions).is_none() {
+            self.havoc(var);
+            return;
+        }
+        *self = AffineEqualities::from_generators(self.vars.clone(), point, directions);
+    }
+
+    // Si impara solo dalle uguaglianze lineari; gli altri confronti servono
+    // a scoprire che il ramo è irraggiungibile quando left - right è costante
+    fn assume_comparison(
+        &mut self,
+        op: Comparison,
+        left: &dyn ArithmeticExpression,
+        right: &dyn ArithmeticExpression,
+    ) {
+        if self.bottom {
+            return;
+        }
+        let difference = match (linearize(left), linearize(right)) {
+            (Some(l), Some(r)) => l.combine(1, &r, -1),
+            _ => None,
+        };
+        let Some(e) = difference else {
+            return;
+        };
+        if op == Comparison::Equal {
+            let row = self.row_of(&e);
+            let mut rows = self.rows.clone();
+            rows.push(row);
+            *self = AffineEqualities::from_rows(self.vars.clone(), rows);
+            return;
+        }
+        if let Some(value) = self.constant_value(&e) {
+            let holds = match op {
+                Comparison::Equal => value.is_zero(),
+                Comparison::NotEqual => !value.is_zero(),
+                Comparison::Less => value.signum() < 0,
+                Comparison::LessEqual => value.signum() <= 0,
+                Comparison::Great => value.signum() > 0,
+                Comparison::GreatEqual => value.signum() >= 0,
+            };
+            if !holds {
+                *self = AffineEqualities::bottom();
+            }
+        }
+    }
+}
+
+impl Display for AffineEqualities {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.bottom {
+            return write!(f, "⊥");
+        }
+        if self.rows.is_empty() {
+            return write!(f, "⊤");
+        }
+        let n = self.vars.len();
+        let entries: Vec<String> = self
+            .rows
+            .iter()
+            .map(|row| {
+                let row = normalize(row.clone()).unwrap_or_else(|| row.clone());
+                let constraint = Constraint {
+                    kind: ConstraintKind::Equality,
+                    coefficients: row[..n].to_vec(),
+                    constant: row[n],
+                };
+                format_constraint(&self.vars, &constraint)
+            })
+            .collect();
+        write!(f, "{{ {} }}", entries.join(", "))
+    }
+}
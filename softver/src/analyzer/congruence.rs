@@ -0,0 +1,297 @@
+use crate::analyzer::{AbstractDomain, Comparison, ValueDomain};
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+// Dominio delle congruenze: aℤ + b è l'insieme { a*k + b | k ∈ ℤ }.
+// Con a = 0 si ha la costante b, con a = 1 tutto ℤ; la parità è il caso a = 2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Congruence {
+    Bottom,
+    Class { modulus: i64, remainder: i64 },
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    let (mut a, mut b) = ((a as i128).abs(), (b as i128).abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    // solo gcd(i64::MIN, i64::MIN) non è rappresentabile: 1 è comunque un modulo corretto
+    i64::try_from(a).unwrap_or(1)
+}
+
+// Soluzione x ≡ b1 (mod a1), x ≡ b2 (mod a2) con a1, a2 > 0, se esiste.
+// Se il minimo comune multiplo non sta in un i64 si tiene solo il vincolo col
+// modulo più grande: contiene la soluzione, quindi resta corretto.
+fn chinese_remainder(a1: i64, b1: i64, a2: i64, b2: i64) -> Option<(i64, i64)> {
+    let g = gcd(a1, a2);
+    if (b2 - b1) % g != 0 {
+        return None;
+    }
+    let Some(lcm) = (a1 / g).checked_mul(a2) else {
+        return Some(if a1 >= a2 { (a1, b1) } else { (a2, b2) });
+    };
+    // x = b1 + a1 * t, con a1 * t ≡ b2 - b1 (mod a2): si cerca t con l'euclide esteso
+    let (mut old_r, mut r) = (a1 / g, a2 / g);
+    let (mut old_s, mut s) = (1i128, 0i128);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_s, s) = (s, old_s - q as i128 * s);
+    }
+    let t = (old_s * ((b2 - b1) / g) as i128).rem_euclid((a2 / g) as i128);
+    let x = (b1 as i128 + a1 as i128 * t).rem_euclid(lcm as i128);
+    Some((lcm, x as i64))
+}
+
+impl Congruence {
+    pub fn new(modulus: i64, remainder: i64) -> Self {
+        let modulus = modulus.checked_abs().unwrap_or(1);
+        if modulus == 0 {
+            Congruence::Class {
+                modulus,
+                remainder,
+            }
+        } else {
+            Congruence::Class {
+                modulus,
+                remainder: remainder.rem_euclid(modulus),
+            }
+        }
+    }
+
+    // Costruisce aℤ + b da valori calcolati con possibile overflow: in quel caso è ⊤
+    fn checked(modulus: Option<i64>, remainder: Option<i64>) -> Self {
+        match (modulus, remainder) {
+            (Some(m), Some(r)) => Congruence::new(m, r),
+            _ => Congruence::top(),
+        }
+    }
+}
+
+impl AbstractDomain for Congruence {
+    fn bottom() -> Self {
+        Congruence::Bottom
+    }
+
+    fn top() -> Self {
+        Congruence::new(1, 0)
+    }
+
+    fn is_bottom(&self) -> bool {
+        *self == Congruence::Bottom
+    }
+
+    fn leq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Congruence::Bottom, _) => true,
+            (_, Congruence::Bottom) => false,
+            (
+                Congruence::Class {
+                    modulus: a1,
+                    remainder: b1,
+                },
+                Congruence::Class {
+                    modulus: a2,
+                    remainder: b2,
+                },
+            ) => {
+                if *a2 == 0 {
+                    *a1 == 0 && b1 == b2
+                } else {
+                    a1 % a2 == 0 && (b1 - b2) % a2 == 0
+                }
+            }
+        }
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Congruence::Bottom, x) | (x, Congruence::Bottom) => *x,
+            (
+                Congruence::Class {
+                    modulus: a1,
+                    remainder: b1,
+                },
+                Congruence::Class {
+                    modulus: a2,
+                    remainder: b2,
+                },
+            ) => match b1.checked_sub(*b2) {
+                Some(diff) => Congruence::new(gcd(gcd(*a1, *a2), diff), *b1),
+                None => Congruence::top(),
+            },
+        }
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Congruence::Bottom, _) | (_, Congruence::Bottom) => Congruence::Bottom,
+            (
+                Congruence::Class {
+                    modulus: a1,
+                    remainder: b1,
+                },
+                Congruence::Class {
+                    modulus: a2,
+                    remainder: b2,
+                },
+            ) => {
+                if *a1 == 0 {
+                    if self.leq(other) {
+                        *self
+                    } else {
+                        Congruence::Bottom
+                    }
+                } else if *a2 == 0 {
+                    if other.leq(self) {
+                        *other
+                    } else {
+                        Congruence::Bottom
+                    }
+                } else {
+                    match chinese_remainder(*a1, *b1, *a2, *b2) {
+                        Some((modulus, remainder)) => Congruence::new(modulus, remainder),
+                        None => Congruence::Bottom,
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl ValueDomain for Congruence {
+    fn constant(value: i32) -> Self {
+        Congruence::new(0, value as i64)
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Congruence::Bottom, _) | (_, Congruence::Bottom) => Congruence::Bottom,
+            (
+                Congruence::Class {
+                    modulus: a1,
+                    remainder: b1,
+                },
+                Congruence::Class {
+                    modulus: a2,
+                    remainder: b2,
+                },
+            ) => Congruence::checked(Some(gcd(*a1, *a2)), b1.checked_add(*b2)),
+        }
+    }
+
+    fn minus(&self, other: &Self) -> Self {
+        self.add(&other.uminus())
+    }
+
+    fn product(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Congruence::Bottom, _) | (_, Congruence::Bottom) => Congruence::Bottom,
+            // (a1ℤ + b1)(a2ℤ + b2) ⊆ gcd(a1a2, a1b2, a2b1)ℤ + b1b2
+            (
+                Congruence::Class {
+                    modulus: a1,
+                    remainder: b1,
+                },
+                Congruence::Class {
+                    modulus: a2,
+                    remainder: b2,
+                },
+            ) => {
+                let modulus = a1.checked_mul(*a2).and_then(|a1a2| {
+                    let a1b2 = a1.checked_mul(*b2)?;
+                    let a2b1 = a2.checked_mul(*b1)?;
+                    Some(gcd(gcd(a1a2, a1b2), a2b1))
+                });
+                Congruence::checked(modulus, b1.checked_mul(*b2))
+            }
+        }
+    }
+
+    fn divide(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Congruence::Bottom, _) | (_, Congruence::Bottom) => Congruence::Bottom,
+            (
+                Congruence::Class {
+                    modulus: a1,
+                    remainder: b1,
+                },
+                Congruence::Class {
+                    modulus: 0,
+                    remainder: c,
+                },
+            ) => {
+                if *c == 0 {
+                    // la divisione per zero blocca l'esecuzione
+                    Congruence::Bottom
+                } else if *a1 == 0 {
+                    Congruence::checked(Some(0), b1.checked_div(*c))
+                } else if a1 % c == 0 && b1 % c == 0 {
+                    // c divide ogni elemento, quindi la divisione è esatta
+                    Congruence::new(a1 / c, b1 / c)
+                } else {
+                    Congruence::top()
+                }
+            }
+            _ => Congruence::top(),
+        }
+    }
+
+    fn uminus(&self) -> Self {
+        match self {
+            Congruence::Bottom => Congruence::Bottom,
+            Congruence::Class { modulus, remainder } => {
+                Congruence::checked(Some(*modulus), remainder.checked_neg())
+            }
+        }
+    }
+
+    fn refine(op: Comparison, left: &Self, right: &Self) -> (Self, Self) {
+        match op {
+            Comparison::Equal => {
+                let common = left.meet(right);
+                (common, common)
+            }
+            _ => match (left.as_constant(), right.as_constant()) {
+                (Some(l), Some(r)) if !op.holds(l as i64, r as i64) => {
+                    (Congruence::Bottom, Congruence::Bottom)
+                }
+                _ if left.is_bottom() || right.is_bottom() => {
+                    (Congruence::Bottom, Congruence::Bottom)
+                }
+                _ => (*left, *right),
+            },
+        }
+    }
+
+    fn as_constant(&self) -> Option<i32> {
+        match self {
+            Congruence::Class {
+                modulus: 0,
+                remainder,
+            } => i32::try_from(*remainder).ok(),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Congruence {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Congruence::Bottom => write!(f, "⊥"),
+            Congruence::Class {
+                modulus: 0,
+                remainder,
+            } => write!(f, "{{{}}}", remainder),
+            Congruence::Class {
+                modulus: 1,
+                remainder: _,
+            } => write!(f, "⊤"),
+            Congruence::Class {
+                modulus,
+                remainder: 0,
+            } => write!(f, "{}ℤ", modulus),
+            Congruence::Class { modulus, remainder } => write!(f, "{}ℤ+{}", modulus, remainder),
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use crate::analyzer::{AbstractDomain, Comparison, ValueDomain};
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+// Dominio piatto delle costanti: ⊥ ⊑ n ⊑ ⊤ per ogni intero n
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Constant {
+    Bottom,
+    Value(i32),
+    Top,
+}
+
+impl Constant {
+    // Applica un'operazione concreta: se va in overflow (o divide per zero) restituisce None
+    fn lift(&self, other: &Constant, op: impl Fn(i32, i32) -> Option<i32>) -> Constant {
+        match (self, other) {
+            (Constant::Bottom, _) | (_, Constant::Bottom) => Constant::Bottom,
+            (Constant::Value(a), Constant::Value(b)) => match op(*a, *b) {
+                Some(result) => Constant::Value(result),
+                None => Constant::Top,
+            },
+            _ => Constant::Top,
+        }
+    }
+}
+
+impl AbstractDomain for Constant {
+    fn bottom() -> Self {
+        Constant::Bottom
+    }
+
+    fn top() -> Self {
+        Constant::Top
+    }
+
+    fn is_bottom(&self) -> bool {
+        *self == Constant::Bottom
+    }
+
+    fn leq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Constant::Bottom, _) | (_, Constant::Top) => true,
+            (Constant::Value(a), Constant::Value(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Constant::Bottom, x) | (x, Constant::Bottom) => *x,
+            (Constant::Value(a), Constant::Value(b)) if a == b => *self,
+            _ => Constant::Top,
+        }
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Constant::Top, x) | (x, Constant::Top) => *x,
+            (Constant::Value(a), Constant::Value(b)) if a == b => *self,
+            _ => Constant::Bottom,
+        }
+    }
+}
+
+impl ValueDomain for Constant {
+    fn constant(value: i32) -> Self {
+        Constant::Value(value)
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        self.lift(other, i32::checked_add)
+    }
+
+    fn minus(&self, other: &Self) -> Self {
+        self.lift(other, i32::checked_sub)
+    }
+
+    fn product(&self, other: &Self) -> Self {
+        // 0 * ⊤ = 0 anche se l'altro operando non è noto
+        match (self, other) {
+            (Constant::Value(0), Constant::Top) | (Constant::Top, Constant::Value(0)) => {
+                Constant::Value(0)
+            }
+            _ => self.lift(other, i32::checked_mul),
+        }
+    }
+
+    fn divide(&self, other: &Self) -> Self {
+        match (self, other) {
+            // la divisione per zero blocca l'esecuzione concreta
+            (_, Constant::Value(0)) => Constant::Bottom,
+            (Constant::Bottom, _) | (_, Constant::Bottom) => Constant::Bottom,
+            (Constant::Value(a), Constant::Value(b)) => match a.checked_div(*b) {
+                Some(result) => Constant::Value(result),
+                None => Constant::Top,
+            },
+            _ => Constant::Top,
+        }
+    }
+
+    fn uminus(&self) -> Self {
+        match self {
+            Constant::Value(a) => match a.checked_neg() {
+                Some(result) => Constant::Value(result),
+                None => Constant::Top,
+            },
+            _ => *self,
+        }
+    }
+
+    fn refine(op: Comparison, left: &Self, right: &Self) -> (Self, Self) {
+        match (left, right) {
+            (Constant::Bottom, _) | (_, Constant::Bottom) => (Constant::Bottom, Constant::Bottom),
+            (Constant::Value(a), Constant::Value(b)) => {
+                if op.holds(*a as i64, *b as i64) {
+                    (*left, *right)
+                } else {
+                    (Constant::Bottom, Constant::Bottom)
+                }
+            }
+            // x = n rende x costante
+            _ if op == Comparison::Equal => {
+                let common = left.meet(right);
+                (common, common)
+            }
+            _ => (*left, *right),
+        }
+    }
+
+    fn as_constant(&self) -> Option<i32> {
+        match self {
+            Constant::Value(a) => Some(*a),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Constant {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Constant::Bottom => write!(f, "⊥"),
+            Constant::Value(a) => write!(f, "{{{}}}", a),
+            Constant::Top => write!(f, "⊤"),
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use std::cmp::{max, min};
+
+// Matrice dei vincoli di differenza (DBM): l'elemento (i, j) è un limite superiore
+// di vⱼ - vᵢ, None rappresenta +∞. È la struttura comune a zone e ottagoni.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dbm {
+    size: usize,
+    bounds: Vec<Option<i64>>,
+}
+
+// Somma di due limiti; un overflow viene approssimato con +∞
+pub fn add_bounds(a: Option<i64>, b: Option<i64>) -> Option<i64> {
+    a?.checked_add(b?)
+}
+
+// Ordine sui limiti, in cui None (+∞) è il massimo
+pub fn bound_leq(a: Option<i64>, b: Option<i64>) -> bool {
+    match (a, b) {
+        (_, None) => true,
+        (None, Some(_)) => false,
+        (Some(a), Some(b)) => a <= b,
+    }
+}
+
+pub fn min_bound(a: Option<i64>, b: Option<i64>) -> Option<i64> {
+    match (a, b) {
+        (None, x) | (x, None) => x,
+        (Some(a), Some(b)) => Some(min(a, b)),
+    }
+}
+
+pub fn max_bound(a: Option<i64>, b: Option<i64>) -> Option<i64> {
+    match (a, b) {
+        (None, _) | (_, None) => None,
+        (Some(a), Some(b)) => Some(max(a, b)),
+    }
+}
+
+impl Dbm {
+    // Matrice senza vincoli, a parte la diagonale nulla
+    pub fn new(size: usize) -> Self {
+        let mut dbm = Dbm {
+            size,
+            bounds: vec![None; size * size],
+        };
+        for i in 0..size {
+            dbm.set(i, i, Some(0));
+        }
+        dbm
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn get(&self, i: usize, j: usize) -> Option<i64> {
+        self.bounds[i * self.size + j]
+    }
+
+    pub fn set(&mut self, i: usize, j: usize, bound: Option<i64>) {
+        self.bounds[i * self.size + j] = bound;
+    }
+
+    // Aggiunge il vincolo vⱼ - vᵢ ≤ bound tenendo il più stretto
+    pub fn constrain(&mut self, i: usize, j: usize, bound: i64) {
+        let current = self.get(i, j);
+        self.set(i, j, min_bound(current, Some(bound)));
+    }
+
+    // Copia la matrice su un nuovo insieme di indici: mapping[k] è la posizione
+    // del vecchio indice k nella nuova matrice di dimensione `size`
+    pub fn remap(&self, size: usize, mapping: &[usize]) -> Dbm {
+        let mut result = Dbm::new(size);
+        for i in 0..self.size {
+            for j in 0..self.size {
+                result.set(mapping[i], mapping[j], self.get(i, j));
+            }
+        }
+        result
+    }
+
+    // Elimina tutti i vincoli che coinvolgono l'indice k
+    pub fn forget(&mut self, k: usize) {
+        for i in 0..self.size {
+            if i != k {
+                self.set(i, k, None);
+                self.set(k, i, None);
+            }
+        }
+    }
+
+    // Chiusura per cammini minimi (Floyd–Warshall)
+    pub fn close(&mut self) {
+        for k in 0..self.size {
+            for i in 0..self.size {
+                let ik = self.get(i, k);
+                if ik.is_none() {
+                    continue;
+                }
+                for j in 0..self.size {
+                    let through_k = add_bounds(ik, self.get(k, j));
+                    if !bound_leq(self.get(i, j), through_k) {
+                        self.set(i, j, through_k);
+                    }
+                }
+            }
+        }
+    }
+
+    // Dopo la chiusura un ciclo negativo compare come diagonale negativa
+    pub fn is_empty(&self) -> bool {
+        (0..self.size).any(|i| matches!(self.get(i, i), Some(d) if d < 0))
+    }
+
+    pub fn leq(&self, other: &Dbm) -> bool {
+        self.bounds
+            .iter()
+            .zip(other.bounds.iter())
+            .all(|(a, b)| bound_leq(*a, *b))
+    }
+
+    pub fn join(&self, other: &Dbm) -> Dbm {
+        self.pointwise(other, max_bound)
+    }
+
+    pub fn meet(&self, other: &Dbm) -> Dbm {
+        self.pointwise(other, min_bound)
+    }
+
+    // Widening standard: si tengono solo i vincoli stabili
+    pub fn widening(&self, other: &Dbm) -> Dbm {
+        self.pointwise(other, |a, b| if bound_leq(b, a) { a } else { None })
+    }
+
+    fn pointwise(&self, other: &Dbm, op: impl Fn(Option<i64>, Option<i64>) -> Option<i64>) -> Dbm {
+        Dbm {
+            size: self.size,
+            bounds: self
+                .bounds
+                .iter()
+                .zip(other.bounds.iter())
+                .map(|(a, b)| op(*a, *b))
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,436 @@
+use crate::analyzer::AbstractState;
+use crate::cfg::{Action, Cfg, Edge, Point};
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::time::{Duration, Instant};
+
+// Componente di un ordinamento topologico debole: un singolo punto oppure
+// una componente fortemente connessa con la sua testa e le sottocomponenti
+#[derive(Debug, Clone, PartialEq)]
+pub enum Component {
+    Vertex(Point),
+    Cycle(Point, Vec<Component>),
+}
+
+// Ordinamento topologico debole (WTO) di Bourdoncle
+#[derive(Debug, Clone, PartialEq)]
+pub struct Wto {
+    pub components: Vec<Component>,
+}
+
+struct WtoBuilder<'a, 'b> {
+    cfg: &'b Cfg<'a>,
+    dfn: Vec<usize>,
+    num: usize,
+    stack: Vec<Point>,
+}
+
+impl WtoBuilder<'_, '_> {
+    // Visita in profondità: restituisce il numero più piccolo raggiungibile
+    // e inserisce in testa a `partition` le componenti chiuse in v
+    fn visit(&mut self, v: Point, partition: &mut Vec<Component>) -> usize {
+        self.stack.push(v);
+        self.num += 1;
+        self.dfn[v] = self.num;
+        let mut head = self.num;
+        let mut is_loop = false;
+        let successors: Vec<Point> = self.cfg.successors(v).map(|e| e.to).collect();
+        for w in successors {
+            let min = if self.dfn[w] == 0 {
+                self.visit(w, partition)
+            } else {
+                self.dfn[w]
+            };
+            if min <= head {
+                head = min;
+                is_loop = true;
+            }
+        }
+        if head == self.dfn[v] {
+            self.dfn[v] = usize::MAX;
+            let mut element = self.stack.pop().unwrap();
+            if is_loop {
+                while element != v {
+                    self.dfn[element] = 0;
+                    element = self.stack.pop().unwrap();
+                }
+                let component = self.component(v);
+                partition.insert(0, component);
+            } else {
+                partition.insert(0, Component::Vertex(v));
+            }
+        }
+        head
+    }
+
+    fn component(&mut self, v: Point) -> Component {
+        let mut partition = Vec::new();
+        let successors: Vec<Point> = self.cfg.successors(v).map(|e| e.to).collect();
+        for w in successors {
+            if self.dfn[w] == 0 {
+                self.visit(w, &mut partition);
+            }
+        }
+        Component::Cycle(v, partition)
+    }
+}
+
+impl Wto {
+    pub fn new(cfg: &Cfg) -> Self {
+        let mut builder = WtoBuilder {
+            cfg,
+            dfn: vec![0; cfg.points()],
+            num: 0,
+            stack: Vec::new(),
+        };
+        let mut components = Vec::new();
+        builder.visit(cfg.entry(), &mut components);
+        Wto { components }
+    }
+
+    // Tutti i punti in ordine, teste comprese
+    pub fn points(&self) -> Vec<Point> {
+        let mut result = Vec::new();
+        flatten(&self.components, &mut result);
+        result
+    }
+
+    // Teste delle componenti che contengono ciascun punto, dalla più esterna
+    fn nesting(&self, size: usize) -> Vec<Vec<Point>> {
+        fn walk(components: &[Component], heads: &mut Vec<Point>, result: &mut Vec<Vec<Point>>) {
+            for c in components {
+                match c {
+                    Component::Vertex(v) => result[*v] = heads.clone(),
+                    Component::Cycle(h, body) => {
+                        heads.push(*h);
+                        result[*h] = heads.clone();
+                        walk(body, heads, result);
+                        heads.pop();
+                    }
+                }
+            }
+        }
+        let mut result = vec![Vec::new(); size];
+        walk(&self.components, &mut Vec::new(), &mut result);
+        result
+    }
+}
+
+fn flatten(components: &[Component], result: &mut Vec<Point>) {
+    for c in components {
+        match c {
+            Component::Vertex(v) => result.push(*v),
+            Component::Cycle(h, body) => {
+                result.push(*h);
+                flatten(body, result);
+            }
+        }
+    }
+}
+
+// Notazione di Bourdoncle: le componenti sono tra parentesi, la testa per prima
+impl Display for Component {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Component::Vertex(v) => write!(f, "{}", v),
+            Component::Cycle(h, body) => {
+                write!(f, "({}", h)?;
+                for c in body {
+                    write!(f, " {}", c)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+impl Display for Wto {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let entries: Vec<String> = self.components.iter().map(|c| c.to_string()).collect();
+        write!(f, "{}", entries.join(" "))
+    }
+}
+
+// Strategie di iterazione di Bourdoncle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    // ogni componente interna viene stabilizzata prima di ricalcolare la testa esterna
+    Recursive,
+    // ogni componente viene ripercorsa per intero finché non si stabilizza
+    Iterative,
+    // tutto il grafo viene ripercorso nell'ordine del WTO finché non cambia più nulla
+    RoundRobin,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoopStatistics {
+    pub head: Point,
+    pub iterations: usize,
+    pub widenings: usize,
+    pub time: Duration,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Statistics {
+    // aggiornamenti di punti, compresi quelli della fase discendente
+    pub iterations: usize,
+    pub widenings: usize,
+    // una voce per ogni testa di componente; i cicli esterni includono quelli interni
+    pub loops: Vec<LoopStatistics>,
+}
+
+impl Display for Statistics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "iterations: {}, widenings: {}", self.iterations, self.widenings)?;
+        for l in &self.loops {
+            write!(
+                f,
+                "\n  loop at {}: iterations: {}, widenings: {}, time: {:?}",
+                l.head, l.iterations, l.widenings, l.time
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// Risolutore a worklist sul grafo di controllo: calcola uno stato astratto per ogni
+// punto di programma seguendo il WTO, con widening solo nelle teste delle componenti
+// (dopo `widening_delay` visite) seguito da `narrowing_steps` passi discendenti.
+pub struct Solver<'a, 'b, S> {
+    cfg: &'b Cfg<'a>,
+    wto: Wto,
+    pub strategy: Strategy,
+    pub widening_delay: usize,
+    pub narrowing_steps: usize,
+    states: Vec<S>,
+    initial: S,
+    visits: Vec<usize>,
+    nesting: Vec<Vec<Point>>,
+    statistics: Statistics,
+}
+
+impl<'a, 'b, S: AbstractState> Solver<'a, 'b, S> {
+    pub fn new(cfg: &'b Cfg<'a>) -> Self {
+        let wto = Wto::new(cfg);
+        let nesting = wto.nesting(cfg.points());
+        Solver {
+            cfg,
+            wto,
+            strategy: Strategy::Recursive,
+            widening_delay: 2,
+            narrowing_steps: 2,
+            states: vec![S::bottom(); cfg.points()],
+            initial: S::bottom(),
+            visits: vec![0; cfg.points()],
+            nesting,
+            statistics: Statistics::default(),
+        }
+    }
+
+    pub fn with_strategy(mut self, strategy: Strategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    pub fn statistics(&self) -> &Statistics {
+        &self.statistics
+    }
+
+    pub fn state_at(&self, point: Point) -> &S {
+        &self.states[point]
+    }
+
+    pub fn exit_state(&self) -> &S {
+        &self.states[self.cfg.exit()]
+    }
+
+    pub fn solve(&mut self, initial: &S) -> &S {
+        self.initial = initial.clone();
+        self.states = vec![S::bottom(); self.cfg.points()];
+        self.visits = vec![0; self.cfg.points()];
+        self.statistics = Statistics {
+            iterations: 0,
+            widenings: 0,
+            loops: self
+                .cfg
+                .loop_heads()
+                .map(|(head, _)| LoopStatistics {
+                    head,
+                    iterations: 0,
+                    widenings: 0,
+                    time: Duration::ZERO,
+                })
+                .collect(),
+        };
+        let components = self.wto.components.clone();
+        match self.strategy {
+            Strategy::Recursive => {
+                for c in &components {
+                    self.recursive(c);
+                }
+            }
+            Strategy::Iterative => {
+                for c in &components {
+                    self.iterative(c);
+                }
+            }
+            Strategy::RoundRobin => {
+                let order = self.wto.points();
+                while self.pass(&order, true) {}
+            }
+        }
+        // con la strategia ricorsiva la fase discendente è già fatta componente per componente
+        if self.strategy != Strategy::Recursive {
+            let order = self.wto.points();
+            for _ in 0..self.narrowing_steps {
+                if !self.pass(&order, false) {
+                    break;
+                }
+            }
+        }
+        self.exit_state()
+    }
+
+    fn recursive(&mut self, component: &Component) {
+        match component {
+            Component::Vertex(v) => {
+                self.update(*v, true);
+            }
+            // come l'interprete sull'AST, a ogni ingresso la componente riparte da ⊥:
+            // un ciclo interno non eredita i widening fatti con stati d'ingresso vecchi
+            Component::Cycle(head, body) => {
+                self.restart(std::slice::from_ref(component));
+                self.update(*head, true);
+                loop {
+                    for c in body {
+                        self.recursive(c);
+                    }
+                    if !self.update(*head, true) {
+                        break;
+                    }
+                }
+                // fase discendente locale: quando la testa migliora il corpo viene
+                // ricalcolato da capo, così anche i cicli interni ne approfittano
+                for _ in 0..self.narrowing_steps {
+                    if !self.update(*head, false) {
+                        break;
+                    }
+                    self.restart(body);
+                    for c in body {
+                        self.recursive(c);
+                    }
+                }
+            }
+        }
+    }
+
+    fn restart(&mut self, components: &[Component]) {
+        let mut points = Vec::new();
+        flatten(components, &mut points);
+        for v in points {
+            self.states[v] = S::bottom();
+            self.visits[v] = 0;
+        }
+    }
+
+    fn iterative(&mut self, component: &Component) {
+        match component {
+            Component::Vertex(v) => {
+                self.update(*v, true);
+            }
+            Component::Cycle(..) => {
+                let mut order = Vec::new();
+                flatten(std::slice::from_ref(component), &mut order);
+                while self.pass(&order, true) {}
+            }
+        }
+    }
+
+    // Aggiorna i punti in ordine; restituisce true se qualcosa è cambiato
+    fn pass(&mut self, order: &[Point], ascending: bool) -> bool {
+        let mut changed = false;
+        for v in order {
+            changed |= self.update(*v, ascending);
+        }
+        changed
+    }
+
+    // Effetto di un arco sullo stato del suo punto di partenza. Come
+    // nell'interprete, alla fine di ciascun ramo di un if si registra il ramo preso.
+    fn transfer(&self, edge: &Edge) -> S {
+        let mut state = self.states[edge.from].clone();
+        match edge.action {
+            Action::Assign(a) => state.assign(&a.var_name, &*a.expr),
+            Action::Assume(guard, polarity) => state.assume(guard, polarity),
+            Action::Skip => {}
+        }
+        if let Some((i, taken)) = self.cfg.branch_of(edge) {
+            state.record_branch(i, taken);
+        }
+        state
+    }
+
+    // Ricalcola lo stato di v dai predecessori. Nella fase ascendente le teste
+    // accumulano con join e poi con widening; in quella discendente il nuovo
+    // valore sostituisce il vecchio solo se è più preciso.
+    fn update(&mut self, v: Point, ascending: bool) -> bool {
+        let start = Instant::now();
+        let mut value = if v == self.cfg.entry() {
+            self.initial.clone()
+        } else {
+            S::bottom()
+        };
+        for edge in self.cfg.predecessors(v) {
+            value = value.join(&self.transfer(edge));
+        }
+        for stmt in self.cfg.statements_ending_at(v) {
+            value.merge_point(stmt);
+        }
+        let old = self.states[v].clone();
+        let is_head = self.nesting[v].last() == Some(&v);
+        let changed = if ascending {
+            if value.leq(&old) {
+                false
+            } else {
+                let mut widened = false;
+                self.states[v] = if is_head {
+                    self.visits[v] += 1;
+                    if self.visits[v] > self.widening_delay {
+                        widened = true;
+                        old.widening(&value)
+                    } else {
+                        old.join(&value)
+                    }
+                } else {
+                    value
+                };
+                if widened {
+                    self.statistics.widenings += 1;
+                    self.record(v, |l| l.widenings += 1);
+                }
+                true
+            }
+        } else if value.leq(&old) && !old.leq(&value) {
+            self.states[v] = value;
+            true
+        } else {
+            false
+        };
+        self.statistics.iterations += 1;
+        let elapsed = start.elapsed();
+        self.record(v, |l| {
+            l.iterations += 1;
+            l.time += elapsed;
+        });
+        changed
+    }
+
+    // Aggiorna le statistiche di tutti i cicli che contengono v
+    fn record(&mut self, v: Point, f: impl Fn(&mut LoopStatistics)) {
+        for head in &self.nesting[v] {
+            if let Some(l) = self.statistics.loops.iter_mut().find(|l| l.head == *head) {
+                f(l);
+            }
+        }
+    }
+}
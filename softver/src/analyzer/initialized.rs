@@ -0,0 +1,103 @@
+use crate::analyzer::{AbstractDomain, AbstractState, Comparison};
+use crate::ast::arithmetic::ArithmeticExpression;
+use crate::ast::State;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+// Variabili sicuramente inizializzate: assegnate su tutti i cammini che arrivano
+// al punto. Più variabili vuol dire più informazione, quindi l'ordine è l'inclusione
+// rovesciata, il join è l'intersezione e top è l'insieme vuoto.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Initialized {
+    vars: BTreeSet<String>,
+    bottom: bool,
+}
+
+impl Initialized {
+    pub fn contains(&self, var: &str) -> bool {
+        self.bottom || self.vars.contains(var)
+    }
+}
+
+impl AbstractDomain for Initialized {
+    fn bottom() -> Self {
+        Initialized {
+            vars: BTreeSet::new(),
+            bottom: true,
+        }
+    }
+
+    fn top() -> Self {
+        Initialized {
+            vars: BTreeSet::new(),
+            bottom: false,
+        }
+    }
+
+    fn is_bottom(&self) -> bool {
+        self.bottom
+    }
+
+    fn leq(&self, other: &Self) -> bool {
+        self.bottom || (!other.bottom && self.vars.is_superset(&other.vars))
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        if self.bottom {
+            return other.clone();
+        }
+        if other.bottom {
+            return self.clone();
+        }
+        Initialized {
+            vars: self.vars.intersection(&other.vars).cloned().collect(),
+            bottom: false,
+        }
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        if self.bottom || other.bottom {
+            return Self::bottom();
+        }
+        Initialized {
+            vars: self.vars.union(&other.vars).cloned().collect(),
+            bottom: false,
+        }
+    }
+}
+
+impl AbstractState for Initialized {
+    // Le variabili definite dal file di stato iniziale
+    fn from_state(state: &State) -> Self {
+        Initialized {
+            vars: state.keys().cloned().collect(),
+            bottom: false,
+        }
+    }
+
+    fn assign(&mut self, var: &str, _expr: &dyn ArithmeticExpression) {
+        if !self.bottom {
+            self.vars.insert(var.to_string());
+        }
+    }
+
+    // Le guardie non inizializzano nulla
+    fn assume_comparison(
+        &mut self,
+        _op: Comparison,
+        _left: &dyn ArithmeticExpression,
+        _right: &dyn ArithmeticExpression,
+    ) {
+    }
+}
+
+impl Display for Initialized {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.bottom {
+            return write!(f, "⊥");
+        }
+        let vars: Vec<&str> = self.vars.iter().map(|v| v.as_str()).collect();
+        write!(f, "{{ {} }}", vars.join(", "))
+    }
+}
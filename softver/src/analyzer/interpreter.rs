@@ -0,0 +1,133 @@
+use crate::analyzer::AbstractState;
+use crate::ast::statement::{Statement, StatementNode, While};
+
+// Interprete astratto che segue la struttura dell'AST.
+// Sui while calcola il punto fisso con widening (dopo `widening_delay` iterazioni)
+// e poi lo raffina con `narrowing_steps` iterazioni discendenti.
+pub struct Interpreter<S> {
+    pub widening_delay: usize,
+    pub narrowing_steps: usize,
+    // invarianti in testa ai cicli, nell'ordine in cui i while compaiono nel programma
+    loop_invariants: Vec<(*const While, S)>,
+}
+
+impl<S: AbstractState> Default for Interpreter<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: AbstractState> Interpreter<S> {
+    pub fn new() -> Self {
+        Interpreter {
+            widening_delay: 2,
+            narrowing_steps: 2,
+            loop_invariants: Vec::new(),
+        }
+    }
+
+    pub fn run(&mut self, stmt: &dyn Statement, pre: &S) -> S {
+        if pre.is_bottom() {
+            return S::bottom();
+        }
+        let mut post = match stmt.node() {
+            StatementNode::Assign(a) => {
+                let mut post = pre.clone();
+                post.assign(&a.var_name, &*a.expr);
+                post
+            }
+            StatementNode::Skip(_) => pre.clone(),
+            StatementNode::Concat(c) => {
+                let middle = self.run(&*c.first, pre);
+                self.run(&*c.second, &middle)
+            }
+            StatementNode::IfThenElse(i) => {
+                let mut then_pre = pre.clone();
+                then_pre.assume(&*i.guard, true);
+                let mut else_pre = pre.clone();
+                else_pre.assume(&*i.guard, false);
+                let mut then_post = self.run(&*i.true_expr, &then_pre);
+                then_post.record_branch(i, true);
+                let mut else_post = self.run(&*i.false_expr, &else_pre);
+                else_post.record_branch(i, false);
+                then_post.join(&else_post)
+            }
+            StatementNode::While(w) => {
+                let mut post = self.loop_invariant(w, pre);
+                post.assume(&*w.guard, false);
+                post
+            }
+            // dopo un assert proseguono solo le esecuzioni che lo soddisfano
+            StatementNode::Assert(a) => {
+                let mut post = pre.clone();
+                post.assume(&*a.condition, true);
+                post
+            }
+            StatementNode::Assume(a) => {
+                let mut post = pre.clone();
+                post.assume(&*a.condition, true);
+                post
+            }
+        };
+        post.merge_point(stmt);
+        post
+    }
+
+    // Invariante in testa al ciclo partendo dallo stato `pre`
+    pub fn loop_invariant(&mut self, w: &While, pre: &S) -> S {
+        // prenota la posizione così i cicli esterni precedono quelli annidati
+        if self.invariant_of(w).is_none() {
+            self.loop_invariants.push((w as *const While, S::bottom()));
+        }
+        let mut invariant = pre.clone();
+        let mut iteration = 0;
+        loop {
+            let next = pre.join(&self.iterate(w, &invariant));
+            if next.leq(&invariant) {
+                break;
+            }
+            invariant = if iteration < self.widening_delay {
+                invariant.join(&next)
+            } else {
+                invariant.widening(&next)
+            };
+            iteration += 1;
+        }
+        for _ in 0..self.narrowing_steps {
+            let next = pre.join(&self.iterate(w, &invariant));
+            if invariant.leq(&next) {
+                break;
+            }
+            invariant = next;
+        }
+        self.record(w, &invariant);
+        invariant
+    }
+
+    // Un'iterazione del corpo a partire dall'invariante corrente
+    fn iterate(&mut self, w: &While, invariant: &S) -> S {
+        let mut body_pre = invariant.clone();
+        body_pre.assume(&*w.guard, true);
+        self.run(&*w.body, &body_pre)
+    }
+
+    fn record(&mut self, w: &While, invariant: &S) {
+        let key = w as *const While;
+        if let Some(entry) = self.loop_invariants.iter_mut().find(|(k, _)| *k == key) {
+            entry.1 = invariant.clone();
+        }
+    }
+
+    pub fn loop_invariants(&self) -> impl Iterator<Item = &S> {
+        self.loop_invariants.iter().map(|(_, invariant)| invariant)
+    }
+
+    // Invariante calcolato per uno specifico while, se è stato raggiunto
+    pub fn invariant_of(&self, w: &While) -> Option<&S> {
+        let key = w as *const While;
+        self.loop_invariants
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, invariant)| invariant)
+    }
+}
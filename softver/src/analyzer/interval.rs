@@ -0,0 +1,340 @@
+use crate::analyzer::{AbstractDomain, Comparison, ValueDomain};
+use std::cmp::{max, min};
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+// Estremo di un intervallo: gli interi matematici sono approssimati con i64,
+// e un risultato che esce da i64 diventa l'infinito con il segno corrispondente.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Bound {
+    NegInf,
+    Finite(i64),
+    PosInf,
+}
+
+impl Bound {
+    pub fn from_i128(value: i128) -> Bound {
+        match i64::try_from(value) {
+            Ok(v) => Bound::Finite(v),
+            Err(_) if value < 0 => Bound::NegInf,
+            Err(_) => Bound::PosInf,
+        }
+    }
+
+    fn signum(self) -> i64 {
+        match self {
+            Bound::NegInf => -1,
+            Bound::Finite(v) => v.signum(),
+            Bound::PosInf => 1,
+        }
+    }
+
+    fn infinity(sign: i64) -> Bound {
+        if sign < 0 {
+            Bound::NegInf
+        } else {
+            Bound::PosInf
+        }
+    }
+
+    // Somma di estremi; ∞ + (-∞) non si presenta mai sugli estremi omologhi
+    pub fn add(self, other: Bound) -> Bound {
+        match (self, other) {
+            (Bound::Finite(a), Bound::Finite(b)) => Bound::from_i128(a as i128 + b as i128),
+            (Bound::Finite(_), inf) | (inf, _) => inf,
+        }
+    }
+
+    pub fn neg(self) -> Bound {
+        match self {
+            Bound::NegInf => Bound::PosInf,
+            Bound::Finite(v) => Bound::from_i128(-(v as i128)),
+            Bound::PosInf => Bound::NegInf,
+        }
+    }
+
+    pub fn mul(self, other: Bound) -> Bound {
+        match (self, other) {
+            (Bound::Finite(a), Bound::Finite(b)) => Bound::from_i128(a as i128 * b as i128),
+            // 0 * ∞ = 0: i valori concreti sono sempre finiti
+            _ if self.signum() == 0 || other.signum() == 0 => Bound::Finite(0),
+            _ => Bound::infinity(self.signum() * other.signum()),
+        }
+    }
+
+    // Divisione troncata verso lo zero, come in Rust; il divisore non è mai 0
+    fn div(self, other: Bound) -> Bound {
+        match (self, other) {
+            (Bound::Finite(a), Bound::Finite(b)) => Bound::from_i128(a as i128 / b as i128),
+            (Bound::Finite(_), _) => Bound::Finite(0),
+            // limite di x/y quando y cresce più in fretta di x
+            (_, Bound::NegInf) | (_, Bound::PosInf) => Bound::Finite(0),
+            (inf, _) => Bound::infinity(inf.signum() * other.signum()),
+        }
+    }
+}
+
+impl Display for Bound {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Bound::NegInf => write!(f, "-∞"),
+            Bound::Finite(v) => write!(f, "{}", v),
+            Bound::PosInf => write!(f, "+∞"),
+        }
+    }
+}
+
+// Dominio degli intervalli [low, high] con estremi eventualmente infiniti
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interval {
+    Bottom,
+    Range { low: Bound, high: Bound },
+}
+
+impl Interval {
+    pub fn new(low: i64, high: i64) -> Interval {
+        Interval::range(Bound::Finite(low), Bound::Finite(high))
+    }
+
+    // Intervallo normalizzato: vuoto se gli estremi si incrociano
+    pub fn range(low: Bound, high: Bound) -> Interval {
+        if low > high || low == Bound::PosInf || high == Bound::NegInf {
+            Interval::Bottom
+        } else {
+            Interval::Range { low, high }
+        }
+    }
+
+    pub fn low(&self) -> Option<Bound> {
+        match self {
+            Interval::Bottom => None,
+            Interval::Range { low, .. } => Some(*low),
+        }
+    }
+
+    pub fn high(&self) -> Option<Bound> {
+        match self {
+            Interval::Bottom => None,
+            Interval::Range { high, .. } => Some(*high),
+        }
+    }
+
+    // Hull degli estremi ottenuti combinando gli angoli dei due intervalli
+    fn corners(&self, other: &Interval, op: impl Fn(Bound, Bound) -> Bound) -> Interval {
+        match (self, other) {
+            (
+                Interval::Range {
+                    low: l1,
+                    high: h1,
+                },
+                Interval::Range {
+                    low: l2,
+                    high: h2,
+                },
+            ) => {
+                let values = [op(*l1, *l2), op(*l1, *h2), op(*h1, *l2), op(*h1, *h2)];
+                Interval::range(
+                    values.iter().copied().min().unwrap(),
+                    values.iter().copied().max().unwrap(),
+                )
+            }
+            _ => Interval::Bottom,
+        }
+    }
+
+    // Divisione per un intervallo che non contiene lo zero
+    fn divide_nonzero(&self, divisor: &Interval) -> Interval {
+        self.corners(divisor, Bound::div)
+    }
+}
+
+impl AbstractDomain for Interval {
+    fn bottom() -> Self {
+        Interval::Bottom
+    }
+
+    fn top() -> Self {
+        Interval::Range {
+            low: Bound::NegInf,
+            high: Bound::PosInf,
+        }
+    }
+
+    fn is_bottom(&self) -> bool {
+        *self == Interval::Bottom
+    }
+
+    fn leq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Interval::Bottom, _) => true,
+            (_, Interval::Bottom) => false,
+            (
+                Interval::Range {
+                    low: l1,
+                    high: h1,
+                },
+                Interval::Range {
+                    low: l2,
+                    high: h2,
+                },
+            ) => l2 <= l1 && h1 <= h2,
+        }
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Interval::Bottom, x) | (x, Interval::Bottom) => *x,
+            (
+                Interval::Range {
+                    low: l1,
+                    high: h1,
+                },
+                Interval::Range {
+                    low: l2,
+                    high: h2,
+                },
+            ) => Interval::range(*min(l1, l2), *max(h1, h2)),
+        }
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Interval::Bottom, _) | (_, Interval::Bottom) => Interval::Bottom,
+            (
+                Interval::Range {
+                    low: l1,
+                    high: h1,
+                },
+                Interval::Range {
+                    low: l2,
+                    high: h2,
+                },
+            ) => Interval::range(*max(l1, l2), *min(h1, h2)),
+        }
+    }
+
+    // Widening standard: gli estremi instabili vanno all'infinito
+    fn widening(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Interval::Bottom, x) | (x, Interval::Bottom) => *x,
+            (
+                Interval::Range {
+                    low: l1,
+                    high: h1,
+                },
+                Interval::Range {
+                    low: l2,
+                    high: h2,
+                },
+            ) => Interval::range(
+                if l2 < l1 { Bound::NegInf } else { *l1 },
+                if h2 > h1 { Bound::PosInf } else { *h1 },
+            ),
+        }
+    }
+}
+
+impl ValueDomain for Interval {
+    fn constant(value: i32) -> Self {
+        Interval::new(value as i64, value as i64)
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        match (self, other) {
+            (
+                Interval::Range {
+                    low: l1,
+                    high: h1,
+                },
+                Interval::Range {
+                    low: l2,
+                    high: h2,
+                },
+            ) => Interval::range(l1.add(*l2), h1.add(*h2)),
+            _ => Interval::Bottom,
+        }
+    }
+
+    fn minus(&self, other: &Self) -> Self {
+        self.add(&other.uminus())
+    }
+
+    fn product(&self, other: &Self) -> Self {
+        self.corners(other, Bound::mul)
+    }
+
+    fn divide(&self, other: &Self) -> Self {
+        // il divisore si spezza nella parte negativa e in quella positiva: lo zero blocca
+        let negative = other.meet(&Interval::range(Bound::NegInf, Bound::Finite(-1)));
+        let positive = other.meet(&Interval::range(Bound::Finite(1), Bound::PosInf));
+        self.divide_nonzero(&negative)
+            .join(&self.divide_nonzero(&positive))
+    }
+
+    fn uminus(&self) -> Self {
+        match self {
+            Interval::Bottom => Interval::Bottom,
+            Interval::Range { low, high } => Interval::range(high.neg(), low.neg()),
+        }
+    }
+
+    fn refine(op: Comparison, left: &Self, right: &Self) -> (Self, Self) {
+        let (Some(l_low), Some(r_high)) = (left.low(), right.high()) else {
+            return (Interval::Bottom, Interval::Bottom);
+        };
+        let one = Bound::Finite(1);
+        let (l, r) = match op {
+            Comparison::Equal => (left.meet(right), left.meet(right)),
+            Comparison::NotEqual => (exclude(left, right), exclude(right, left)),
+            Comparison::LessEqual => (
+                left.meet(&Interval::range(Bound::NegInf, r_high)),
+                right.meet(&Interval::range(l_low, Bound::PosInf)),
+            ),
+            Comparison::Less => (
+                left.meet(&Interval::range(Bound::NegInf, r_high.add(one.neg()))),
+                right.meet(&Interval::range(l_low.add(one), Bound::PosInf)),
+            ),
+            Comparison::GreatEqual | Comparison::Great => {
+                let (r, l) = Interval::refine(op.flip(), right, left);
+                (l, r)
+            }
+        };
+        if l.is_bottom() || r.is_bottom() {
+            (Interval::Bottom, Interval::Bottom)
+        } else {
+            (l, r)
+        }
+    }
+
+    fn as_constant(&self) -> Option<i32> {
+        match self {
+            Interval::Range {
+                low: Bound::Finite(a),
+                high: Bound::Finite(b),
+            } if a == b => i32::try_from(*a).ok(),
+            _ => None,
+        }
+    }
+}
+
+// Toglie da `value` l'estremo uguale alla costante `excluded`, se è una costante
+fn exclude(value: &Interval, excluded: &Interval) -> Interval {
+    match (value, excluded.as_constant()) {
+        (Interval::Range { low, high }, Some(c)) => {
+            let c = Bound::Finite(c as i64);
+            let low = if *low == c { low.add(Bound::Finite(1)) } else { *low };
+            let high = if *high == c { high.add(Bound::Finite(-1)) } else { *high };
+            Interval::range(low, high)
+        }
+        _ => *value,
+    }
+}
+
+impl Display for Interval {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Interval::Bottom => write!(f, "⊥"),
+            Interval::Range { low, high } => write!(f, "[{},{}]", low, high),
+        }
+    }
+}
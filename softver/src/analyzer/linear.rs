@@ -0,0 +1,121 @@
+use crate::ast::arithmetic::{ArithmeticExpression, ArithmeticNode};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+// Espressione lineare Σ aᵢ·xᵢ + c a coefficienti interi.
+// I coefficienti nulli non vengono memorizzati.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinearExpression {
+    pub coefficients: BTreeMap<String, i64>,
+    pub constant: i64,
+}
+
+impl LinearExpression {
+    pub fn constant(value: i64) -> Self {
+        LinearExpression {
+            coefficients: BTreeMap::new(),
+            constant: value,
+        }
+    }
+
+    pub fn variable(name: &str) -> Self {
+        let mut coefficients = BTreeMap::new();
+        coefficients.insert(name.to_string(), 1);
+        LinearExpression {
+            coefficients,
+            constant: 0,
+        }
+    }
+
+    pub fn coefficient(&self, var: &str) -> i64 {
+        self.coefficients.get(var).copied().unwrap_or(0)
+    }
+
+    pub fn is_constant(&self) -> bool {
+        self.coefficients.is_empty()
+    }
+
+    // Somma di a·self e b·other; None in caso di overflow
+    pub fn combine(&self, a: i64, other: &Self, b: i64) -> Option<Self> {
+        let mut result = LinearExpression::constant(
+            a.checked_mul(self.constant)?
+                .checked_add(b.checked_mul(other.constant)?)?,
+        );
+        for (var, coefficient) in &self.coefficients {
+            result.add_term(var, a.checked_mul(*coefficient)?)?;
+        }
+        for (var, coefficient) in &other.coefficients {
+            result.add_term(var, b.checked_mul(*coefficient)?)?;
+        }
+        Some(result)
+    }
+
+    pub fn scale(&self, factor: i64) -> Option<Self> {
+        self.combine(factor, &LinearExpression::constant(0), 0)
+    }
+
+    fn add_term(&mut self, var: &str, coefficient: i64) -> Option<()> {
+        let value = self.coefficient(var).checked_add(coefficient)?;
+        if value == 0 {
+            self.coefficients.remove(var);
+        } else {
+            self.coefficients.insert(var.to_string(), value);
+        }
+        Some(())
+    }
+}
+
+// Traduce un'espressione aritmetica in forma lineare, se lo è.
+// Restituisce None per prodotti tra variabili, divisioni e overflow.
+pub fn linearize(expr: &dyn ArithmeticExpression) -> Option<LinearExpression> {
+    match expr.node() {
+        ArithmeticNode::Numeral(n) => Some(LinearExpression::constant(n.0 as i64)),
+        ArithmeticNode::Variable(v) => Some(LinearExpression::variable(&v.value)),
+        ArithmeticNode::Add(e) => linearize(&*e.left)?.combine(1, &linearize(&*e.right)?, 1),
+        ArithmeticNode::Minus(e) => linearize(&*e.left)?.combine(1, &linearize(&*e.right)?, -1),
+        ArithmeticNode::Uminus(e) => linearize(&*e.right)?.scale(-1),
+        ArithmeticNode::Product(e) => {
+            let left = linearize(&*e.left)?;
+            let right = linearize(&*e.right)?;
+            if left.is_constant() {
+                right.scale(left.constant)
+            } else if right.is_constant() {
+                left.scale(right.constant)
+            } else {
+                None
+            }
+        }
+        ArithmeticNode::Divide(_) => None,
+    }
+}
+
+impl Display for LinearExpression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut first = true;
+        for (var, coefficient) in &self.coefficients {
+            let sign = if *coefficient < 0 { "-" } else { "+" };
+            if first {
+                if *coefficient < 0 {
+                    write!(f, "-")?;
+                }
+            } else {
+                write!(f, " {} ", sign)?;
+            }
+            if coefficient.unsigned_abs() != 1 {
+                write!(f, "{}*", coefficient.unsigned_abs())?;
+            }
+            write!(f, "{}", var)?;
+            first = false;
+        }
+        if first {
+            write!(f, "{}", self.constant)
+        } else if self.constant > 0 {
+            write!(f, " + {}", self.constant)
+        } else if self.constant < 0 {
+            write!(f, " - {}", self.constant.unsigned_abs())
+        } else {
+            Ok(())
+        }
+    }
+}
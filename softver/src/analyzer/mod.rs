@@ -0,0 +1,187 @@
+pub mod affine;
+pub mod congruence;
+pub mod constant;
+pub mod dbm;
+pub mod fixpoint;
+pub mod initialized;
+pub mod interpreter;
+pub mod interval;
+pub mod linear;
+pub mod octagon;
+pub mod partition;
+pub mod polyhedron;
+pub mod powerset;
+pub mod product;
+pub mod rational;
+pub mod sign;
+pub mod value_state;
+pub mod zone;
+
+use crate::ast::arithmetic::ArithmeticExpression;
+use crate::ast::boolean::{BooleanExpression, BooleanNode};
+use crate::ast::statement::{IfThenElse, Statement};
+use crate::ast::State;
+use std::fmt::{Debug, Display};
+
+// Reticolo astratto: operazioni comuni a valori e stati astratti
+pub trait AbstractDomain: Clone + Debug + PartialEq + Display {
+    fn bottom() -> Self;
+    fn top() -> Self;
+    fn is_bottom(&self) -> bool;
+    fn leq(&self, other: &Self) -> bool;
+    fn join(&self, other: &Self) -> Self;
+    fn meet(&self, other: &Self) -> Self;
+
+    // Per i domini ad altezza finita il join è già un widening
+    fn widening(&self, other: &Self) -> Self {
+        self.join(other)
+    }
+}
+
+// Operatori di confronto delle guardie, con gli stessi nomi dei nodi in ast/boolean.rs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Great,
+    GreatEqual,
+}
+
+impl Comparison {
+    // Confronto che vale quando self è falso
+    pub fn negate(self) -> Comparison {
+        match self {
+            Comparison::Equal => Comparison::NotEqual,
+            Comparison::NotEqual => Comparison::Equal,
+            Comparison::Less => Comparison::GreatEqual,
+            Comparison::LessEqual => Comparison::Great,
+            Comparison::Great => Comparison::LessEqual,
+            Comparison::GreatEqual => Comparison::Less,
+        }
+    }
+
+    // Confronto equivalente con gli operandi scambiati
+    pub fn flip(self) -> Comparison {
+        match self {
+            Comparison::Equal => Comparison::Equal,
+            Comparison::NotEqual => Comparison::NotEqual,
+            Comparison::Less => Comparison::Great,
+            Comparison::LessEqual => Comparison::GreatEqual,
+            Comparison::Great => Comparison::Less,
+            Comparison::GreatEqual => Comparison::LessEqual,
+        }
+    }
+
+    pub fn holds(self, left: i64, right: i64) -> bool {
+        match self {
+            Comparison::Equal => left == right,
+            Comparison::NotEqual => left != right,
+            Comparison::Less => left < right,
+            Comparison::LessEqual => left <= right,
+            Comparison::Great => left > right,
+            Comparison::GreatEqual => left >= right,
+        }
+    }
+}
+
+// Scompone un confronto dell'AST in (operatore, sinistra, destra)
+pub fn comparison_of(
+    guard: &dyn BooleanExpression,
+) -> Option<(Comparison, &dyn ArithmeticExpression, &dyn ArithmeticExpression)> {
+    match guard.node() {
+        BooleanNode::Equal(e) => Some((Comparison::Equal, &*e.left, &*e.right)),
+        BooleanNode::Less(e) => Some((Comparison::Less, &*e.left, &*e.right)),
+        BooleanNode::LessEqual(e) => Some((Comparison::LessEqual, &*e.left, &*e.right)),
+        BooleanNode::Great(e) => Some((Comparison::Great, &*e.left, &*e.right)),
+        BooleanNode::GreatEqual(e) => Some((Comparison::GreatEqual, &*e.left, &*e.right)),
+        _ => None,
+    }
+}
+
+// Dominio non relazionale dei valori di una singola variabile
+pub trait ValueDomain: AbstractDomain {
+    fn constant(value: i32) -> Self;
+    fn add(&self, other: &Self) -> Self;
+    fn minus(&self, other: &Self) -> Self;
+    fn product(&self, other: &Self) -> Self;
+    fn divide(&self, other: &Self) -> Self;
+    fn uminus(&self) -> Self;
+
+    // Raffina gli operandi sapendo che `left op right` vale.
+    // Di default non si impara nulla oltre al caso banale del bottom.
+    fn refine(_op: Comparison, left: &Self, right: &Self) -> (Self, Self) {
+        if left.is_bottom() || right.is_bottom() {
+            (Self::bottom(), Self::bottom())
+        } else {
+            (left.clone(), right.clone())
+        }
+    }
+
+    // Il valore concreto rappresentato, se è uno solo
+    fn as_constant(&self) -> Option<i32> {
+        None
+    }
+}
+
+// Stato astratto: quello che l'interprete astratto manipola statement per statement
+pub trait AbstractState: AbstractDomain {
+    // Astrazione dello stato iniziale concreto letto dal file di stato
+    fn from_state(state: &State) -> Self;
+    fn assign(&mut self, var: &str, expr: &dyn ArithmeticExpression);
+    fn assume_comparison(
+        &mut self,
+        op: Comparison,
+        left: &dyn ArithmeticExpression,
+        right: &dyn ArithmeticExpression,
+    );
+
+    // Filtra lo stato con la guardia (o con la sua negazione se polarity è false)
+    fn assume(&mut self, guard: &dyn BooleanExpression, polarity: bool) {
+        if self.is_bottom() {
+            return;
+        }
+        if let Some((op, left, right)) = comparison_of(guard) {
+            let op = if polarity { op } else { op.negate() };
+            self.assume_comparison(op, left, right);
+            return;
+        }
+        match guard.node() {
+            BooleanNode::Boolean(b) if b.0 != polarity => *self = Self::bottom(),
+            BooleanNode::Not(n) => self.assume(&*n.expression, !polarity),
+            // congiunzione: (a && b) vero oppure (a || b) falso
+            BooleanNode::And(a) if polarity => {
+                self.assume(&*a.left, true);
+                self.assume(&*a.right, true);
+            }
+            BooleanNode::Or(o) if !polarity => {
+                self.assume(&*o.left, false);
+                self.assume(&*o.right, false);
+            }
+            // disgiunzione: si analizzano i due casi e si fa il join
+            BooleanNode::And(a) => self.assume_either(&*a.left, &*a.right, false),
+            BooleanNode::Or(o) => self.assume_either(&*o.left, &*o.right, true),
+            _ => {}
+        }
+    }
+
+    fn assume_either(
+        &mut self,
+        left: &dyn BooleanExpression,
+        right: &dyn BooleanExpression,
+        polarity: bool,
+    ) {
+        let mut first = self.clone();
+        first.assume(left, polarity);
+        let mut second = self.clone();
+        second.assume(right, polarity);
+        *self = first.join(&second);
+    }
+
+    // Punti di aggancio per il partizionamento delle tracce, chiamati dall'interprete
+    // e dal risolutore sul grafo dopo ciascun ramo di un if e alla fine di ogni
+    // statement. Di default non fanno nulla.
+    fn record_branch(&mut self, _stmt: &IfThenElse, _taken: bool) {}
+    fn merge_point(&mut self, _stmt: &dyn Statement) {}
+}
@@ -0,0 +1,483 @@
+use crate::analyzer::dbm::Dbm;
+use crate::analyzer::interval::{Bound, Interval};
+use crate::analyzer::linear::{linearize, LinearExpression};
+use crate::analyzer::value_state::ValueState;
+use crate::analyzer::{AbstractDomain, AbstractState, Comparison};
+use crate::ast::arithmetic::ArithmeticExpression;
+use crate::ast::State;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+// Dominio degli ottagoni: vincoli ±x ±y ≤ c. Ogni variabile x_k ha due nodi nella DBM,
+// 2k per +x_k e 2k+1 per -x_k; l'elemento (i, j) limita Vⱼ - Vᵢ.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Octagon {
+    vars: Vec<String>,
+    dbm: Dbm,
+    bottom: bool,
+}
+
+fn bar(i: usize) -> usize {
+    i ^ 1
+}
+
+fn floor_half(value: i64) -> i64 {
+    value.div_euclid(2)
+}
+
+impl Octagon {
+    fn index_of(&self, var: &str) -> Option<usize> {
+        self.vars.iter().position(|v| v == var)
+    }
+
+    // Indice della variabile, aggiungendola senza vincoli se non c'è
+    fn ensure_var(&mut self, var: &str) -> usize {
+        if let Some(k) = self.index_of(var) {
+            return k;
+        }
+        let mapping: Vec<usize> = (0..self.dbm.size()).collect();
+        self.dbm = self.dbm.remap(self.dbm.size() + 2, &mapping);
+        self.vars.push(var.to_string());
+        self.vars.len() - 1
+    }
+
+    // Porta entrambi gli ottagoni sullo stesso insieme di variabili
+    fn unify(&self, other: &Octagon) -> (Octagon, Octagon) {
+        let mut left = self.clone();
+        for var in &other.vars {
+            left.ensure_var(var);
+        }
+        let mut mapping = vec![0; other.dbm.size()];
+        for (k, var) in other.vars.iter().enumerate() {
+            let position = left.index_of(var).unwrap();
+            mapping[2 * k] = 2 * position;
+            mapping[2 * k + 1] = 2 * position + 1;
+        }
+        let right = Octagon {
+            vars: left.vars.clone(),
+            dbm: other.dbm.remap(left.dbm.size(), &mapping),
+            bottom: other.bottom,
+        };
+        (left, right)
+    }
+
+    // Chiusura forte sugli interi: Floyd–Warshall, tightening dei vincoli unari
+    // (2x ≤ c diventa 2x ≤ 2⌊c/2⌋) e rafforzamento con le coppie di vincoli unari
+    pub fn close(&mut self) {
+        if self.bottom {
+            return;
+        }
+        let size = self.dbm.size();
+        self.dbm.close();
+        for i in 0..size {
+            if let Some(c) = self.dbm.get(i, bar(i)) {
+                self.dbm.set(i, bar(i), Some(2 * floor_half(c)));
+            }
+        }
+        for i in 0..size {
+            for j in 0..size {
+                if let (Some(a), Some(b)) = (self.dbm.get(i, bar(i)), self.dbm.get(bar(j), j)) {
+                    if let Some(sum) = a.checked_add(b) {
+                        self.dbm.constrain(i, j, floor_half(sum));
+                    }
+                }
+            }
+        }
+        if self.dbm.is_empty() {
+            *self = Octagon::bottom();
+        }
+    }
+
+    // Vincolo Vⱼ - Vᵢ ≤ c insieme al suo gemello coerente V_ī - V_j̄ ≤ c
+    fn add_constraint(&mut self, i: usize, j: usize, c: i64) {
+        self.dbm.constrain(i, j, c);
+        self.dbm.constrain(bar(j), bar(i), c);
+    }
+
+    // Proiezione sulla variabile k di un ottagono già chiuso
+    fn interval_at(&self, k: usize) -> Interval {
+        if self.bottom {
+            return Interval::Bottom;
+        }
+        let high = match self.dbm.get(2 * k + 1, 2 * k) {
+            Some(c) => Bound::Finite(floor_half(c)),
+            None => Bound::PosInf,
+        };
+        let low = match self.dbm.get(2 * k, 2 * k + 1) {
+            Some(c) => Bound::Finite(-floor_half(c)),
+            None => Bound::NegInf,
+        };
+        Interval::range(low, high)
+    }
+
+    // Proiezione non relazionale, usata per le espressioni che l'ottagono non rappresenta
+    pub fn to_intervals(&self) -> ValueState<Interval> {
+        if self.bottom {
+            return ValueState::bottom();
+        }
+        let mut closed = self.clone();
+        closed.close();
+        let mut result = ValueState::top();
+        for (k, var) in closed.vars.iter().enumerate() {
+            result.set(var, closed.interval_at(k));
+        }
+        result
+    }
+
+    fn set_interval(&mut self, k: usize, interval: Interval) {
+        match interval {
+            Interval::Bottom => *self = Octagon::bottom(),
+            Interval::Range { low, high } => {
+                if let Bound::Finite(h) = high {
+                    if let Some(c) = h.checked_mul(2) {
+                        self.dbm.constrain(2 * k + 1, 2 * k, c);
+                    }
+                }
+                if let Bound::Finite(l) = low {
+                    if let Some(c) = l.checked_mul(-2) {
+                        self.dbm.constrain(2 * k, 2 * k + 1, c);
+                    }
+                }
+            }
+        }
+    }
+
+    fn forget(&mut self, k: usize) {
+        self.dbm.forget(2 * k);
+        self.dbm.forget(2 * k + 1);
+    }
+
+    // x := x + c
+    fn shift(&mut self, k: usize, c: i64) {
+        let size = self.dbm.size();
+        for i in 0..size {
+            for j in 0..size {
+                let mut delta = 0i64;
+                if j == 2 * k {
+                    delta += c;
+                } else if j == 2 * k + 1 {
+                    delta -= c;
+                }
+                if i == 2 * k {
+                    delta -= c;
+                } else if i == 2 * k + 1 {
+                    delta += c;
+                }
+                if delta != 0 {
+                    let shifted = self.dbm.get(i, j).and_then(|b| b.checked_add(delta));
+                    self.dbm.set(i, j, shifted);
+                }
+            }
+        }
+    }
+
+    // x := -x, cioè scambio dei nodi +x e -x
+    fn negate(&mut self, k: usize) {
+        let size = self.dbm.size();
+        let mapping: Vec<usize> = (0..size)
+            .map(|i| if i / 2 == k { bar(i) } else { i })
+            .collect();
+        self.dbm = self.dbm.remap(size, &mapping);
+    }
+
+    // Nodo che rappresenta a·x con a = ±1
+    fn node(&mut self, var: &str, coefficient: i64) -> usize {
+        let k = self.ensure_var(var);
+        if coefficient > 0 {
+            2 * k
+        } else {
+            2 * k + 1
+        }
+    }
+
+    // Aggiunge e ≤ 0 se e ha forma ottagonale; altrimenti restituisce false
+    fn add_linear_leq(&mut self, e: &LinearExpression) -> bool {
+        if e.coefficients.values().any(|a| a.abs() != 1) {
+            return false;
+        }
+        let terms: Vec<(String, i64)> = e
+            .coefficients
+            .iter()
+            .map(|(v, a)| (v.clone(), *a))
+            .collect();
+        match terms.as_slice() {
+            [] => {
+                if e.constant > 0 {
+                    *self = Octagon::bottom();
+                }
+                true
+            }
+            // Vₚ + c ≤ 0, cioè 2Vₚ ≤ -2c
+            [(x, a)] => {
+                let p = self.node(x, *a);
+                match e.constant.checked_mul(-2) {
+                    Some(c) => self.add_constraint(bar(p), p, c),
+                    None => return false,
+                }
+                true
+            }
+            // Vₚ + V_q + c ≤ 0, cioè Vₚ - V_q̄ ≤ -c
+            [(x, a), (y, b)] => {
+                let p = self.node(x, *a);
+                let q = self.node(y, *b);
+                match e.constant.checked_neg() {
+                    Some(c) => self.add_constraint(bar(q), p, c),
+                    None => return false,
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // Raffinamento con gli intervalli quando il vincolo non è ottagonale
+    fn assume_with_intervals(
+        &mut self,
+        op: Comparison,
+        left: &dyn ArithmeticExpression,
+        right: &dyn ArithmeticExpression,
+    ) {
+        let mut intervals = self.to_intervals();
+        intervals.assume_comparison(op, left, right);
+        if intervals.is_bottom() {
+            *self = Octagon::bottom();
+            return;
+        }
+        let vars = self.vars.clone();
+        for (k, var) in vars.iter().enumerate() {
+            self.set_interval(k, intervals.get(var));
+        }
+    }
+}
+
+impl AbstractDomain for Octagon {
+    fn bottom() -> Self {
+        Octagon {
+            vars: Vec::new(),
+            dbm: Dbm::new(0),
+            bottom: true,
+        }
+    }
+
+    fn top() -> Self {
+        Octagon {
+            vars: Vec::new(),
+            dbm: Dbm::new(0),
+            bottom: false,
+        }
+    }
+
+    fn is_bottom(&self) -> bool {
+        self.bottom
+    }
+
+    fn leq(&self, other: &Self) -> bool {
+        if self.bottom {
+            return true;
+        }
+        if other.bottom {
+            return false;
+        }
+        let (mut left, right) = self.unify(other);
+        left.close();
+        left.bottom || left.dbm.leq(&right.dbm)
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        if self.bottom {
+            return other.clone();
+        }
+        if other.bottom {
+            return self.clone();
+        }
+        let (mut left, mut right) = self.unify(other);
+        left.close();
+        right.close();
+        if left.bottom {
+            return right;
+        }
+        if right.bottom {
+            return left;
+        }
+        left.dbm = left.dbm.join(&right.dbm);
+        left
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        if self.bottom || other.bottom {
+            return Octagon::bottom();
+        }
+        let (mut left, right) = self.unify(other);
+        left.dbm = left.dbm.meet(&right.dbm);
+        left.close();
+        left
+    }
+
+    // Il primo argomento non viene chiuso, altrimenti la sequenza potrebbe non stabilizzarsi
+    fn widening(&self, other: &Self) -> Self {
+        if self.bottom {
+            return other.clone();
+        }
+        if other.bottom {
+            return self.clone();
+        }
+        let (mut left, mut right) = self.unify(other);
+        right.close();
+        left.dbm = left.dbm.widening(&right.dbm);
+        left
+    }
+}
+
+impl AbstractState for Octagon {
+    fn from_state(state: &State) -> Self {
+        let mut result = Octagon::top();
+        // ordine stabile delle variabili, lo stato concreto è una HashMap
+        let mut entries: Vec<(&String, &i32)> = state.iter().collect();
+        entries.sort();
+        for (name, value) in entries {
+            let k = result.ensure_var(name);
+            result.set_interval(k, Interval::new(*value as i64, *value as i64));
+        }
+        result.close();
+        result
+    }
+
+    fn assign(&mut self, var: &str, expr: &dyn ArithmeticExpression) {
+        self.close();
+        if self.bottom {
+            return;
+        }
+        let k = self.ensure_var(var);
+        let linear = linearize(expr);
+        let terms: Option<Vec<(String, i64)>> = linear.as_ref().map(|e| {
+            e.coefficients
+                .iter()
+                .map(|(v, a)| (v.clone(), *a))
+                .collect()
+        });
+        match (linear.as_ref(), terms.as_deref()) {
+            // x := c
+            (Some(e), Some([])) => {
+                self.forget(k);
+                self.set_interval(k, Interval::new(e.constant, e.constant));
+            }
+            // x := ±x + c
+            (Some(e), Some([(y, a)])) if y == var && a.abs() == 1 => {
+                if *a < 0 {
+                    self.negate(k);
+                }
+                self.shift(k, e.constant);
+            }
+            // x := y + c oppure x := -y + c
+            (Some(e), Some([(y, a)])) if a.abs() == 1 => {
+                self.forget(k);
+                let x = 2 * k;
+                let q = self.node(y, *a);
+                // x - (±y) ≤ c e (±y) - x ≤ -c
+                self.add_constraint(q, x, e.constant);
+                if let Some(c) = e.constant.checked_neg() {
+                    self.add_constraint(x, q, c);
+                }
+            }
+            // assegnamento generico: proiezione sugli intervalli
+            _ => {
+                let value = self.to_intervals().eval(expr);
+                self.forget(k);
+                self.set_interval(k, value);
+            }
+        }
+        self.close();
+    }
+
+    fn assume_comparison(
+        &mut self,
+        op: Comparison,
+        left: &dyn ArithmeticExpression,
+        right: &dyn ArithmeticExpression,
+    ) {
+        self.close();
+        if self.bottom {
+            return;
+        }
+        // si ragiona su e = left - right confrontato con 0
+        let difference = match (linearize(left), linearize(right)) {
+            (Some(l), Some(r)) => l.combine(1, &r, -1),
+            _ => None,
+        };
+        let Some(e) = difference else {
+            self.assume_with_intervals(op, left, right);
+            self.close();
+            return;
+        };
+        let minus_e = e.scale(-1);
+        let constraints: Option<Vec<LinearExpression>> = match op {
+            Comparison::LessEqual => Some(vec![e.clone()]),
+            Comparison::Less => e.combine(1, &LinearExpression::constant(1), 1).map(|c| vec![c]),
+            Comparison::GreatEqual => minus_e.map(|c| vec![c]),
+            Comparison::Great => minus_e
+                .and_then(|m| m.combine(1, &LinearExpression::constant(1), 1))
+                .map(|c| vec![c]),
+            Comparison::Equal => minus_e.map(|m| vec![e.clone(), m]),
+            Comparison::NotEqual => None,
+        };
+        let handled = match constraints {
+            Some(constraints) => constraints.iter().all(|c| {
+                let mut attempt = self.clone();
+                let ok = attempt.add_linear_leq(c);
+                if ok {
+                    *self = attempt;
+                }
+                ok
+            }),
+            None => false,
+        };
+        if !handled {
+            self.assume_with_intervals(op, left, right);
+        }
+        self.close();
+    }
+}
+
+impl Display for Octagon {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut closed = self.clone();
+        closed.close();
+        if closed.bottom {
+            return write!(f, "⊥");
+        }
+        let mut entries = Vec::new();
+        for (k, var) in closed.vars.iter().enumerate() {
+            let interval = closed.interval_at(k);
+            if interval != Interval::top() {
+                entries.push(format!("{} ∈ {}", var, interval));
+            }
+        }
+        for (k, x) in closed.vars.iter().enumerate() {
+            for (h, y) in closed.vars.iter().enumerate().skip(k + 1) {
+                let relations = [
+                    (2 * h, 2 * k, format!("{} - {}", x, y)),
+                    (2 * k, 2 * h, format!("{} - {}", y, x)),
+                    (2 * h + 1, 2 * k, format!("{} + {}", x, y)),
+                    (2 * h, 2 * k + 1, format!("-{} - {}", x, y)),
+                ];
+                for (i, j, text) in relations {
+                    // si omettono i vincoli già implicati dagli intervalli
+                    let implied = match (closed.dbm.get(i, bar(i)), closed.dbm.get(bar(j), j)) {
+                        (Some(a), Some(b)) => a.checked_add(b).map(floor_half),
+                        _ => None,
+                    };
+                    if let Some(c) = closed.dbm.get(i, j) {
+                        if implied != Some(c) {
+                            entries.push(format!("{} ≤ {}", text, c));
+                        }
+                    }
+                }
+            }
+        }
+        if entries.is_empty() {
+            write!(f, "⊤")
+        } else {
+            write!(f, "{{ {} }}", entries.join(", "))
+        }
+    }
+}
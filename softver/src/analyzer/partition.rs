@@ -0,0 +1,306 @@
+use crate::analyzer::{AbstractDomain, AbstractState, Comparison};
+use crate::ast::arithmetic::ArithmeticExpression;
+use crate::ast::statement::{IfThenElse, NodeId, Statement, StatementNode};
+use crate::ast::State;
+use crate::source::SourceMap;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+// Direttiva di partizionamento: si separano le tracce a seconda del ramo preso
+// in `branch`, e le partizioni si riuniscono alla fine di `merge_after`
+// (None vuol dire mai, fino alla fine dell'analisi)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Directive {
+    branch: NodeId,
+    merge_after: Option<NodeId>,
+}
+
+// Primo statement, in ordine di sorgente, che inizia alla riga data e soddisfa
+// `wanted`; le sequenze non contano, iniziano insieme al loro primo statement
+fn statement_at<'a>(
+    stmt: &'a dyn Statement,
+    spans: &SourceMap,
+    line: usize,
+    wanted: &dyn Fn(&dyn Statement) -> bool,
+) -> Option<&'a dyn Statement> {
+    let children: Vec<&'a dyn Statement> = match stmt.node() {
+        StatementNode::Concat(c) => {
+            return [&*c.first, &*c.second]
+                .into_iter()
+                .find_map(|s| statement_at(s, spans, line, wanted))
+        }
+        StatementNode::IfThenElse(i) => vec![&*i.true_expr, &*i.false_expr],
+        StatementNode::While(w) => vec![&*w.body],
+        _ => Vec::new(),
+    };
+    let starts_here = spans
+        .span_of_statement(stmt)
+        .is_some_and(|s| s.start.line == line);
+    if starts_here && wanted(stmt) {
+        return Some(stmt);
+    }
+    children
+        .into_iter()
+        .find_map(|s| statement_at(s, spans, line, wanted))
+}
+
+impl Directive {
+    pub fn new(branch: &IfThenElse, merge_after: Option<&dyn Statement>) -> Self {
+        Directive {
+            branch: branch.id,
+            merge_after: merge_after.map(|s| s.id()),
+        }
+    }
+
+    // Direttiva scelta sul sorgente: si partiziona sull'if che inizia alla riga
+    // `branch` e si riunisce alla fine dello statement (il più esterno) che inizia
+    // alla riga `merge`. None se a quelle righe non c'è lo statement cercato.
+    pub fn at_lines(
+        program: &dyn Statement,
+        spans: &SourceMap,
+        branch: usize,
+        merge: Option<usize>,
+    ) -> Option<Self> {
+        let is_if = |s: &dyn Statement| matches!(s.node(), StatementNode::IfThenElse(_));
+        let branch = match statement_at(program, spans, branch, &is_if)?.node() {
+            StatementNode::IfThenElse(i) => i,
+            _ => return None,
+        };
+        let merge_after = match merge {
+            Some(line) => Some(statement_at(program, spans, line, &|_| true)?),
+            None => None,
+        };
+        Some(Directive::new(branch, merge_after))
+    }
+}
+
+// Chiave di una partizione: per ogni direttiva attiva il ramo preso l'ultima volta
+type Token = Vec<(NodeId, bool)>;
+
+// Partizionamento delle tracce sopra un dominio qualsiasi: a ogni chiave
+// corrisponde uno stato di S, e il join unisce solo le partizioni con la stessa chiave
+#[derive(Debug, Clone, PartialEq)]
+pub struct Partitioned<S> {
+    partitions: Vec<(Token, S)>,
+    directives: Vec<Directive>,
+}
+
+impl<S: AbstractState> Partitioned<S> {
+    pub fn new(state: S, directives: Vec<Directive>) -> Self {
+        let mut result = Partitioned {
+            partitions: Vec::new(),
+            directives,
+        };
+        result.add(Vec::new(), state);
+        result
+    }
+
+    // Join di tutte le partizioni nel dominio di base
+    pub fn merged(&self) -> S {
+        self.partitions
+            .iter()
+            .fold(S::bottom(), |result, (_, s)| result.join(s))
+    }
+
+    fn get(&self, token: &Token) -> Option<&S> {
+        self.partitions
+            .iter()
+            .find(|(t, _)| t == token)
+            .map(|(_, s)| s)
+    }
+
+    // Aggiunge uno stato alla partizione, unendolo a quello già presente
+    fn add(&mut self, token: Token, state: S) {
+        if state.is_bottom() {
+            return;
+        }
+        match self.partitions.iter_mut().find(|(t, _)| *t == token) {
+            Some((_, s)) => *s = s.join(&state),
+            None => self.partitions.push((token, state)),
+        }
+    }
+
+    // Le direttive vengono dal primo operando che ne ha
+    fn directives_of(&self, other: &Self) -> Vec<Directive> {
+        if self.directives.is_empty() {
+            other.directives.clone()
+        } else {
+            self.directives.clone()
+        }
+    }
+
+    fn map(&self, f: impl Fn(&mut S)) -> Self {
+        let mut result = Partitioned {
+            partitions: Vec::new(),
+            directives: self.directives.clone(),
+        };
+        for (token, s) in &self.partitions {
+            let mut s = s.clone();
+            f(&mut s);
+            result.add(token.clone(), s);
+        }
+        result
+    }
+
+    // Riscrive le chiavi, unendo le partizioni che finiscono sulla stessa
+    fn rekey(&mut self, f: impl Fn(&Token) -> Token) {
+        let partitions = std::mem::take(&mut self.partitions);
+        for (token, s) in partitions {
+            self.add(f(&token), s);
+        }
+    }
+}
+
+impl<S: AbstractState> AbstractDomain for Partitioned<S> {
+    fn bottom() -> Self {
+        Partitioned {
+            partitions: Vec::new(),
+            directives: Vec::new(),
+        }
+    }
+
+    fn top() -> Self {
+        Partitioned {
+            partitions: vec![(Vec::new(), S::top())],
+            directives: Vec::new(),
+        }
+    }
+
+    fn is_bottom(&self) -> bool {
+        self.partitions.is_empty()
+    }
+
+    // Si confrontano le partizioni con la stessa chiave; se manca si usa l'unione
+    fn leq(&self, other: &Self) -> bool {
+        self.partitions.iter().all(|(token, s)| match other.get(token) {
+            Some(o) => s.leq(o),
+            None => s.leq(&other.merged()),
+        })
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        let mut result = self.clone();
+        result.directives = self.directives_of(other);
+        for (token, s) in &other.partitions {
+            result.add(token.clone(), s.clone());
+        }
+        result
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        let mut result = Partitioned {
+            partitions: Vec::new(),
+            directives: self.directives_of(other),
+        };
+        let merged = other.merged();
+        for (token, s) in &self.partitions {
+            let o = other.get(token).unwrap_or(&merged);
+            result.add(token.clone(), s.meet(o));
+        }
+        result
+    }
+
+    // Widening chiave per chiave: le chiavi possibili sono finite,
+    // quindi la sequenza si stabilizza
+    fn widening(&self, other: &Self) -> Self {
+        let mut result = Partitioned {
+            partitions: Vec::new(),
+            directives: self.directives_of(other),
+        };
+        for (token, s) in &self.partitions {
+            match other.get(token) {
+                Some(o) => result.add(token.clone(), s.widening(o)),
+                None => result.add(token.clone(), s.clone()),
+            }
+        }
+        for (token, o) in &other.partitions {
+            if self.get(token).is_none() {
+                result.add(token.clone(), o.clone());
+            }
+        }
+        result
+    }
+}
+
+impl<S: AbstractState> AbstractState for Partitioned<S> {
+    // Senza direttive si comporta esattamente come S
+    fn from_state(state: &State) -> Self {
+        Partitioned::new(S::from_state(state), Vec::new())
+    }
+
+    fn assign(&mut self, var: &str, expr: &dyn ArithmeticExpression) {
+        *self = self.map(|s| s.assign(var, expr));
+    }
+
+    fn assume_comparison(
+        &mut self,
+        op: Comparison,
+        left: &dyn ArithmeticExpression,
+        right: &dyn ArithmeticExpression,
+    ) {
+        *self = self.map(|s| s.assume_comparison(op, left, right));
+    }
+
+    fn record_branch(&mut self, stmt: &IfThenElse, taken: bool) {
+        *self = self.map(|s| s.record_branch(stmt, taken));
+        let key = stmt.id;
+        if !self.directives.iter().any(|d| d.branch == key) {
+            return;
+        }
+        self.rekey(|token| {
+            let mut token: Token = token.iter().filter(|(b, _)| *b != key).copied().collect();
+            token.push((key, taken));
+            token.sort();
+            token
+        });
+    }
+
+    fn merge_point(&mut self, stmt: &dyn Statement) {
+        *self = self.map(|s| s.merge_point(stmt));
+        let key = stmt.id();
+        let merged: Vec<NodeId> = self
+            .directives
+            .iter()
+            .filter(|d| d.merge_after == Some(key))
+            .map(|d| d.branch)
+            .collect();
+        if !merged.is_empty() {
+            self.rekey(|token| {
+                token
+                    .iter()
+                    .filter(|(b, _)| !merged.contains(b))
+                    .copied()
+                    .collect()
+            });
+        }
+    }
+}
+
+impl<S: Display> Display for Partitioned<S> {
+    // Le chiavi si stampano con il numero della direttiva e il ramo preso
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.partitions.is_empty() {
+            return write!(f, "⊥");
+        }
+        let mut entries = Vec::new();
+        for (token, s) in &self.partitions {
+            let branches: Vec<String> = self
+                .directives
+                .iter()
+                .enumerate()
+                .filter_map(|(k, d)| {
+                    token
+                        .iter()
+                        .find(|(b, _)| *b == d.branch)
+                        .map(|(_, taken)| format!("if{}: {}", k, if *taken { "then" } else { "else" }))
+                })
+                .collect();
+            if branches.is_empty() {
+                entries.push(s.to_string());
+            } else {
+                entries.push(format!("[{}] {}", branches.join(", "), s));
+            }
+        }
+        write!(f, "{}", entries.join(" ∨ "))
+    }
+}
@@ -0,0 +1,904 @@
+use crate::analyzer::interval::{Bound, Interval};
+use crate::analyzer::linear::{linearize, LinearExpression};
+use crate::analyzer::rational::{gcd, Rational};
+use crate::analyzer::value_state::ValueState;
+use crate::analyzer::{AbstractDomain, AbstractState, Comparison};
+use crate::ast::arithmetic::ArithmeticExpression;
+use crate::ast::State;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+// Vettore nello spazio omogeneo: le coordinate delle variabili seguite
+// dalla componente del termine noto
+pub type Vector = Vec<Rational>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConstraintKind {
+    Equality,
+    Inequality,
+}
+
+// Vincolo Σ aᵢ·xᵢ + b = 0 oppure Σ aᵢ·xᵢ + b ≥ 0
+#[derive(Debug, Clone, PartialEq)]
+pub struct Constraint {
+    pub kind: ConstraintKind,
+    pub coefficients: Vec<Rational>,
+    pub constant: Rational,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GeneratorKind {
+    Point,
+    Ray,
+    Line,
+}
+
+// Generatore: il poliedro è l'inviluppo convesso dei punti
+// più le combinazioni non negative dei raggi e quelle arbitrarie delle linee
+#[derive(Debug, Clone, PartialEq)]
+pub struct Generator {
+    pub kind: GeneratorKind,
+    pub coordinates: Vec<Rational>,
+}
+
+// Dominio dei poliedri convessi in doppia rappresentazione: il sistema di vincoli
+// e quello di generatori vengono tenuti entrambi in forma minimale e sincronizzati.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polyhedron {
+    vars: Vec<String>,
+    constraints: Vec<Constraint>,
+    generators: Vec<Generator>,
+    bottom: bool,
+}
+
+// Le operazioni sui vettori restituiscono None in caso di overflow
+pub fn dot(a: &[Rational], b: &[Rational]) -> Option<Rational> {
+    a.iter()
+        .zip(b.iter())
+        .try_fold(Rational::zero(), |sum, (x, y)| sum.checked_add(x.checked_mul(*y)?))
+}
+
+// a·u + b·v
+pub fn combine(a: Rational, u: &[Rational], b: Rational, v: &[Rational]) -> Option<Vector> {
+    u.iter()
+        .zip(v.iter())
+        .map(|(x, y)| a.checked_mul(*x)?.checked_add(b.checked_mul(*y)?))
+        .collect()
+}
+
+pub fn unit(dim: usize, i: usize) -> Vector {
+    let mut v = vec![Rational::zero(); dim];
+    v[i] = Rational::one();
+    v
+}
+
+// Stessa direzione, ma a coefficienti interi primi tra loro: evita la crescita
+// dei numeri durante l'eliminazione
+pub fn normalize(v: Vector) -> Option<Vector> {
+    let lcm = v.iter().try_fold(1i128, |lcm, x| {
+        (lcm / gcd(lcm, x.denominator())).checked_mul(x.denominator())
+    })?;
+    let scaled = v
+        .iter()
+        .map(|x| Some(x.checked_mul(Rational::from(lcm))?.numerator()))
+        .collect::<Option<Vec<i128>>>()?;
+    let g = scaled.iter().fold(0, |g, x| gcd(g, *x));
+    if g == 0 {
+        return Some(v);
+    }
+    Some(scaled.iter().map(|x| Rational::from(x / g)).collect())
+}
+
+// Due raggi sono adiacenti se nessun altro raggio satura tutti i vincoli
+// che saturano entrambi (test combinatorio)
+fn adjacent(i: usize, j: usize, saturated: &[Vec<bool>]) -> bool {
+    let common: Vec<bool> = saturated[i]
+        .iter()
+        .zip(saturated[j].iter())
+        .map(|(a, b)| *a && *b)
+        .collect();
+    !(0..saturated.len()).any(|k| {
+        k != i
+            && k != j
+            && common
+                .iter()
+                .zip(saturated[k].iter())
+                .all(|(c, s)| !*c || *s)
+    })
+}
+
+// Metodo della doppia descrizione (Motzkin): dato il cono
+// { v | c·v = 0 per c in equalities, c·v ≥ 0 per c in inequalities }
+// restituisce una base del suo spazio di linealità e i suoi raggi estremi.
+// Per dualità la stessa funzione converte anche i generatori in vincoli.
+// None se i coefficienti vanno in overflow.
+fn double_description(
+    dim: usize,
+    equalities: &[Vector],
+    inequalities: &[Vector],
+) -> Option<(Vec<Vector>, Vec<Vector>)> {
+    let mut lines: Vec<Vector> = (0..dim).map(|i| unit(dim, i)).collect();
+    let mut rays: Vec<Vector> = Vec::new();
+    let mut processed: Vec<&Vector> = Vec::new();
+    let all = equalities
+        .iter()
+        .map(|c| (c, true))
+        .chain(inequalities.iter().map(|c| (c, false)));
+    for (c, equality) in all {
+        let line_values = lines
+            .iter()
+            .map(|l| dot(c, l))
+            .collect::<Option<Vec<Rational>>>()?;
+        if let Some(k) = line_values.iter().position(|value| !value.is_zero()) {
+            // una linea non ortogonale al vincolo: si usa come pivot per rendere
+            // ortogonali tutte le altre, poi diventa un raggio (o sparisce)
+            let pivot = lines.swap_remove(k);
+            let value = line_values[k];
+            for v in lines.iter_mut().chain(rays.iter_mut()) {
+                let other = dot(c, v)?;
+                if !other.is_zero() {
+                    let other = if value.signum() > 0 { -other } else { other };
+                    *v = normalize(combine(value.abs(), v, other, &pivot)?)?;
+                }
+            }
+            if !equality {
+                rays.push(if value.signum() > 0 {
+                    pivot
+                } else {
+                    pivot.iter().map(|x| -*x).collect()
+                });
+            }
+        } else {
+            let values = rays
+                .iter()
+                .map(|r| dot(c, r))
+                .collect::<Option<Vec<Rational>>>()?;
+            let saturated = rays
+                .iter()
+                .map(|r| {
+                    processed
+                        .iter()
+                        .map(|p| Some(dot(p, r)?.is_zero()))
+                        .collect::<Option<Vec<bool>>>()
+                })
+                .collect::<Option<Vec<Vec<bool>>>>()?;
+            let mut next: Vec<Vector> = Vec::new();
+            for (r, value) in rays.iter().zip(values.iter()) {
+                if value.is_zero() || (!equality && value.signum() > 0) {
+                    next.push(r.clone());
+                }
+            }
+            // ogni coppia adiacente di raggi ai lati opposti dell'iperpiano
+            // genera un nuovo raggio sull'iperpiano
+            for (i, vi) in values.iter().enumerate().filter(|(_, v)| v.signum() > 0) {
+                for (j, vj) in values.iter().enumerate().filter(|(_, v)| v.signum() < 0) {
+                    if adjacent(i, j, &saturated) {
+                        let ray = normalize(combine(*vi, &rays[j], -*vj, &rays[i])?)?;
+                        if !next.contains(&ray) {
+                            next.push(ray);
+                        }
+                    }
+                }
+            }
+            rays = next;
+        }
+        processed.push(c);
+    }
+    Some((lines, rays))
+}
+
+impl Constraint {
+    fn homogeneous(&self) -> Vector {
+        let mut v = self.coefficients.clone();
+        v.push(self.constant);
+        v
+    }
+
+    // Valore di Σ aᵢ·xᵢ + b sul generatore (senza termine noto per raggi e linee)
+    fn evaluate(&self, generator: &Generator) -> Option<Rational> {
+        let value = dot(&self.coefficients, &generator.coordinates)?;
+        if generator.kind == GeneratorKind::Point {
+            value.checked_add(self.constant)
+        } else {
+            Some(value)
+        }
+    }
+
+    // In caso di overflow il vincolo si considera non soddisfatto: l'inclusione
+    // e l'implicazione non vengono dimostrate e il widening scarta il vincolo
+    fn satisfied_by(&self, generator: &Generator) -> bool {
+        let Some(value) = self.evaluate(generator) else {
+            return false;
+        };
+        match (self.kind, generator.kind) {
+            (ConstraintKind::Equality, _) | (_, GeneratorKind::Line) => value.is_zero(),
+            (ConstraintKind::Inequality, _) => value.signum() >= 0,
+        }
+    }
+
+    // e ≥ 0 oppure e = 0 con e lineare a coefficienti interi
+    fn from_linear(kind: ConstraintKind, vars: &[String], e: &LinearExpression) -> Constraint {
+        Constraint {
+            kind,
+            coefficients: vars
+                .iter()
+                .map(|v| Rational::from(e.coefficient(v)))
+                .collect(),
+            constant: Rational::from(e.constant),
+        }
+    }
+}
+
+impl Generator {
+    fn homogeneous(&self) -> Vector {
+        let mut v = self.coordinates.clone();
+        v.push(match self.kind {
+            GeneratorKind::Point => Rational::one(),
+            _ => Rational::zero(),
+        });
+        v
+    }
+}
+
+// Generatori minimali del poliedro descritto dai vincoli (senza punti se è
+// vuoto), None in caso di overflow
+fn generators_of(dim: usize, constraints: &[Constraint]) -> Option<Vec<Generator>> {
+    let mut equalities = Vec::new();
+    // il vincolo di positività della componente omogenea
+    let mut inequalities = vec![unit(dim + 1, dim)];
+    for c in constraints {
+        match c.kind {
+            ConstraintKind::Equality => equalities.push(c.homogeneous()),
+            ConstraintKind::Inequality => inequalities.push(c.homogeneous()),
+        }
+    }
+    let (lines, rays) = double_description(dim + 1, &equalities, &inequalities)?;
+    let mut generators: Vec<Generator> = lines
+        .into_iter()
+        .map(|l| Generator {
+            kind: GeneratorKind::Line,
+            coordinates: l[..dim].to_vec(),
+        })
+        .collect();
+    for r in rays {
+        let h = r[dim];
+        generators.push(if h.is_zero() {
+            Generator {
+                kind: GeneratorKind::Ray,
+                coordinates: r[..dim].to_vec(),
+            }
+        } else {
+            Generator {
+                kind: GeneratorKind::Point,
+                coordinates: r[..dim]
+                    .iter()
+                    .map(|x| x.checked_div(h))
+                    .collect::<Option<Vec<Rational>>>()?,
+            }
+        });
+    }
+    Some(generators)
+}
+
+// Vincoli minimali del poliedro generato, calcolati sul cono duale
+fn constraints_of(dim: usize, generators: &[Generator]) -> Option<Vec<Constraint>> {
+    let mut equalities = Vec::new();
+    let mut inequalities = Vec::new();
+    for g in generators {
+        match g.kind {
+            GeneratorKind::Line => equalities.push(g.homogeneous()),
+            _ => inequalities.push(g.homogeneous()),
+        }
+    }
+    let (lines, rays) = double_description(dim + 1, &equalities, &inequalities)?;
+    let (lines, rays) = canonicalize(dim, lines, rays)?;
+    let constraints = lines
+        .into_iter()
+        .map(|v| (ConstraintKind::Equality, v))
+        .chain(rays.into_iter().map(|v| (ConstraintKind::Inequality, v)));
+    let constraints = constraints
+        .map(|(kind, v)| Constraint {
+            kind,
+            coefficients: v[..dim].to_vec(),
+            constant: v[dim],
+        })
+        // si scarta il vincolo banale 1 ≥ 0 che corrisponde alla positività
+        .filter(|c| c.coefficients.iter().any(|a| !a.is_zero()))
+        .collect();
+    Some(constraints)
+}
+
+// Forma canonica dei vincoli: uguaglianze ridotte con Gauss–Jordan sulle prime
+// `dim` componenti e disuguaglianze ridotte modulo le uguaglianze, così lo stesso
+// poliedro ha sempre la stessa rappresentazione
+fn canonicalize(
+    dim: usize,
+    mut equalities: Vec<Vector>,
+    inequalities: Vec<Vector>,
+) -> Option<(Vec<Vector>, Vec<Vector>)> {
+    let mut pivots = Vec::new();
+    for col in 0..dim {
+        let rank = pivots.len();
+        let Some(r) = (rank..equalities.len()).find(|r| !equalities[*r][col].is_zero()) else {
+            continue;
+        };
+        equalities.swap(rank, r);
+        let factor = equalities[rank][col];
+        equalities[rank] = equalities[rank]
+            .iter()
+            .map(|x| x.checked_div(factor))
+            .collect::<Option<Vector>>()?;
+        for r in 0..equalities.len() {
+            let value = equalities[r][col];
+            if r != rank && !value.is_zero() {
+                equalities[r] =
+                    combine(Rational::one(), &equalities[r], -value, &equalities[rank])?;
+            }
+        }
+        pivots.push(col);
+    }
+    equalities.truncate(pivots.len());
+    let mut reduced: Vec<Vector> = Vec::new();
+    for mut v in inequalities {
+        for (row, col) in equalities.iter().zip(pivots.iter()) {
+            let value = v[*col];
+            if !value.is_zero() {
+                v = combine(Rational::one(), &v, -value, row)?;
+            }
+        }
+        let v = normalize(v)?;
+        if !reduced.contains(&v) {
+            reduced.push(v);
+        }
+    }
+    let equalities = equalities
+        .into_iter()
+        .map(normalize)
+        .collect::<Option<Vec<Vector>>>()?;
+    Some((equalities, reduced))
+}
+
+impl Polyhedron {
+    fn index_of(&self, var: &str) -> Option<usize> {
+        self.vars.iter().position(|v| v == var)
+    }
+
+    // Indice della variabile, aggiungendola senza vincoli se non c'è.
+    // Entrambe le rappresentazioni restano minimali.
+    fn ensure_var(&mut self, var: &str) -> usize {
+        if let Some(k) = self.index_of(var) {
+            return k;
+        }
+        for c in &mut self.constraints {
+            c.coefficients.push(Rational::zero());
+        }
+        for g in &mut self.generators {
+            g.coordinates.push(Rational::zero());
+        }
+        self.vars.push(var.to_string());
+        let k = self.vars.len() - 1;
+        if !self.bottom {
+            self.generators.push(Generator {
+                kind: GeneratorKind::Line,
+                coordinates: unit(self.vars.len(), k),
+            });
+        }
+        k
+    }
+
+    // Porta entrambi i poliedri sullo stesso insieme di variabili, nello stesso ordine
+    fn unify(&self, other: &Polyhedron) -> (Polyhedron, Polyhedron) {
+        let mut left = self.clone();
+        let mut right = other.clone();
+        for var in &other.vars {
+            left.ensure_var(var);
+        }
+        for var in &left.vars {
+            right.ensure_var(var);
+        }
+        let positions: Vec<usize> = left
+            .vars
+            .iter()
+            .map(|v| right.index_of(v).unwrap())
+            .collect();
+        let permute = |v: &Vec<Rational>| positions.iter().map(|p| v[*p]).collect();
+        for c in &mut right.constraints {
+            c.coefficients = permute(&c.coefficients);
+        }
+        for g in &mut right.generators {
+            g.coordinates = permute(&g.coordinates);
+        }
+        right.vars = left.vars.clone();
+        (left, right)
+    }
+
+    // Se la conversione va in overflow si tolgono i vincoli a partire dagli
+    // ultimi, quelli aggiunti più di recente: il poliedro ottenuto contiene quello
+    // esatto, e senza vincoli la conversione non può fallire
+    pub fn from_constraints(vars: Vec<String>, mut constraints: Vec<Constraint>) -> Polyhedron {
+        loop {
+            if let Some(result) = Polyhedron::convert_constraints(&vars, &constraints) {
+                return result;
+            }
+            constraints.pop();
+        }
+    }
+
+    fn convert_constraints(vars: &[String], constraints: &[Constraint]) -> Option<Polyhedron> {
+        let generators = generators_of(vars.len(), constraints)?;
+        if !generators.iter().any(|g| g.kind == GeneratorKind::Point) {
+            return Some(Polyhedron::bottom());
+        }
+        Some(Polyhedron {
+            constraints: constraints_of(vars.len(), &generators)?,
+            vars: vars.to_vec(),
+            generators,
+            bottom: false,
+        })
+    }
+
+    // In caso di overflow si ripiega su tutto lo spazio delle variabili
+    pub fn from_generators(vars: Vec<String>, generators: Vec<Generator>) -> Polyhedron {
+        if !generators.iter().any(|g| g.kind == GeneratorKind::Point) {
+            return Polyhedron::bottom();
+        }
+        let convert = || {
+            let constraints = constraints_of(vars.len(), &generators)?;
+            Some(Polyhedron {
+                generators: generators_of(vars.len(), &constraints)?,
+                vars: vars.clone(),
+                constraints,
+                bottom: false,
+            })
+        };
+        match convert() {
+            Some(result) => result,
+            None => Polyhedron::from_constraints(vars, Vec::new()),
+        }
+    }
+
+    pub fn variables(&self) -> &[String] {
+        &self.vars
+    }
+
+    // I vincoli come disuguaglianze e ≥ 0 a coefficienti interi; ogni
+    // uguaglianza e = 0 dà sia e ≥ 0 che -e ≥ 0
+    pub fn inequalities(&self) -> Vec<LinearExpression> {
+        let mut result = Vec::new();
+        for c in &self.constraints {
+            let Some(e) = self.to_linear(c) else {
+                continue;
+            };
+            if c.kind == ConstraintKind::Equality {
+                if let Some(opposite) = e.scale(-1) {
+                    result.push(opposite);
+                }
+            }
+            result.push(e);
+        }
+        result
+    }
+
+    // Vincolo moltiplicato per il minimo comune multiplo dei denominatori
+    fn to_linear(&self, c: &Constraint) -> Option<LinearExpression> {
+        let lcm = c
+            .coefficients
+            .iter()
+            .chain(std::iter::once(&c.constant))
+            .try_fold(1i128, |l, a| (l / gcd(l, a.denominator())).checked_mul(a.denominator()))?;
+        let integer = |a: &Rational| {
+            let value = a.numerator().checked_mul(lcm / a.denominator())?;
+            i64::try_from(value).ok()
+        };
+        let mut e = LinearExpression::constant(integer(&c.constant)?);
+        for (var, a) in self.vars.iter().zip(c.coefficients.iter()) {
+            if !a.is_zero() {
+                e = e.combine(1, &LinearExpression::variable(var), integer(a)?)?;
+            }
+        }
+        Some(e)
+    }
+
+    // Vero se e ≥ 0 vale in tutto il poliedro: ogni generatore deve soddisfarlo
+    pub fn entails(&self, e: &LinearExpression) -> bool {
+        if self.bottom {
+            return true;
+        }
+        let constraint = Constraint::from_linear(ConstraintKind::Inequality, &self.vars, e);
+        // una variabile che il poliedro non conosce è libera
+        let unknown = e.coefficients.keys().any(|v| self.index_of(v).is_none());
+        !unknown && self.generators.iter().all(|g| constraint.satisfied_by(g))
+    }
+
+    fn add_constraints(&mut self, constraints: Vec<Constraint>) {
+        if self.bottom {
+            return;
+        }
+        let mut all = self.constraints.clone();
+        all.extend(constraints);
+        *self = Polyhedron::from_constraints(self.vars.clone(), all);
+    }
+
+    // Aggiunge e ≥ 0 oppure e = 0, introducendo le variabili che mancano
+    fn add_linear(&mut self, kind: ConstraintKind, e: &LinearExpression) {
+        for var in e.coefficients.keys() {
+            self.ensure_var(var);
+        }
+        let constraint = Constraint::from_linear(kind, &self.vars, e);
+        self.add_constraints(vec![constraint]);
+    }
+
+    // Estremi di e: minimo e massimo sui punti, infiniti se un raggio
+    // o una linea fanno variare e. Sugli interi si arrotonda verso l'interno.
+    // Se il calcolo va in overflow e può valere qualsiasi cosa.
+    pub fn range_of(&self, e: &LinearExpression) -> Interval {
+        if self.bottom {
+            return Interval::Bottom;
+        }
+        if e.coefficients.keys().any(|v| self.index_of(v).is_none()) {
+            return Interval::top();
+        }
+        let constraint = Constraint::from_linear(ConstraintKind::Inequality, &self.vars, e);
+        let mut low = Bound::PosInf;
+        let mut high = Bound::NegInf;
+        for g in &self.generators {
+            let Some(x) = constraint.evaluate(g) else {
+                return Interval::top();
+            };
+            match g.kind {
+                GeneratorKind::Point => {
+                    low = low.min(Bound::from_i128(x.ceil()));
+                    high = high.max(Bound::from_i128(x.floor()));
+                }
+                GeneratorKind::Ray if x.signum() > 0 => high = Bound::PosInf,
+                GeneratorKind::Ray if x.signum() < 0 => low = Bound::NegInf,
+                GeneratorKind::Line if !x.is_zero() => {
+                    low = Bound::NegInf;
+                    high = Bound::PosInf;
+                }
+                _ => {}
+            }
+        }
+        Interval::range(low, high)
+    }
+
+    fn interval_at(&self, k: usize) -> Interval {
+        self.range_of(&LinearExpression::variable(&self.vars[k]))
+    }
+
+    // Proiezione non relazionale, usata per le espressioni non lineari
+    pub fn to_intervals(&self) -> ValueState<Interval> {
+        if self.bottom {
+            return ValueState::bottom();
+        }
+        let mut result = ValueState::top();
+        for (k, var) in self.vars.iter().enumerate() {
+            result.set(var, self.interval_at(k));
+        }
+        result
+    }
+
+    // Vincoli low ≤ xₖ ≤ high
+    fn interval_constraints(&self, k: usize, interval: Interval) -> Vec<Constraint> {
+        let Interval::Range { low, high } = interval else {
+            // intervallo vuoto: 0 ≥ 1, cioè -1 ≥ 0
+            return vec![Constraint {
+                kind: ConstraintKind::Inequality,
+                coefficients: vec![Rational::zero(); self.vars.len()],
+                constant: -Rational::one(),
+            }];
+        };
+        let mut result = Vec::new();
+        if let Bound::Finite(l) = low {
+            let mut coefficients = vec![Rational::zero(); self.vars.len()];
+            coefficients[k] = Rational::one();
+            result.push(Constraint {
+                kind: ConstraintKind::Inequality,
+                coefficients,
+                constant: -Rational::from(l),
+            });
+        }
+        if let Bound::Finite(h) = high {
+            let mut coefficients = vec![Rational::zero(); self.vars.len()];
+            coefficients[k] = -Rational::one();
+            result.push(Constraint {
+                kind: ConstraintKind::Inequality,
+                coefficients,
+                constant: Rational::from(h),
+            });
+        }
+        result
+    }
+
+    // Proiezione esistenziale: xₖ diventa libera
+    fn forget(&mut self, k: usize) {
+        if self.bottom {
+            return;
+        }
+        let mut generators = self.generators.clone();
+        generators.push(Generator {
+            kind: GeneratorKind::Line,
+            coordinates: unit(self.vars.len(), k),
+        });
+        *self = Polyhedron::from_generators(self.vars.clone(), generators);
+    }
+
+    // Raffinamento con gli intervalli quando il vincolo non è lineare
+    fn assume_with_intervals(
+        &mut self,
+        op: Comparison,
+        left: &dyn ArithmeticExpression,
+        right: &dyn ArithmeticExpression,
+    ) {
+        let mut intervals = self.to_intervals();
+        intervals.assume_comparison(op, left, right);
+        let constraints = (0..self.vars.len())
+            .flat_map(|k| self.interval_constraints(k, intervals.get(&self.vars[k])))
+            .collect();
+        self.add_constraints(constraints);
+    }
+}
+
+impl AbstractDomain for Polyhedron {
+    fn bottom() -> Self {
+        Polyhedron {
+            vars: Vec::new(),
+            constraints: Vec::new(),
+            generators: Vec::new(),
+            bottom: true,
+        }
+    }
+
+    // Lo spazio a zero dimensioni contiene un solo punto, l'origine
+    fn top() -> Self {
+        Polyhedron {
+            vars: Vec::new(),
+            constraints: Vec::new(),
+            generators: vec![Generator {
+                kind: GeneratorKind::Point,
+                coordinates: Vec::new(),
+            }],
+            bottom: false,
+        }
+    }
+
+    fn is_bottom(&self) -> bool {
+        self.bottom
+    }
+
+    // P ⊆ Q se ogni generatore di P soddisfa ogni vincolo di Q
+    fn leq(&self, other: &Self) -> bool {
+        if self.bottom {
+            return true;
+        }
+        if other.bottom {
+            return false;
+        }
+        let (left, right) = self.unify(other);
+        right
+            .constraints
+            .iter()
+            .all(|c| left.generators.iter().all(|g| c.satisfied_by(g)))
+    }
+
+    // Inviluppo convesso: unione dei generatori
+    fn join(&self, other: &Self) -> Self {
+        if self.bottom {
+            return other.clone();
+        }
+        if other.bottom {
+            return self.clone();
+        }
+        let (left, right) = self.unify(other);
+        let mut generators = left.generators;
+        generators.extend(right.generators);
+        Polyhedron::from_generators(left.vars, generators)
+    }
+
+    // Intersezione: unione dei vincoli
+    fn meet(&self, other: &Self) -> Self {
+        if self.bottom || other.bottom {
+            return Polyhedron::bottom();
+        }
+        let (mut left, right) = self.unify(other);
+        left.add_constraints(right.constraints);
+        left
+    }
+
+    // Widening standard: si tengono i vincoli (minimali) di self soddisfatti
+    // da other. Le uguaglianze si spezzano in due disuguaglianze, così
+    // ne sopravvive almeno metà.
+    fn widening(&self, other: &Self) -> Self {
+        if self.bottom {
+            return other.clone();
+        }
+        if other.bottom {
+            return self.clone();
+        }
+        let (left, right) = self.unify(other);
+        let mut candidates = Vec::new();
+        for c in &left.constraints {
+            let inequality = Constraint {
+                kind: ConstraintKind::Inequality,
+                ..c.clone()
+            };
+            if c.kind == ConstraintKind::Equality {
+                candidates.push(Constraint {
+                    kind: ConstraintKind::Inequality,
+                    coefficients: c.coefficients.iter().map(|a| -*a).collect(),
+                    constant: -c.constant,
+                });
+            }
+            candidates.push(inequality);
+        }
+        let stable = candidates
+            .into_iter()
+            .filter(|c| right.generators.iter().all(|g| c.satisfied_by(g)))
+            .collect();
+        Polyhedron::from_constraints(left.vars, stable)
+    }
+}
+
+impl AbstractState for Polyhedron {
+    fn from_state(state: &State) -> Self {
+        // ordine stabile delle variabili, lo stato concreto è una HashMap
+        let mut entries: Vec<(&String, &i32)> = state.iter().collect();
+        entries.sort();
+        let vars: Vec<String> = entries.iter().map(|(name, _)| name.to_string()).collect();
+        let constraints = entries
+            .iter()
+            .enumerate()
+            .map(|(k, (_, value))| Constraint {
+                kind: ConstraintKind::Equality,
+                coefficients: unit(vars.len(), k),
+                constant: -Rational::from(**value as i64),
+            })
+            .collect();
+        Polyhedron::from_constraints(vars, constraints)
+    }
+
+    fn assign(&mut self, var: &str, expr: &dyn ArithmeticExpression) {
+        if self.bottom {
+            return;
+        }
+        let k = self.ensure_var(var);
+        match linearize(expr) {
+            // assegnamento lineare: immagine esatta dei generatori
+            Some(e) => {
+                for v in e.coefficients.keys() {
+                    self.ensure_var(v);
+                }
+                let coefficients: Vec<Rational> = self
+                    .vars
+                    .iter()
+                    .map(|v| Rational::from(e.coefficient(v)))
+                    .collect();
+                let mut generators = self.generators.clone();
+                for g in &mut generators {
+                    let value = dot(&coefficients, &g.coordinates).and_then(|value| {
+                        if g.kind == GeneratorKind::Point {
+                            value.checked_add(Rational::from(e.constant))
+                        } else {
+                            Some(value)
+                        }
+                    });
+                    match value {
+                        Some(value) => g.coordinates[k] = value,
+                        // immagine non rappresentabile: la variabile diventa libera
+                        None => {
+                            self.forget(k);
+                            return;
+                        }
+                    }
+                }
+                *self = Polyhedron::from_generators(self.vars.clone(), generators);
+            }
+            // prodotti tra variabili e divisioni: si dimentica la variabile
+            // e la si limita con il valore calcolato sugli intervalli
+            None => {
+                let value = self.to_intervals().eval(expr);
+                self.forget(k);
+                let constraints = self.interval_constraints(k, value);
+                self.add_constraints(constraints);
+            }
+        }
+    }
+
+    fn assume_comparison(
+        &mut self,
+        op: Comparison,
+        left: &dyn ArithmeticExpression,
+        right: &dyn ArithmeticExpression,
+    ) {
+        if self.bottom {
+            return;
+        }
+        // si ragiona su e = left - right confrontato con 0
+        let difference = match (linearize(left), linearize(right)) {
+            (Some(l), Some(r)) => l.combine(1, &r, -1),
+            _ => None,
+        };
+        let Some(e) = difference else {
+            self.assume_with_intervals(op, left, right);
+            return;
+        };
+        let one = LinearExpression::constant(1);
+        // sugli interi e < 0 equivale a -e - 1 ≥ 0
+        let constraint = match op {
+            Comparison::LessEqual => e.scale(-1),
+            Comparison::Less => e.combine(-1, &one, -1),
+            Comparison::GreatEqual => Some(e.clone()),
+            Comparison::Great => e.combine(1, &one, -1),
+            Comparison::Equal => {
+                self.add_linear(ConstraintKind::Equality, &e);
+                return;
+            }
+            // e ≠ 0 è l'unione di e < 0 ed e > 0: se ne prende l'inviluppo
+            Comparison::NotEqual => {
+                let mut less = self.clone();
+                less.assume_comparison(Comparison::Less, left, right);
+                self.assume_comparison(Comparison::Great, left, right);
+                *self = self.join(&less);
+                return;
+            }
+        };
+        match constraint {
+            Some(c) => self.add_linear(ConstraintKind::Inequality, &c),
+            None => self.assume_with_intervals(op, left, right),
+        }
+    }
+}
+
+// Forma leggibile di un vincolo: termine noto a destra e primo coefficiente
+// positivo (per le disuguaglianze si passa a ≤ quando serve)
+pub fn format_constraint(vars: &[String], c: &Constraint) -> String {
+    let negate = c
+        .coefficients
+        .iter()
+        .find(|a| !a.is_zero())
+        .is_some_and(|a| a.signum() < 0);
+    let signed = |a: Rational| if negate { -a } else { a };
+    let relation = match c.kind {
+        ConstraintKind::Equality => "=",
+        ConstraintKind::Inequality if negate => "≤",
+        ConstraintKind::Inequality => "≥",
+    };
+    let mut text = String::new();
+    for (var, a) in vars.iter().zip(c.coefficients.iter()) {
+        let a = signed(*a);
+        if a.is_zero() {
+            continue;
+        }
+        if text.is_empty() {
+            if a.signum() < 0 {
+                text.push('-');
+            }
+        } else if a.signum() < 0 {
+            text.push_str(" - ");
+        } else {
+            text.push_str(" + ");
+        }
+        if a.abs() != Rational::one() {
+            text.push_str(&format!("{}*", a.abs()));
+        }
+        text.push_str(var);
+    }
+    format!("{} {} {}", text, relation, -signed(c.constant))
+}
+
+impl Display for Polyhedron {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.bottom {
+            return write!(f, "⊥");
+        }
+        if self.constraints.is_empty() {
+            return write!(f, "⊤");
+        }
+        let entries: Vec<String> = self
+            .constraints
+            .iter()
+            .map(|c| format_constraint(&self.vars, c))
+            .collect();
+        write!(f, "{{ {} }}", entries.join(", "))
+    }
+}
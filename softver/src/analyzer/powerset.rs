@@ -0,0 +1,143 @@
+use crate::analyzer::{AbstractDomain, AbstractState, Comparison};
+use crate::ast::arithmetic::ArithmeticExpression;
+use crate::ast::statement::{IfThenElse, Statement};
+use crate::ast::State;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+// Completamento disgiuntivo limitato: uno stato è l'unione di al più N disgiunti
+// del dominio di base. Il join non perde precisione finché non si supera il limite,
+// dopo di che gli ultimi due disgiunti vengono uniti.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Powerset<S, const N: usize = 4> {
+    disjuncts: Vec<S>,
+}
+
+impl<S: AbstractState, const N: usize> Powerset<S, N> {
+    pub fn new(state: S) -> Self {
+        let mut result = Self::bottom();
+        result.add(state);
+        result
+    }
+
+    // Join di tutti i disgiunti nel dominio di base
+    pub fn merged(&self) -> S {
+        self.disjuncts
+            .iter()
+            .fold(S::bottom(), |result, d| result.join(d))
+    }
+
+    // Aggiunge un disgiunto tenendo l'insieme senza elementi ridondanti
+    fn add(&mut self, state: S) {
+        if state.is_bottom() || self.disjuncts.iter().any(|d| state.leq(d)) {
+            return;
+        }
+        self.disjuncts.retain(|d| !d.leq(&state));
+        self.disjuncts.push(state);
+        if self.disjuncts.len() > N.max(1) {
+            let last = self.disjuncts.pop().unwrap();
+            let previous = self.disjuncts.pop().unwrap();
+            self.add(previous.join(&last));
+        }
+    }
+
+    fn map(&self, f: impl Fn(&mut S)) -> Self {
+        let mut result = Self::bottom();
+        for d in &self.disjuncts {
+            let mut d = d.clone();
+            f(&mut d);
+            result.add(d);
+        }
+        result
+    }
+}
+
+impl<S: AbstractState, const N: usize> AbstractDomain for Powerset<S, N> {
+    fn bottom() -> Self {
+        Powerset {
+            disjuncts: Vec::new(),
+        }
+    }
+
+    fn top() -> Self {
+        Powerset {
+            disjuncts: vec![S::top()],
+        }
+    }
+
+    fn is_bottom(&self) -> bool {
+        self.disjuncts.is_empty()
+    }
+
+    // Ogni disgiunto deve essere contenuto in almeno uno dei disgiunti di other
+    fn leq(&self, other: &Self) -> bool {
+        self.disjuncts
+            .iter()
+            .all(|d| other.disjuncts.iter().any(|e| d.leq(e)))
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        let mut result = self.clone();
+        for d in &other.disjuncts {
+            result.add(d.clone());
+        }
+        result
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        let mut result = Self::bottom();
+        for d in &self.disjuncts {
+            for e in &other.disjuncts {
+                result.add(d.meet(e));
+            }
+        }
+        result
+    }
+
+    // Se other aggiunge stati nuovi si riduce tutto a un solo disgiunto e si
+    // applica il widening di base: la sequenza si stabilizza come quella di S
+    fn widening(&self, other: &Self) -> Self {
+        if other.leq(self) {
+            return self.clone();
+        }
+        let merged = self.merged();
+        Powerset::new(merged.widening(&merged.join(&other.merged())))
+    }
+}
+
+impl<S: AbstractState, const N: usize> AbstractState for Powerset<S, N> {
+    fn from_state(state: &State) -> Self {
+        Powerset::new(S::from_state(state))
+    }
+
+    fn assign(&mut self, var: &str, expr: &dyn ArithmeticExpression) {
+        *self = self.map(|d| d.assign(var, expr));
+    }
+
+    fn assume_comparison(
+        &mut self,
+        op: Comparison,
+        left: &dyn ArithmeticExpression,
+        right: &dyn ArithmeticExpression,
+    ) {
+        *self = self.map(|d| d.assume_comparison(op, left, right));
+    }
+
+    fn record_branch(&mut self, stmt: &IfThenElse, taken: bool) {
+        *self = self.map(|d| d.record_branch(stmt, taken));
+    }
+
+    fn merge_point(&mut self, stmt: &dyn Statement) {
+        *self = self.map(|d| d.merge_point(stmt));
+    }
+}
+
+impl<S: Display, const N: usize> Display for Powerset<S, N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.disjuncts.is_empty() {
+            return write!(f, "⊥");
+        }
+        let entries: Vec<String> = self.disjuncts.iter().map(|d| d.to_string()).collect();
+        write!(f, "{}", entries.join(" ∨ "))
+    }
+}
@@ -0,0 +1,271 @@
+use crate::analyzer::congruence::Congruence;
+use crate::analyzer::constant::Constant;
+use crate::analyzer::interval::{Bound, Interval};
+use crate::analyzer::sign::Sign;
+use crate::analyzer::{AbstractDomain, AbstractState, Comparison, ValueDomain};
+use crate::ast::arithmetic::ArithmeticExpression;
+use crate::ast::statement::{IfThenElse, Statement};
+use crate::ast::State;
+use std::fmt;
+use std::fmt::{Debug, Display, Formatter};
+use std::marker::PhantomData;
+
+// Operatore di riduzione: stringe ciascuna componente usando l'informazione dell'altra
+pub trait Reduction<D1, D2>: Debug + Clone + PartialEq {
+    fn reduce(first: &D1, second: &D2) -> (D1, D2);
+}
+
+// Prodotto cartesiano senza riduzione (a parte la propagazione del bottom)
+#[derive(Debug, Clone, PartialEq)]
+pub struct NoReduction;
+
+impl<D1: AbstractDomain, D2: AbstractDomain> Reduction<D1, D2> for NoReduction {
+    fn reduce(first: &D1, second: &D2) -> (D1, D2) {
+        (first.clone(), second.clone())
+    }
+}
+
+// Prodotto ridotto di due domini. Implementa gli stessi trait delle componenti,
+// quindi si può usare ovunque è accettato un dominio singolo (anche annidato).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReducedProduct<D1, D2, R = NoReduction> {
+    pub first: D1,
+    pub second: D2,
+    reduction: PhantomData<R>,
+}
+
+impl<D1: AbstractDomain, D2: AbstractDomain, R: Reduction<D1, D2>> ReducedProduct<D1, D2, R> {
+    pub fn new(first: D1, second: D2) -> Self {
+        let (first, second) = R::reduce(&first, &second);
+        // se una componente è vuota lo è tutto il prodotto
+        if first.is_bottom() || second.is_bottom() {
+            return Self::bottom();
+        }
+        ReducedProduct {
+            first,
+            second,
+            reduction: PhantomData,
+        }
+    }
+
+    fn map(&self, other: &Self, f1: impl Fn(&D1, &D1) -> D1, f2: impl Fn(&D2, &D2) -> D2) -> Self {
+        Self::new(f1(&self.first, &other.first), f2(&self.second, &other.second))
+    }
+}
+
+impl<D1: AbstractDomain, D2: AbstractDomain, R: Reduction<D1, D2>> AbstractDomain
+    for ReducedProduct<D1, D2, R>
+{
+    fn bottom() -> Self {
+        ReducedProduct {
+            first: D1::bottom(),
+            second: D2::bottom(),
+            reduction: PhantomData,
+        }
+    }
+
+    fn top() -> Self {
+        ReducedProduct {
+            first: D1::top(),
+            second: D2::top(),
+            reduction: PhantomData,
+        }
+    }
+
+    fn is_bottom(&self) -> bool {
+        self.first.is_bottom() || self.second.is_bottom()
+    }
+
+    fn leq(&self, other: &Self) -> bool {
+        self.is_bottom() || (self.first.leq(&other.first) && self.second.leq(&other.second))
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        if self.is_bottom() {
+            return other.clone();
+        }
+        if other.is_bottom() {
+            return self.clone();
+        }
+        self.map(other, D1::join, D2::join)
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        self.map(other, D1::meet, D2::meet)
+    }
+
+    // Dopo il widening non si riduce, altrimenti la terminazione non è garantita
+    fn widening(&self, other: &Self) -> Self {
+        if self.is_bottom() {
+            return other.clone();
+        }
+        if other.is_bottom() {
+            return self.clone();
+        }
+        ReducedProduct {
+            first: self.first.widening(&other.first),
+            second: self.second.widening(&other.second),
+            reduction: PhantomData,
+        }
+    }
+}
+
+impl<D1: ValueDomain, D2: ValueDomain, R: Reduction<D1, D2>> ValueDomain
+    for ReducedProduct<D1, D2, R>
+{
+    fn constant(value: i32) -> Self {
+        Self::new(D1::constant(value), D2::constant(value))
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        self.map(other, D1::add, D2::add)
+    }
+
+    fn minus(&self, other: &Self) -> Self {
+        self.map(other, D1::minus, D2::minus)
+    }
+
+    fn product(&self, other: &Self) -> Self {
+        self.map(other, D1::product, D2::product)
+    }
+
+    fn divide(&self, other: &Self) -> Self {
+        self.map(other, D1::divide, D2::divide)
+    }
+
+    fn uminus(&self) -> Self {
+        Self::new(self.first.uminus(), self.second.uminus())
+    }
+
+    fn refine(op: Comparison, left: &Self, right: &Self) -> (Self, Self) {
+        let (l1, r1) = D1::refine(op, &left.first, &right.first);
+        let (l2, r2) = D2::refine(op, &left.second, &right.second);
+        (Self::new(l1, l2), Self::new(r1, r2))
+    }
+
+    fn as_constant(&self) -> Option<i32> {
+        self.first.as_constant().or(self.second.as_constant())
+    }
+}
+
+// Prodotto di due stati: ogni operazione viene eseguita su entrambi e poi ridotta
+impl<D1: AbstractState, D2: AbstractState, R: Reduction<D1, D2>> AbstractState
+    for ReducedProduct<D1, D2, R>
+{
+    fn from_state(state: &State) -> Self {
+        Self::new(D1::from_state(state), D2::from_state(state))
+    }
+
+    fn assign(&mut self, var: &str, expr: &dyn ArithmeticExpression) {
+        let mut first = self.first.clone();
+        first.assign(var, expr);
+        let mut second = self.second.clone();
+        second.assign(var, expr);
+        *self = Self::new(first, second);
+    }
+
+    fn assume_comparison(
+        &mut self,
+        op: Comparison,
+        left: &dyn ArithmeticExpression,
+        right: &dyn ArithmeticExpression,
+    ) {
+        let mut first = self.first.clone();
+        first.assume_comparison(op, left, right);
+        let mut second = self.second.clone();
+        second.assume_comparison(op, left, right);
+        *self = Self::new(first, second);
+    }
+
+    fn record_branch(&mut self, stmt: &IfThenElse, taken: bool) {
+        self.first.record_branch(stmt, taken);
+        self.second.record_branch(stmt, taken);
+    }
+
+    fn merge_point(&mut self, stmt: &dyn Statement) {
+        self.first.merge_point(stmt);
+        self.second.merge_point(stmt);
+    }
+}
+
+impl<D1: Display, D2: Display, R> Display for ReducedProduct<D1, D2, R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "⟨{}, {}⟩", self.first, self.second)
+    }
+}
+
+// Intervalli × congruenze: gli estremi vengono spostati sul primo valore della classe
+#[derive(Debug, Clone, PartialEq)]
+pub struct IntervalCongruence;
+
+impl Reduction<Interval, Congruence> for IntervalCongruence {
+    fn reduce(interval: &Interval, congruence: &Congruence) -> (Interval, Congruence) {
+        let (
+            Interval::Range { low, high },
+            Congruence::Class {
+                modulus,
+                remainder,
+            },
+        ) = (interval, congruence)
+        else {
+            return (Interval::Bottom, Congruence::Bottom);
+        };
+        if *modulus == 0 {
+            let value = Interval::new(*remainder, *remainder);
+            return match interval.meet(&value) {
+                Interval::Bottom => (Interval::Bottom, Congruence::Bottom),
+                reduced => (reduced, *congruence),
+            };
+        }
+        // in i128 le differenze non vanno in overflow neanche agli estremi di i64
+        let (modulus, remainder) = (*modulus as i128, *remainder as i128);
+        let low = match low {
+            Bound::Finite(l) => {
+                let shift = (remainder - *l as i128).rem_euclid(modulus);
+                Bound::from_i128(*l as i128 + shift)
+            }
+            infinite => *infinite,
+        };
+        let high = match high {
+            Bound::Finite(h) => {
+                let shift = (*h as i128 - remainder).rem_euclid(modulus);
+                Bound::from_i128(*h as i128 - shift)
+            }
+            infinite => *infinite,
+        };
+        match Interval::range(low, high) {
+            Interval::Bottom => (Interval::Bottom, Congruence::Bottom),
+            reduced => match reduced.as_constant() {
+                // un solo valore possibile: anche la congruenza diventa una costante
+                Some(value) => (reduced, Congruence::constant(value)),
+                None => (reduced, *congruence),
+            },
+        }
+    }
+}
+
+// Segni × costanti: una costante determina il segno, il segno 0 determina la costante
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignConstant;
+
+impl Reduction<Sign, Constant> for SignConstant {
+    fn reduce(sign: &Sign, constant: &Constant) -> (Sign, Constant) {
+        match constant {
+            Constant::Value(value) => {
+                if sign.contains(*value as i64) {
+                    (Sign::of(*value as i64), *constant)
+                } else {
+                    (Sign::bottom(), Constant::Bottom)
+                }
+            }
+            Constant::Top => match sign.as_constant() {
+                Some(value) => (*sign, Constant::Value(value)),
+                None => (*sign, *constant),
+            },
+            Constant::Bottom => (Sign::bottom(), Constant::Bottom),
+        }
+    }
+}
+
+pub type IntervalCongruenceProduct = ReducedProduct<Interval, Congruence, IntervalCongruence>;
+pub type SignConstantProduct = ReducedProduct<Sign, Constant, SignConstant>;
@@ -0,0 +1,178 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::ops::Neg;
+
+// Numero razionale esatto num/den, sempre ridotto ai minimi termini con den > 0.
+// Le operazioni sono controllate e restituiscono None in caso di overflow di i128:
+// sta a chi le usa ripiegare su un'approssimazione corretta. Numeratore e
+// denominatore non valgono mai i128::MIN, quindi opposto e valore assoluto non falliscono.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational {
+    num: i128,
+    den: i128,
+}
+
+pub fn gcd(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+impl Rational {
+    pub fn new(num: i128, den: i128) -> Self {
+        Rational::reduced(num, den).expect("Razionale non rappresentabile")
+    }
+
+    // num/den ai minimi termini, None se uno dei due è i128::MIN
+    fn reduced(num: i128, den: i128) -> Option<Self> {
+        if den == 0 {
+            panic!("Razionale con denominatore nullo");
+        }
+        if num == i128::MIN || den == i128::MIN {
+            return None;
+        }
+        let g = gcd(num, den);
+        let sign = if den < 0 { -1 } else { 1 };
+        Some(Rational {
+            num: sign * num / g.max(1),
+            den: sign * den / g.max(1),
+        })
+    }
+
+    pub fn zero() -> Self {
+        Rational { num: 0, den: 1 }
+    }
+
+    pub fn one() -> Self {
+        Rational { num: 1, den: 1 }
+    }
+
+    pub fn numerator(&self) -> i128 {
+        self.num
+    }
+
+    pub fn denominator(&self) -> i128 {
+        self.den
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.num == 0
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.den == 1
+    }
+
+    pub fn signum(&self) -> i128 {
+        self.num.signum()
+    }
+
+    pub fn abs(&self) -> Self {
+        Rational {
+            num: self.num.abs(),
+            den: self.den,
+        }
+    }
+
+    pub fn floor(&self) -> i128 {
+        self.num.div_euclid(self.den)
+    }
+
+    pub fn ceil(&self) -> i128 {
+        -(-self.num).div_euclid(self.den)
+    }
+
+    pub fn checked_add(self, other: Rational) -> Option<Rational> {
+        let g = gcd(self.den, other.den);
+        let den = (self.den / g).checked_mul(other.den)?;
+        let left = self.num.checked_mul(other.den / g)?;
+        let right = other.num.checked_mul(self.den / g)?;
+        Rational::reduced(left.checked_add(right)?, den)
+    }
+
+    pub fn checked_mul(self, other: Rational) -> Option<Rational> {
+        // si semplifica in croce prima di moltiplicare per limitare la crescita
+        let g1 = gcd(self.num, other.den).max(1);
+        let g2 = gcd(other.num, self.den).max(1);
+        Rational::reduced(
+            (self.num / g1).checked_mul(other.num / g2)?,
+            (self.den / g2).checked_mul(other.den / g1)?,
+        )
+    }
+
+    pub fn checked_div(self, other: Rational) -> Option<Rational> {
+        if other.num == 0 {
+            panic!("Divisione razionale per zero");
+        }
+        self.checked_mul(Rational::new(other.den, other.num))
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(value: i64) -> Self {
+        Rational {
+            num: value as i128,
+            den: 1,
+        }
+    }
+}
+
+impl From<i128> for Rational {
+    fn from(value: i128) -> Self {
+        Rational { num: value, den: 1 }
+    }
+}
+
+impl Neg for Rational {
+    type Output = Rational;
+
+    fn neg(self) -> Rational {
+        Rational {
+            num: -self.num,
+            den: self.den,
+        }
+    }
+}
+
+// Confronto esatto senza prodotti incrociati, che potrebbero andare in overflow:
+// si confrontano le parti intere e poi, come nell'algoritmo di Euclide, i
+// reciproci delle parti frazionarie
+fn compare(a: i128, b: i128, c: i128, d: i128) -> Ordering {
+    let (p, q) = (a.div_euclid(b), c.div_euclid(d));
+    if p != q {
+        return p.cmp(&q);
+    }
+    let (r, s) = (a.rem_euclid(b), c.rem_euclid(d));
+    match (r, s) {
+        (0, 0) => Ordering::Equal,
+        (0, _) => Ordering::Less,
+        (_, 0) => Ordering::Greater,
+        // r/b < s/d se e solo se d/s < b/r
+        _ => compare(d, s, b, r),
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        compare(self.num, self.den, other.num, other.den)
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.den == 1 {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use crate::analyzer::{AbstractDomain, Comparison, ValueDomain};
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+const NEGATIVE: u8 = 0b001;
+const ZERO: u8 = 0b010;
+const POSITIVE: u8 = 0b100;
+const SIGNS: [u8; 3] = [NEGATIVE, ZERO, POSITIVE];
+
+// Dominio dei segni: un sottoinsieme di {<0, 0, >0}, codificato come maschera di bit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sign(u8);
+
+impl Sign {
+    pub fn of(value: i64) -> Sign {
+        match value.signum() {
+            -1 => Sign(NEGATIVE),
+            0 => Sign(ZERO),
+            _ => Sign(POSITIVE),
+        }
+    }
+
+    pub fn contains(&self, value: i64) -> bool {
+        self.0 & Sign::of(value).0 != 0
+    }
+
+    // Estende alle maschere un'operazione sui segni elementari
+    fn lift(&self, other: &Sign, op: impl Fn(u8, u8) -> u8) -> Sign {
+        let mut result = 0;
+        for a in SIGNS.iter().filter(|s| self.0 & **s != 0) {
+            for b in SIGNS.iter().filter(|s| other.0 & **s != 0) {
+                result |= op(*a, *b);
+            }
+        }
+        Sign(result)
+    }
+}
+
+fn add_signs(a: u8, b: u8) -> u8 {
+    match (a, b) {
+        (ZERO, x) | (x, ZERO) => x,
+        (x, y) if x == y => x,
+        _ => NEGATIVE | ZERO | POSITIVE,
+    }
+}
+
+fn product_signs(a: u8, b: u8) -> u8 {
+    match (a, b) {
+        (ZERO, _) | (_, ZERO) => ZERO,
+        (x, y) if x == y => POSITIVE,
+        _ => NEGATIVE,
+    }
+}
+
+// La divisione troncata può dare 0 (1 / 2) ma non cambia il segno
+fn divide_signs(a: u8, b: u8) -> u8 {
+    match (a, b) {
+        (_, ZERO) => 0,
+        (ZERO, _) => ZERO,
+        (x, y) if x == y => POSITIVE | ZERO,
+        _ => NEGATIVE | ZERO,
+    }
+}
+
+fn rank(sign: u8) -> i32 {
+    match sign {
+        NEGATIVE => -1,
+        ZERO => 0,
+        _ => 1,
+    }
+}
+
+// Vero se esistono due interi con i segni dati per cui `a op b` vale;
+// due numeri con lo stesso segno non nullo possono stare in qualunque ordine
+fn may_hold(op: Comparison, a: u8, b: u8) -> bool {
+    let (a, b) = (rank(a), rank(b));
+    match op {
+        Comparison::Equal => a == b,
+        Comparison::NotEqual => a != b || a != 0,
+        Comparison::Less => a < b || (a == b && a != 0),
+        Comparison::LessEqual => a <= b,
+        Comparison::Great => a > b || (a == b && a != 0),
+        Comparison::GreatEqual => a >= b,
+    }
+}
+
+impl AbstractDomain for Sign {
+    fn bottom() -> Self {
+        Sign(0)
+    }
+
+    fn top() -> Self {
+        Sign(NEGATIVE | ZERO | POSITIVE)
+    }
+
+    fn is_bottom(&self) -> bool {
+        self.0 == 0
+    }
+
+    fn leq(&self, other: &Self) -> bool {
+        self.0 & !other.0 == 0
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        Sign(self.0 | other.0)
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        Sign(self.0 & other.0)
+    }
+}
+
+impl ValueDomain for Sign {
+    fn constant(value: i32) -> Self {
+        Sign::of(value as i64)
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        self.lift(other, add_signs)
+    }
+
+    fn minus(&self, other: &Self) -> Self {
+        self.add(&other.uminus())
+    }
+
+    fn product(&self, other: &Self) -> Self {
+        self.lift(other, product_signs)
+    }
+
+    fn divide(&self, other: &Self) -> Self {
+        self.lift(other, divide_signs)
+    }
+
+    fn uminus(&self) -> Self {
+        let mut result = self.0 & ZERO;
+        if self.0 & NEGATIVE != 0 {
+            result |= POSITIVE;
+        }
+        if self.0 & POSITIVE != 0 {
+            result |= NEGATIVE;
+        }
+        Sign(result)
+    }
+
+    // Si tengono i segni di un operando compatibili con almeno un segno dell'altro
+    fn refine(op: Comparison, left: &Self, right: &Self) -> (Self, Self) {
+        let mut l = 0;
+        let mut r = 0;
+        for a in SIGNS.iter().filter(|s| left.0 & **s != 0) {
+            for b in SIGNS.iter().filter(|s| right.0 & **s != 0) {
+                if may_hold(op, *a, *b) {
+                    l |= a;
+                    r |= b;
+                }
+            }
+        }
+        (Sign(l), Sign(r))
+    }
+
+    fn as_constant(&self) -> Option<i32> {
+        if self.0 == ZERO {
+            Some(0)
+        } else {
+            None
+        }
+    }
+}
+
+impl Display for Sign {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let text = match self.0 {
+            0 => "⊥",
+            NEGATIVE => "<0",
+            ZERO => "0",
+            POSITIVE => ">0",
+            x if x == NEGATIVE | ZERO => "≤0",
+            x if x == NEGATIVE | POSITIVE => "≠0",
+            x if x == ZERO | POSITIVE => "≥0",
+            _ => "⊤",
+        };
+        write!(f, "{}", text)
+    }
+}
@@ -0,0 +1,175 @@
+use crate::analyzer::{AbstractDomain, AbstractState, Comparison, ValueDomain};
+use crate::ast::arithmetic::{ArithmeticExpression, ArithmeticNode};
+use crate::ast::State;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+// Stato non relazionale: ad ogni variabile è associato un valore astratto di V.
+// Le variabili assenti dalla mappa valgono top, quindi i top non vengono mai memorizzati.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueState<V> {
+    values: BTreeMap<String, V>,
+    bottom: bool,
+}
+
+impl<V: ValueDomain> ValueState<V> {
+    pub fn get(&self, var: &str) -> V {
+        if self.bottom {
+            return V::bottom();
+        }
+        self.values.get(var).cloned().unwrap_or_else(V::top)
+    }
+
+    pub fn set(&mut self, var: &str, value: V) {
+        if self.bottom {
+            return;
+        }
+        if value.is_bottom() {
+            *self = Self::bottom();
+        } else if value == V::top() {
+            self.values.remove(var);
+        } else {
+            self.values.insert(var.to_string(), value);
+        }
+    }
+
+    // Valutazione astratta di un'espressione
+    pub fn eval(&self, expr: &dyn ArithmeticExpression) -> V {
+        if self.bottom {
+            return V::bottom();
+        }
+        match expr.node() {
+            ArithmeticNode::Numeral(n) => V::constant(n.0),
+            ArithmeticNode::Variable(v) => self.get(&v.value),
+            ArithmeticNode::Add(e) => self.eval(&*e.left).add(&self.eval(&*e.right)),
+            ArithmeticNode::Minus(e) => self.eval(&*e.left).minus(&self.eval(&*e.right)),
+            ArithmeticNode::Product(e) => self.eval(&*e.left).product(&self.eval(&*e.right)),
+            ArithmeticNode::Divide(e) => self.eval(&*e.left).divide(&self.eval(&*e.right)),
+            ArithmeticNode::Uminus(e) => self.eval(&*e.right).uminus(),
+        }
+    }
+
+    // Combina variabile per variabile; le variabili mancanti valgono top
+    fn combine(&self, other: &Self, op: impl Fn(&V, &V) -> V) -> Self {
+        let mut result = Self::top();
+        let names: Vec<&String> = self.values.keys().chain(other.values.keys()).collect();
+        for name in names {
+            result.set(name, op(&self.get(name), &other.get(name)));
+        }
+        result
+    }
+}
+
+impl<V: ValueDomain> AbstractDomain for ValueState<V> {
+    fn bottom() -> Self {
+        ValueState {
+            values: BTreeMap::new(),
+            bottom: true,
+        }
+    }
+
+    fn top() -> Self {
+        ValueState {
+            values: BTreeMap::new(),
+            bottom: false,
+        }
+    }
+
+    fn is_bottom(&self) -> bool {
+        self.bottom
+    }
+
+    fn leq(&self, other: &Self) -> bool {
+        if self.bottom {
+            return true;
+        }
+        if other.bottom {
+            return false;
+        }
+        other
+            .values
+            .iter()
+            .all(|(name, value)| self.get(name).leq(value))
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        if self.bottom {
+            return other.clone();
+        }
+        if other.bottom {
+            return self.clone();
+        }
+        self.combine(other, |a, b| a.join(b))
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        if self.bottom || other.bottom {
+            return Self::bottom();
+        }
+        self.combine(other, |a, b| a.meet(b))
+    }
+
+    fn widening(&self, other: &Self) -> Self {
+        if self.bottom {
+            return other.clone();
+        }
+        if other.bottom {
+            return self.clone();
+        }
+        self.combine(other, |a, b| a.widening(b))
+    }
+}
+
+impl<V: ValueDomain> AbstractState for ValueState<V> {
+    fn from_state(state: &State) -> Self {
+        let mut result = Self::top();
+        for (name, value) in state {
+            result.set(name, V::constant(*value));
+        }
+        result
+    }
+
+    fn assign(&mut self, var: &str, expr: &dyn ArithmeticExpression) {
+        let value = self.eval(expr);
+        self.set(var, value);
+    }
+
+    fn assume_comparison(
+        &mut self,
+        op: Comparison,
+        left: &dyn ArithmeticExpression,
+        right: &dyn ArithmeticExpression,
+    ) {
+        let (left_value, right_value) = V::refine(op, &self.eval(left), &self.eval(right));
+        if left_value.is_bottom() || right_value.is_bottom() {
+            *self = Self::bottom();
+            return;
+        }
+        // si possono raffinare solo gli operandi che sono variabili
+        if let ArithmeticNode::Variable(v) = left.node() {
+            self.set(&v.value, left_value);
+        }
+        if let ArithmeticNode::Variable(v) = right.node() {
+            let refined = self.get(&v.value).meet(&right_value);
+            self.set(&v.value, refined);
+        }
+    }
+}
+
+impl<V: ValueDomain> Display for ValueState<V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.bottom {
+            return write!(f, "⊥");
+        }
+        if self.values.is_empty() {
+            return write!(f, "⊤");
+        }
+        let entries: Vec<String> = self
+            .values
+            .iter()
+            .map(|(name, value)| format!("{} ∈ {}", name, value))
+            .collect();
+        write!(f, "{{ {} }}", entries.join(", "))
+    }
+}
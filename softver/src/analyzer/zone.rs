@@ -0,0 +1,399 @@
+use crate::analyzer::dbm::{add_bounds, Dbm};
+use crate::analyzer::interval::{Bound, Interval};
+use crate::analyzer::linear::{linearize, LinearExpression};
+use crate::analyzer::value_state::ValueState;
+use crate::analyzer::{AbstractDomain, AbstractState, Comparison};
+use crate::ast::arithmetic::ArithmeticExpression;
+use crate::ast::State;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+// Dominio delle zone: vincoli x - y ≤ c e ±x ≤ c. Il nodo 0 della DBM è la
+// costante zero, la variabile x_k è il nodo k + 1; l'elemento (i, j) limita Vⱼ - Vᵢ.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Zone {
+    vars: Vec<String>,
+    dbm: Dbm,
+    bottom: bool,
+}
+
+const ZERO: usize = 0;
+
+impl Zone {
+    fn index_of(&self, var: &str) -> Option<usize> {
+        self.vars.iter().position(|v| v == var)
+    }
+
+    // Nodo della variabile, aggiungendola senza vincoli se non c'è
+    fn ensure_var(&mut self, var: &str) -> usize {
+        if let Some(k) = self.index_of(var) {
+            return k + 1;
+        }
+        let mapping: Vec<usize> = (0..self.dbm.size()).collect();
+        self.dbm = self.dbm.remap(self.dbm.size() + 1, &mapping);
+        self.vars.push(var.to_string());
+        self.vars.len()
+    }
+
+    // Porta entrambe le zone sullo stesso insieme di variabili
+    fn unify(&self, other: &Zone) -> (Zone, Zone) {
+        let mut left = self.clone();
+        for var in &other.vars {
+            left.ensure_var(var);
+        }
+        let mut mapping = vec![ZERO; other.dbm.size()];
+        for (k, var) in other.vars.iter().enumerate() {
+            mapping[k + 1] = left.index_of(var).unwrap() + 1;
+        }
+        let right = Zone {
+            vars: left.vars.clone(),
+            dbm: other.dbm.remap(left.dbm.size(), &mapping),
+            bottom: other.bottom,
+        };
+        (left, right)
+    }
+
+    pub fn close(&mut self) {
+        if self.bottom {
+            return;
+        }
+        self.dbm.close();
+        if self.dbm.is_empty() {
+            *self = Zone::bottom();
+        }
+    }
+
+    // Proiezione sul nodo i di una zona già chiusa
+    fn interval_at(&self, i: usize) -> Interval {
+        if self.bottom {
+            return Interval::Bottom;
+        }
+        let high = match self.dbm.get(ZERO, i) {
+            Some(c) => Bound::Finite(c),
+            None => Bound::PosInf,
+        };
+        let low = match self.dbm.get(i, ZERO) {
+            Some(c) => Bound::Finite(-c),
+            None => Bound::NegInf,
+        };
+        Interval::range(low, high)
+    }
+
+    // Proiezione non relazionale, usata per le espressioni che la zona non rappresenta
+    pub fn to_intervals(&self) -> ValueState<Interval> {
+        if self.bottom {
+            return ValueState::bottom();
+        }
+        let mut closed = self.clone();
+        closed.close();
+        let mut result = ValueState::top();
+        for (k, var) in closed.vars.iter().enumerate() {
+            result.set(var, closed.interval_at(k + 1));
+        }
+        result
+    }
+
+    fn set_interval(&mut self, i: usize, interval: Interval) {
+        match interval {
+            Interval::Bottom => *self = Zone::bottom(),
+            Interval::Range { low, high } => {
+                if let Bound::Finite(h) = high {
+                    self.dbm.constrain(ZERO, i, h);
+                }
+                if let Bound::Finite(l) = low {
+                    if let Some(c) = l.checked_neg() {
+                        self.dbm.constrain(i, ZERO, c);
+                    }
+                }
+            }
+        }
+    }
+
+    // x := x + c
+    fn shift(&mut self, i: usize, c: i64) {
+        for j in 0..self.dbm.size() {
+            if j == i {
+                continue;
+            }
+            // Vᵢ - Vⱼ cresce di c, Vⱼ - Vᵢ diminuisce di c
+            let outgoing = add_bounds(self.dbm.get(j, i), Some(c));
+            self.dbm.set(j, i, outgoing);
+            let incoming = c.checked_neg().and_then(|m| add_bounds(self.dbm.get(i, j), Some(m)));
+            self.dbm.set(i, j, incoming);
+        }
+    }
+
+    // Aggiunge e ≤ 0 se e ha forma di zona; altrimenti restituisce false
+    fn add_linear_leq(&mut self, e: &LinearExpression) -> bool {
+        let terms: Vec<(String, i64)> = e
+            .coefficients
+            .iter()
+            .map(|(v, a)| (v.clone(), *a))
+            .collect();
+        let Some(c) = e.constant.checked_neg() else {
+            return false;
+        };
+        match terms.as_slice() {
+            [] => {
+                if e.constant > 0 {
+                    *self = Zone::bottom();
+                }
+            }
+            // x ≤ -c
+            [(x, 1)] => {
+                let i = self.ensure_var(x);
+                self.dbm.constrain(ZERO, i, c);
+            }
+            // -x ≤ -c
+            [(x, -1)] => {
+                let i = self.ensure_var(x);
+                self.dbm.constrain(i, ZERO, c);
+            }
+            // x - y ≤ -c (i termini sono ordinati per nome)
+            [(x, 1), (y, -1)] | [(y, -1), (x, 1)] => {
+                let i = self.ensure_var(x);
+                let j = self.ensure_var(y);
+                self.dbm.constrain(j, i, c);
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    // Raffinamento con gli intervalli quando il vincolo non è di zona
+    fn assume_with_intervals(
+        &mut self,
+        op: Comparison,
+        left: &dyn ArithmeticExpression,
+        right: &dyn ArithmeticExpression,
+    ) {
+        let mut intervals = self.to_intervals();
+        intervals.assume_comparison(op, left, right);
+        if intervals.is_bottom() {
+            *self = Zone::bottom();
+            return;
+        }
+        let vars = self.vars.clone();
+        for (k, var) in vars.iter().enumerate() {
+            self.set_interval(k + 1, intervals.get(var));
+        }
+    }
+}
+
+impl AbstractDomain for Zone {
+    fn bottom() -> Self {
+        Zone {
+            vars: Vec::new(),
+            dbm: Dbm::new(1),
+            bottom: true,
+        }
+    }
+
+    fn top() -> Self {
+        Zone {
+            vars: Vec::new(),
+            dbm: Dbm::new(1),
+            bottom: false,
+        }
+    }
+
+    fn is_bottom(&self) -> bool {
+        self.bottom
+    }
+
+    fn leq(&self, other: &Self) -> bool {
+        if self.bottom {
+            return true;
+        }
+        if other.bottom {
+            return false;
+        }
+        let (mut left, right) = self.unify(other);
+        left.close();
+        left.bottom || left.dbm.leq(&right.dbm)
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        if self.bottom {
+            return other.clone();
+        }
+        if other.bottom {
+            return self.clone();
+        }
+        let (mut left, mut right) = self.unify(other);
+        left.close();
+        right.close();
+        if left.bottom {
+            return right;
+        }
+        if right.bottom {
+            return left;
+        }
+        left.dbm = left.dbm.join(&right.dbm);
+        left
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        if self.bottom || other.bottom {
+            return Zone::bottom();
+        }
+        let (mut left, right) = self.unify(other);
+        left.dbm = left.dbm.meet(&right.dbm);
+        left.close();
+        left
+    }
+
+    // Come per gli ottagoni il primo argomento non viene chiuso
+    fn widening(&self, other: &Self) -> Self {
+        if self.bottom {
+            return other.clone();
+        }
+        if other.bottom {
+            return self.clone();
+        }
+        let (mut left, mut right) = self.unify(other);
+        right.close();
+        left.dbm = left.dbm.widening(&right.dbm);
+        left
+    }
+}
+
+impl AbstractState for Zone {
+    fn from_state(state: &State) -> Self {
+        let mut result = Zone::top();
+        // ordine stabile delle variabili, lo stato concreto è una HashMap
+        let mut entries: Vec<(&String, &i32)> = state.iter().collect();
+        entries.sort();
+        for (name, value) in entries {
+            let i = result.ensure_var(name);
+            result.set_interval(i, Interval::new(*value as i64, *value as i64));
+        }
+        result.close();
+        result
+    }
+
+    fn assign(&mut self, var: &str, expr: &dyn ArithmeticExpression) {
+        self.close();
+        if self.bottom {
+            return;
+        }
+        let i = self.ensure_var(var);
+        let linear = linearize(expr);
+        let terms: Option<Vec<(String, i64)>> = linear.as_ref().map(|e| {
+            e.coefficients
+                .iter()
+                .map(|(v, a)| (v.clone(), *a))
+                .collect()
+        });
+        match (linear.as_ref(), terms.as_deref()) {
+            // x := c
+            (Some(e), Some([])) => {
+                self.dbm.forget(i);
+                self.set_interval(i, Interval::new(e.constant, e.constant));
+            }
+            // x := x + c
+            (Some(e), Some([(y, 1)])) if y == var => self.shift(i, e.constant),
+            // x := y + c
+            (Some(e), Some([(y, 1)])) => {
+                self.dbm.forget(i);
+                let j = self.ensure_var(y);
+                self.dbm.constrain(j, i, e.constant);
+                if let Some(c) = e.constant.checked_neg() {
+                    self.dbm.constrain(i, j, c);
+                }
+            }
+            // assegnamento generico: proiezione sugli intervalli
+            _ => {
+                let value = self.to_intervals().eval(expr);
+                self.dbm.forget(i);
+                self.set_interval(i, value);
+            }
+        }
+        self.close();
+    }
+
+    fn assume_comparison(
+        &mut self,
+        op: Comparison,
+        left: &dyn ArithmeticExpression,
+        right: &dyn ArithmeticExpression,
+    ) {
+        self.close();
+        if self.bottom {
+            return;
+        }
+        // si ragiona su e = left - right confrontato con 0
+        let difference = match (linearize(left), linearize(right)) {
+            (Some(l), Some(r)) => l.combine(1, &r, -1),
+            _ => None,
+        };
+        let Some(e) = difference else {
+            self.assume_with_intervals(op, left, right);
+            self.close();
+            return;
+        };
+        let minus_e = e.scale(-1);
+        let constraints: Option<Vec<LinearExpression>> = match op {
+            Comparison::LessEqual => Some(vec![e.clone()]),
+            Comparison::Less => e.combine(1, &LinearExpression::constant(1), 1).map(|c| vec![c]),
+            Comparison::GreatEqual => minus_e.map(|c| vec![c]),
+            Comparison::Great => minus_e
+                .and_then(|m| m.combine(1, &LinearExpression::constant(1), 1))
+                .map(|c| vec![c]),
+            Comparison::Equal => minus_e.map(|m| vec![e.clone(), m]),
+            Comparison::NotEqual => None,
+        };
+        let handled = match constraints {
+            Some(constraints) => constraints.iter().all(|c| {
+                let mut attempt = self.clone();
+                let ok = attempt.add_linear_leq(c);
+                if ok {
+                    *self = attempt;
+                }
+                ok
+            }),
+            None => false,
+        };
+        if !handled {
+            self.assume_with_intervals(op, left, right);
+        }
+        self.close();
+    }
+}
+
+impl Display for Zone {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut closed = self.clone();
+        closed.close();
+        if closed.bottom {
+            return write!(f, "⊥");
+        }
+        let mut entries = Vec::new();
+        for (k, var) in closed.vars.iter().enumerate() {
+            let interval = closed.interval_at(k + 1);
+            if interval != Interval::top() {
+                entries.push(format!("{} ∈ {}", var, interval));
+            }
+        }
+        for (k, x) in closed.vars.iter().enumerate() {
+            for (h, y) in closed.vars.iter().enumerate() {
+                let (i, j) = (k + 1, h + 1);
+                if i == j {
+                    continue;
+                }
+                // x - y ≤ c, omesso se già implicato dagli intervalli
+                let implied = add_bounds(closed.dbm.get(ZERO, i), closed.dbm.get(j, ZERO));
+                if let Some(c) = closed.dbm.get(j, i) {
+                    if implied != Some(c) {
+                        entries.push(format!("{} - {} ≤ {}", x, y, c));
+                    }
+                }
+            }
+        }
+        if entries.is_empty() {
+            write!(f, "⊤")
+        } else {
+            write!(f, "{{ {} }}", entries.join(", "))
+        }
+    }
+}
@@ -1,22 +1,152 @@
 use crate::ast::State;
-use std::fmt::Debug;
+use std::fmt;
+use std::fmt::{Debug, Display, Formatter};
 
 
 pub trait ArithmeticExpression: Debug  {
     fn evaluate(&self, state: &State) -> i32;
+    // Vista sul nodo concreto, usata dalle analisi per ispezionare l'albero
+    fn node(&self) -> ArithmeticNode<'_>;
 }
 
+#[derive(Debug, Clone, Copy)]
+pub enum ArithmeticNode<'a> {
+    Numeral(&'a Numeral),
+    Variable(&'a Variable),
+    Add(&'a Add),
+    Product(&'a Product),
+    Minus(&'a Minus),
+    Uminus(&'a Uminus),
+    Divide(&'a Divide),
+}
+
+impl dyn ArithmeticExpression + '_ {
+    // Copia dell'albero a partire da un riferimento
+    pub fn to_boxed(&self) -> Box<dyn ArithmeticExpression> {
+        match self.node() {
+            ArithmeticNode::Numeral(n) => Box::new(n.clone()),
+            ArithmeticNode::Variable(v) => Box::new(v.clone()),
+            ArithmeticNode::Add(a) => Box::new(a.clone()),
+            ArithmeticNode::Product(p) => Box::new(p.clone()),
+            ArithmeticNode::Minus(m) => Box::new(m.clone()),
+            ArithmeticNode::Uminus(u) => Box::new(u.clone()),
+            ArithmeticNode::Divide(d) => Box::new(d.clone()),
+        }
+    }
+}
+
+impl Clone for Box<dyn ArithmeticExpression> {
+    fn clone(&self) -> Self {
+        self.to_boxed()
+    }
+}
+
+// Uguaglianza strutturale tra alberi
+impl PartialEq for dyn ArithmeticExpression + '_ {
+    fn eq(&self, other: &Self) -> bool {
+        match (self.node(), other.node()) {
+            (ArithmeticNode::Numeral(a), ArithmeticNode::Numeral(b)) => a == b,
+            (ArithmeticNode::Variable(a), ArithmeticNode::Variable(b)) => a == b,
+            (ArithmeticNode::Add(a), ArithmeticNode::Add(b)) => {
+                *a.left == *b.left && *a.right == *b.right
+            }
+            (ArithmeticNode::Product(a), ArithmeticNode::Product(b)) => {
+                *a.left == *b.left && *a.right == *b.right
+            }
+            (ArithmeticNode::Minus(a), ArithmeticNode::Minus(b)) => {
+                *a.left == *b.left && *a.right == *b.right
+            }
+            (ArithmeticNode::Uminus(a), ArithmeticNode::Uminus(b)) => *a.right == *b.right,
+            (ArithmeticNode::Divide(a), ArithmeticNode::Divide(b)) => {
+                *a.left == *b.left && *a.right == *b.right
+            }
+            _ => false,
+        }
+    }
+}
+
+// Livello di precedenza: 1 somme e differenze, 2 prodotti e divisioni,
+// 3 meno unario (anche un numerale negativo si stampa così), 4 atomi
+fn precedence(expr: &dyn ArithmeticExpression) -> u8 {
+    match expr.node() {
+        ArithmeticNode::Add(_) | ArithmeticNode::Minus(_) => 1,
+        ArithmeticNode::Product(_) | ArithmeticNode::Divide(_) => 2,
+        ArithmeticNode::Uminus(_) => 3,
+        ArithmeticNode::Numeral(n) if n.0 < 0 => 3,
+        ArithmeticNode::Numeral(_) | ArithmeticNode::Variable(_) => 4,
+    }
+}
+
+fn operand(f: &mut Formatter<'_>, expr: &dyn ArithmeticExpression, parens: bool) -> fmt::Result {
+    if parens {
+        write!(f, "({})", expr)
+    } else {
+        write!(f, "{}", expr)
+    }
+}
+
+// Gli operatori binari associano a sinistra: l'operando destro va tra parentesi
+// anche a parità di precedenza, così a - (b - c) non diventa a - b - c
+fn binary(
+    f: &mut Formatter<'_>,
+    left: &dyn ArithmeticExpression,
+    op: &str,
+    right: &dyn ArithmeticExpression,
+    level: u8,
+) -> fmt::Result {
+    operand(f, left, precedence(left) < level)?;
+    write!(f, " {} ", op)?;
+    operand(f, right, precedence(right) <= level)
+}
+
+// Sintassi concreta del linguaggio While, con le sole parentesi necessarie
+impl Display for dyn ArithmeticExpression + '_ {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.node() {
+            ArithmeticNode::Numeral(n) => write!(f, "{}", n.0),
+            ArithmeticNode::Variable(v) => write!(f, "{}", v.value),
+            ArithmeticNode::Add(e) => binary(f, &*e.left, "+", &*e.right, 1),
+            ArithmeticNode::Minus(e) => binary(f, &*e.left, "-", &*e.right, 1),
+            ArithmeticNode::Product(e) => binary(f, &*e.left, "*", &*e.right, 2),
+            ArithmeticNode::Divide(e) => binary(f, &*e.left, "/", &*e.right, 2),
+            // -5 è il numerale negativo, il meno applicato a 5 si scrive -(5)
+            ArithmeticNode::Uminus(e) => {
+                let parens = precedence(&*e.right) < 3
+                    || matches!(e.right.node(), ArithmeticNode::Numeral(_));
+                write!(f, "-")?;
+                operand(f, &*e.right, parens)
+            }
+        }
+    }
+}
+
+macro_rules! display_as_expression {
+    ($($node:ty),*) => {
+        $(impl Display for $node {
+            fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                Display::fmt(self as &dyn ArithmeticExpression, f)
+            }
+        })*
+    };
+}
+
+display_as_expression!(Numeral, Variable, Add, Product, Minus, Uminus, Divide);
+
  
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Numeral(pub i32);
 
 impl ArithmeticExpression for Numeral {
     fn evaluate(&self, _state: &State) -> i32 {
         self.0
     }
+
+    fn node(&self) -> ArithmeticNode<'_> {
+        ArithmeticNode::Numeral(self)
+    }
 }
  
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Variable {
     pub value: String,
 }
@@ -28,11 +158,15 @@ impl ArithmeticExpression for Variable {
             None => panic!("Variabile '{}' non trovata nello stato!", self.value),
         }
     }
+
+    fn node(&self) -> ArithmeticNode<'_> {
+        ArithmeticNode::Variable(self)
+    }
 }
 
 
  
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Add {
     pub left: Box<dyn ArithmeticExpression>,
     pub right: Box<dyn ArithmeticExpression>,
@@ -42,9 +176,13 @@ impl ArithmeticExpression for Add {
     fn evaluate(&self, state: &State) -> i32 {
         self.left.evaluate(state) + self.right.evaluate(state)
     }
+
+    fn node(&self) -> ArithmeticNode<'_> {
+        ArithmeticNode::Add(self)
+    }
 }
  
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Product {
     pub left: Box<dyn ArithmeticExpression>,
     pub right: Box<dyn ArithmeticExpression>,
@@ -54,9 +192,13 @@ impl ArithmeticExpression for Product {
     fn evaluate(&self, state: &State) -> i32 {
         self.left.evaluate(state) * self.right.evaluate(state)
     }
+
+    fn node(&self) -> ArithmeticNode<'_> {
+        ArithmeticNode::Product(self)
+    }
 }
  
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Minus {
     pub left: Box<dyn ArithmeticExpression>,
     pub right: Box<dyn ArithmeticExpression>,
@@ -66,9 +208,13 @@ impl ArithmeticExpression for Minus {
     fn evaluate(&self, state: &State) -> i32 {
         self.left.evaluate(state) - self.right.evaluate(state)
     }
+
+    fn node(&self) -> ArithmeticNode<'_> {
+        ArithmeticNode::Minus(self)
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Uminus{
     pub right : Box<dyn ArithmeticExpression>,
 }
@@ -77,9 +223,13 @@ impl ArithmeticExpression for Uminus{
     fn evaluate(&self, state: &State) -> i32 {
         - self.right.evaluate(state)
     }
+
+    fn node(&self) -> ArithmeticNode<'_> {
+        ArithmeticNode::Uminus(self)
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Divide {
     pub left: Box<dyn ArithmeticExpression>,
     pub right: Box<dyn ArithmeticExpression>,
@@ -89,4 +239,8 @@ impl ArithmeticExpression for Divide {
     fn evaluate(&self, state: &State) -> i32 {
         self.left.evaluate(state) / self.right.evaluate(state)
     }
+
+    fn node(&self) -> ArithmeticNode<'_> {
+        ArithmeticNode::Divide(self)
+    }
 }
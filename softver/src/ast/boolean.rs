@@ -1,21 +1,159 @@
 use crate::ast::arithmetic::ArithmeticExpression;
 use crate::ast::State;
-use std::fmt::Debug;
+use std::fmt;
+use std::fmt::{Debug, Display, Formatter};
 
 pub trait BooleanExpression: Debug {
     fn evaluate(&self, state: &State) -> bool;
+    // Vista sul nodo concreto, usata dalle analisi per ispezionare l'albero
+    fn node(&self) -> BooleanNode<'_>;
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
+pub enum BooleanNode<'a> {
+    Boolean(&'a Boolean),
+    Equal(&'a Equal),
+    GreatEqual(&'a GreatEqual),
+    Great(&'a Great),
+    LessEqual(&'a LessEqual),
+    Less(&'a Less),
+    And(&'a And),
+    Or(&'a Or),
+    Not(&'a Not),
+}
+
+impl dyn BooleanExpression + '_ {
+    // Copia dell'albero a partire da un riferimento
+    pub fn to_boxed(&self) -> Box<dyn BooleanExpression> {
+        match self.node() {
+            BooleanNode::Boolean(x) => Box::new(x.clone()),
+            BooleanNode::Equal(x) => Box::new(x.clone()),
+            BooleanNode::GreatEqual(x) => Box::new(x.clone()),
+            BooleanNode::Great(x) => Box::new(x.clone()),
+            BooleanNode::LessEqual(x) => Box::new(x.clone()),
+            BooleanNode::Less(x) => Box::new(x.clone()),
+            BooleanNode::And(x) => Box::new(x.clone()),
+            BooleanNode::Or(x) => Box::new(x.clone()),
+            BooleanNode::Not(x) => Box::new(x.clone()),
+        }
+    }
+}
+
+impl Clone for Box<dyn BooleanExpression> {
+    fn clone(&self) -> Self {
+        self.to_boxed()
+    }
+}
+
+// Uguaglianza strutturale tra alberi
+impl PartialEq for dyn BooleanExpression + '_ {
+    fn eq(&self, other: &Self) -> bool {
+        match (self.node(), other.node()) {
+            (BooleanNode::Boolean(a), BooleanNode::Boolean(b)) => a == b,
+            (BooleanNode::Equal(a), BooleanNode::Equal(b)) => {
+                *a.left == *b.left && *a.right == *b.right
+            }
+            (BooleanNode::GreatEqual(a), BooleanNode::GreatEqual(b)) => {
+                *a.left == *b.left && *a.right == *b.right
+            }
+            (BooleanNode::Great(a), BooleanNode::Great(b)) => {
+                *a.left == *b.left && *a.right == *b.right
+            }
+            (BooleanNode::LessEqual(a), BooleanNode::LessEqual(b)) => {
+                *a.left == *b.left && *a.right == *b.right
+            }
+            (BooleanNode::Less(a), BooleanNode::Less(b)) => {
+                *a.left == *b.left && *a.right == *b.right
+            }
+            (BooleanNode::And(a), BooleanNode::And(b)) => {
+                *a.left == *b.left && *a.right == *b.right
+            }
+            (BooleanNode::Or(a), BooleanNode::Or(b)) => {
+                *a.left == *b.left && *a.right == *b.right
+            }
+            (BooleanNode::Not(a), BooleanNode::Not(b)) => *a.expression == *b.expression,
+            _ => false,
+        }
+    }
+}
+
+// Livello di precedenza: 1 or, 2 and, 3 negazione, 4 costanti e confronti
+// (gli operandi aritmetici di un confronto non hanno mai bisogno di parentesi)
+fn precedence(expr: &dyn BooleanExpression) -> u8 {
+    match expr.node() {
+        BooleanNode::Or(_) => 1,
+        BooleanNode::And(_) => 2,
+        BooleanNode::Not(_) => 3,
+        _ => 4,
+    }
+}
+
+fn operand(f: &mut Formatter<'_>, expr: &dyn BooleanExpression, parens: bool) -> fmt::Result {
+    if parens {
+        write!(f, "({})", expr)
+    } else {
+        write!(f, "{}", expr)
+    }
+}
+
+fn binary(
+    f: &mut Formatter<'_>,
+    left: &dyn BooleanExpression,
+    op: &str,
+    right: &dyn BooleanExpression,
+    level: u8,
+) -> fmt::Result {
+    operand(f, left, precedence(left) < level)?;
+    write!(f, " {} ", op)?;
+    operand(f, right, precedence(right) <= level)
+}
+
+// Sintassi concreta del linguaggio While, con le sole parentesi necessarie
+impl Display for dyn BooleanExpression + '_ {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.node() {
+            BooleanNode::Boolean(b) => write!(f, "{}", b.0),
+            BooleanNode::Equal(e) => write!(f, "{} = {}", e.left, e.right),
+            BooleanNode::GreatEqual(e) => write!(f, "{} >= {}", e.left, e.right),
+            BooleanNode::Great(e) => write!(f, "{} > {}", e.left, e.right),
+            BooleanNode::LessEqual(e) => write!(f, "{} <= {}", e.left, e.right),
+            BooleanNode::Less(e) => write!(f, "{} < {}", e.left, e.right),
+            BooleanNode::And(e) => binary(f, &*e.left, "&&", &*e.right, 2),
+            BooleanNode::Or(e) => binary(f, &*e.left, "||", &*e.right, 1),
+            BooleanNode::Not(e) => {
+                write!(f, "!")?;
+                operand(f, &*e.expression, precedence(&*e.expression) < 3)
+            }
+        }
+    }
+}
+
+macro_rules! display_as_expression {
+    ($($node:ty),*) => {
+        $(impl Display for $node {
+            fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                Display::fmt(self as &dyn BooleanExpression, f)
+            }
+        })*
+    };
+}
+
+display_as_expression!(Boolean, Equal, GreatEqual, Great, LessEqual, Less, And, Or, Not);
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Boolean(pub bool);
 
 impl BooleanExpression for Boolean {
     fn evaluate(&self, _state: &State) -> bool {
         self.0
     }
+
+    fn node(&self) -> BooleanNode<'_> {
+        BooleanNode::Boolean(self)
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Equal {
     pub left: Box<dyn ArithmeticExpression>,
     pub right: Box<dyn ArithmeticExpression>,
@@ -25,9 +163,13 @@ impl BooleanExpression for Equal {
     fn evaluate(&self, state: &State) -> bool {
         self.left.evaluate(state) == self.right.evaluate(state)
     }
+
+    fn node(&self) -> BooleanNode<'_> {
+        BooleanNode::Equal(self)
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct GreatEqual {
     pub left: Box<dyn ArithmeticExpression>,
     pub right: Box<dyn ArithmeticExpression>,
@@ -37,8 +179,12 @@ impl BooleanExpression for GreatEqual {
     fn evaluate(&self, state: &State) -> bool {
         self.left.evaluate(state) >= self.right.evaluate(state)
     }
+
+    fn node(&self) -> BooleanNode<'_> {
+        BooleanNode::GreatEqual(self)
+    }
 }
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Great{
     pub left: Box<dyn ArithmeticExpression>,
     pub right: Box<dyn ArithmeticExpression>,
@@ -47,10 +193,14 @@ impl BooleanExpression for Great{
     fn evaluate(&self, state: &State) -> bool {
         self.left.evaluate(state) > self.right.evaluate(state)
     }
+
+    fn node(&self) -> BooleanNode<'_> {
+        BooleanNode::Great(self)
+    }
 }
 
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct LessEqual {
     pub left: Box<dyn ArithmeticExpression>,
     pub right: Box<dyn ArithmeticExpression>,
@@ -60,9 +210,13 @@ impl BooleanExpression for LessEqual {
     fn evaluate(&self, state: &State) -> bool {
         self.left.evaluate(state) <= self.right.evaluate(state)
     }
+
+    fn node(&self) -> BooleanNode<'_> {
+        BooleanNode::LessEqual(self)
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Less{
     pub left: Box<dyn ArithmeticExpression>,
     pub right: Box<dyn ArithmeticExpression>,
@@ -72,9 +226,13 @@ impl BooleanExpression for Less{
     fn evaluate(&self, state: &State) -> bool {
         self.left.evaluate(state) < self.right.evaluate(state)   
     }
+
+    fn node(&self) -> BooleanNode<'_> {
+        BooleanNode::Less(self)
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct And {
     pub left: Box<dyn BooleanExpression>,
     pub right: Box<dyn BooleanExpression>,
@@ -84,9 +242,13 @@ impl BooleanExpression for And {
     fn evaluate(&self, state: &State) -> bool {
         self.left.evaluate(state) && self.right.evaluate(state)
     }
+
+    fn node(&self) -> BooleanNode<'_> {
+        BooleanNode::And(self)
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Or {
     pub left: Box<dyn BooleanExpression>,
     pub right: Box<dyn BooleanExpression>,
@@ -96,9 +258,13 @@ impl BooleanExpression for Or {
     fn evaluate(&self, state: &State) -> bool {
         self.left.evaluate(state) || self.right.evaluate(state)
     }
+
+    fn node(&self) -> BooleanNode<'_> {
+        BooleanNode::Or(self)
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Not {
     pub expression: Box<dyn BooleanExpression>,
 }
@@ -107,4 +273,8 @@ impl BooleanExpression for Not {
     fn evaluate(&self, state: &State) -> bool {
         !(self.expression.evaluate(state))
     }
+
+    fn node(&self) -> BooleanNode<'_> {
+        BooleanNode::Not(self)
+    }
 }
@@ -3,5 +3,72 @@ pub mod boolean;
 pub mod statement;
 
 
+use crate::ast::boolean::BooleanExpression;
+use crate::ast::statement::Statement;
 use std::collections::HashMap;
+use std::fmt;
+use std::fmt::{Display, Formatter};
 pub type State = HashMap<String, i32>;
+
+// Tripla di Hoare {pre} program {post}; gli invarianti dei cicli stanno nei While
+#[derive(Debug, Clone)]
+pub struct Specification {
+    pub pre: Box<dyn BooleanExpression>,
+    pub program: Box<dyn Statement>,
+    pub post: Box<dyn BooleanExpression>,
+}
+
+impl Display for Specification {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{{{}}}\n{}\n{{{}}}", self.pre, self.program, self.post)
+    }
+}
+
+// Cosa succede quando un'operazione esce dagli interi a 32 bit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowSemantics {
+    // aritmetica modulo 2^32 in complemento a due
+    Wrapping,
+    // l'overflow è un errore a tempo di esecuzione
+    Error,
+}
+
+impl OverflowSemantics {
+    fn apply(
+        self,
+        wrapping: fn(i32, i32) -> i32,
+        checked: fn(i32, i32) -> Option<i32>,
+        a: i32,
+        b: i32,
+    ) -> Option<i32> {
+        match self {
+            OverflowSemantics::Wrapping => Some(wrapping(a, b)),
+            OverflowSemantics::Error => checked(a, b),
+        }
+    }
+
+    // Le operazioni concrete: None se il risultato è un errore
+    pub fn add(self, a: i32, b: i32) -> Option<i32> {
+        self.apply(i32::wrapping_add, i32::checked_add, a, b)
+    }
+
+    pub fn minus(self, a: i32, b: i32) -> Option<i32> {
+        self.apply(i32::wrapping_sub, i32::checked_sub, a, b)
+    }
+
+    pub fn product(self, a: i32, b: i32) -> Option<i32> {
+        self.apply(i32::wrapping_mul, i32::checked_mul, a, b)
+    }
+
+    // la divisione per zero è un errore con entrambe le semantiche
+    pub fn divide(self, a: i32, b: i32) -> Option<i32> {
+        if b == 0 {
+            return None;
+        }
+        self.apply(i32::wrapping_div, i32::checked_div, a, b)
+    }
+
+    pub fn uminus(self, a: i32) -> Option<i32> {
+        self.minus(0, a)
+    }
+}
@@ -1,74 +1,287 @@
 use crate::ast::arithmetic::ArithmeticExpression;
 use crate::ast::boolean::BooleanExpression;
 use crate::ast::State;
-use std::fmt::Debug;
+use crate::printer;
+use std::fmt;
+use std::fmt::{Debug, Display, Formatter};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Motivo per cui un'esecuzione concreta si ferma prima della fine
+#[derive(Debug, Clone, PartialEq)]
+pub enum Halt {
+    // un assert con la condizione falsa: è un errore
+    AssertionFailed(String),
+    // un assume con la condizione falsa: l'esecuzione non prosegue, senza errore
+    Blocked,
+}
+
+impl Display for Halt {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Halt::AssertionFailed(condition) => write!(f, "Asserzione violata: {}", condition),
+            Halt::Blocked => write!(f, "Esecuzione bloccata da un assume"),
+        }
+    }
+}
+
+// Identità di uno statement, assegnata quando il nodo viene creato. Le copie e le
+// trasformazioni che ricostruiscono il nodo al suo posto la conservano, quindi
+// non dipende dall'indirizzo (gli Skip, senza campi, ne condividerebbero uno solo).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(usize);
+
+impl NodeId {
+    pub fn fresh() -> Self {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        NodeId(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
 
 pub trait Statement: Debug {
-    fn evaluate(&self, state: &mut State);
+    fn evaluate(&self, state: &mut State) -> Result<(), Halt>;
+    // Vista sul nodo concreto, usata dalle analisi per ispezionare l'albero
+    fn node(&self) -> StatementNode<'_>;
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum StatementNode<'a> {
+    Assign(&'a Assign),
+    Skip(&'a Skip),
+    Concat(&'a Concat),
+    IfThenElse(&'a IfThenElse),
+    While(&'a While),
+    Assert(&'a Assert),
+    Assume(&'a Assume),
+}
+
+impl dyn Statement + '_ {
+    pub fn id(&self) -> NodeId {
+        match self.node() {
+            StatementNode::Assign(x) => x.id,
+            StatementNode::Skip(x) => x.id,
+            StatementNode::Concat(x) => x.id,
+            StatementNode::IfThenElse(x) => x.id,
+            StatementNode::While(x) => x.id,
+            StatementNode::Assert(x) => x.id,
+            StatementNode::Assume(x) => x.id,
+        }
+    }
+
+    // Copia dell'albero a partire da un riferimento
+    pub fn to_boxed(&self) -> Box<dyn Statement> {
+        match self.node() {
+            StatementNode::Assign(x) => Box::new(x.clone()),
+            StatementNode::Skip(x) => Box::new(x.clone()),
+            StatementNode::Concat(x) => Box::new(x.clone()),
+            StatementNode::IfThenElse(x) => Box::new(x.clone()),
+            StatementNode::While(x) => Box::new(x.clone()),
+            StatementNode::Assert(x) => Box::new(x.clone()),
+            StatementNode::Assume(x) => Box::new(x.clone()),
+        }
+    }
+}
+
+impl Clone for Box<dyn Statement> {
+    fn clone(&self) -> Self {
+        self.to_boxed()
+    }
+}
+
+// Uguaglianza strutturale tra alberi
+impl PartialEq for dyn Statement + '_ {
+    fn eq(&self, other: &Self) -> bool {
+        match (self.node(), other.node()) {
+            (StatementNode::Assign(a), StatementNode::Assign(b)) => {
+                a.var_name == b.var_name && *a.expr == *b.expr
+            }
+            (StatementNode::Skip(_), StatementNode::Skip(_)) => true,
+            (StatementNode::Concat(a), StatementNode::Concat(b)) => {
+                *a.first == *b.first && *a.second == *b.second
+            }
+            (StatementNode::IfThenElse(a), StatementNode::IfThenElse(b)) => {
+                *a.guard == *b.guard && *a.true_expr == *b.true_expr && *a.false_expr == *b.false_expr
+            }
+            (StatementNode::While(a), StatementNode::While(b)) => {
+                let same_invariant = match (&a.invariant, &b.invariant) {
+                    (Some(x), Some(y)) => **x == **y,
+                    (None, None) => true,
+                    _ => false,
+                };
+                *a.guard == *b.guard && *a.body == *b.body && same_invariant
+            }
+            (StatementNode::Assert(a), StatementNode::Assert(b)) => *a.condition == *b.condition,
+            (StatementNode::Assume(a), StatementNode::Assume(b)) => *a.condition == *b.condition,
+            _ => false,
+        }
+    }
 }
 
-#[derive(Debug)]
+// Sorgente While indentato, senza il ritorno a capo finale
+impl Display for dyn Statement + '_ {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", printer::program(self).trim_end_matches('\n'))
+    }
+}
+
+macro_rules! display_as_statement {
+    ($($node:ty),*) => {
+        $(impl Display for $node {
+            fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                Display::fmt(self as &dyn Statement, f)
+            }
+        })*
+    };
+}
+
+display_as_statement!(Assign, Skip, Concat, IfThenElse, While, Assert, Assume);
+
+#[derive(Debug, Clone)]
 pub struct Assign {
+    pub id: NodeId,
     pub var_name: String,
     pub expr: Box<dyn ArithmeticExpression>,
 }
 
 impl Statement for Assign {
-    fn evaluate(&self, state: &mut State) {
+    fn evaluate(&self, state: &mut State) -> Result<(), Halt> {
         let value = self.expr.evaluate(state);
         state.insert(self.var_name.clone(), value);
+        Ok(())
+    }
+
+    fn node(&self) -> StatementNode<'_> {
+        StatementNode::Assign(self)
     }
 }
 
-#[derive(Debug)]
-pub struct Skip;
+#[derive(Debug, Clone)]
+pub struct Skip {
+    pub id: NodeId,
+}
+
+impl Skip {
+    pub fn new() -> Self {
+        Skip {
+            id: NodeId::fresh(),
+        }
+    }
+}
+
+impl Default for Skip {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl Statement for Skip {
-    fn evaluate(&self, _state: &mut State) {
+    fn evaluate(&self, _state: &mut State) -> Result<(), Halt> {
         // Do nothing
+        Ok(())
+    }
+
+    fn node(&self) -> StatementNode<'_> {
+        StatementNode::Skip(self)
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Concat {
+    pub id: NodeId,
     pub first: Box<dyn Statement>,
     pub second: Box<dyn Statement>,
 }
 
 impl Statement for Concat {
-    fn evaluate(&self, state: &mut State) {
-        self.first.evaluate(state);
-        self.second.evaluate(state);
+    fn evaluate(&self, state: &mut State) -> Result<(), Halt> {
+        self.first.evaluate(state)?;
+        self.second.evaluate(state)
+    }
+
+    fn node(&self) -> StatementNode<'_> {
+        StatementNode::Concat(self)
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct IfThenElse {
+    pub id: NodeId,
     pub guard: Box<dyn BooleanExpression>,
     pub true_expr: Box<dyn Statement>,
     pub false_expr: Box<dyn Statement>,
 }
 
 impl Statement for IfThenElse {
-    fn evaluate(&self, state: &mut State) {
+    fn evaluate(&self, state: &mut State) -> Result<(), Halt> {
         if self.guard.evaluate(state) {
-            self.true_expr.evaluate(state);
+            self.true_expr.evaluate(state)
         } else {
-            self.false_expr.evaluate(state);
+            self.false_expr.evaluate(state)
         }
     }
+
+    fn node(&self) -> StatementNode<'_> {
+        StatementNode::IfThenElse(self)
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct While {
+    pub id: NodeId,
     pub guard: Box<dyn BooleanExpression>,
     pub body: Box<dyn Statement>,
+    // invariante dichiarato dall'utente con la clausola `invariant`
+    pub invariant: Option<Box<dyn BooleanExpression>>,
 }
 
 impl Statement for While {
-    fn evaluate(&self, state: &mut State) {
+    fn evaluate(&self, state: &mut State) -> Result<(), Halt> {
         while self.guard.evaluate(state) {
-            self.body.evaluate(state);
+            self.body.evaluate(state)?;
+        }
+        Ok(())
+    }
+
+    fn node(&self) -> StatementNode<'_> {
+        StatementNode::While(self)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Assert {
+    pub id: NodeId,
+    pub condition: Box<dyn BooleanExpression>,
+}
+
+impl Statement for Assert {
+    fn evaluate(&self, state: &mut State) -> Result<(), Halt> {
+        if self.condition.evaluate(state) {
+            Ok(())
+        } else {
+            Err(Halt::AssertionFailed(self.condition.to_string()))
         }
     }
+
+    fn node(&self) -> StatementNode<'_> {
+        StatementNode::Assert(self)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Assume {
+    pub id: NodeId,
+    pub condition: Box<dyn BooleanExpression>,
+}
+
+impl Statement for Assume {
+    fn evaluate(&self, state: &mut State) -> Result<(), Halt> {
+        if self.condition.evaluate(state) {
+            Ok(())
+        } else {
+            Err(Halt::Blocked)
+        }
+    }
+
+    fn node(&self) -> StatementNode<'_> {
+        StatementNode::Assume(self)
+    }
 }
@@ -0,0 +1,235 @@
+use crate::ast::boolean::BooleanExpression;
+use crate::ast::statement::{Assign, IfThenElse, NodeId, Statement, StatementNode, While};
+
+// Punto di programma: l'etichetta di un nodo del grafo
+pub type Point = usize;
+
+// Azione portata da un arco: un assegnamento, una guardia (con la sua polarità)
+// oppure un passaggio senza effetto (join dopo un if, ritorno in testa al ciclo)
+#[derive(Debug, Clone, Copy)]
+pub enum Action<'a> {
+    Assign(&'a Assign),
+    Assume(&'a dyn BooleanExpression, bool),
+    Skip,
+}
+
+#[derive(Debug, Clone)]
+pub struct Edge<'a> {
+    pub from: Point,
+    pub to: Point,
+    pub action: Action<'a>,
+}
+
+// Sequenza massimale di punti collegati da archi senza diramazioni
+#[derive(Debug, Clone, PartialEq)]
+pub struct BasicBlock {
+    pub points: Vec<Point>,
+}
+
+// Grafo di controllo costruito da uno statement. I nodi sono i punti di programma
+// 0..points(), ogni statement va da un punto di ingresso a uno di uscita.
+#[derive(Debug)]
+pub struct Cfg<'a> {
+    points: usize,
+    edges: Vec<Edge<'a>>,
+    entry: Point,
+    exit: Point,
+    loop_heads: Vec<(Point, &'a While)>,
+    // archi che chiudono un ramo di un if: (da, a, if, ramo vero)
+    branches: Vec<(Point, Point, &'a IfThenElse, bool)>,
+    // per ogni statement dell'AST: (identità, statement, ingresso, uscita)
+    statements: Vec<(NodeId, &'a dyn Statement, Point, Point)>,
+}
+
+impl<'a> Cfg<'a> {
+    pub fn new(program: &'a dyn Statement) -> Self {
+        let mut cfg = Cfg {
+            points: 1,
+            edges: Vec::new(),
+            entry: 0,
+            exit: 0,
+            loop_heads: Vec::new(),
+            branches: Vec::new(),
+            statements: Vec::new(),
+        };
+        cfg.exit = cfg.build(program, 0);
+        cfg
+    }
+
+    fn new_point(&mut self) -> Point {
+        self.points += 1;
+        self.points - 1
+    }
+
+    fn add_edge(&mut self, from: Point, to: Point, action: Action<'a>) {
+        self.edges.push(Edge { from, to, action });
+    }
+
+    // Aggiunge lo statement a partire dal punto `entry` e restituisce il punto di uscita
+    fn build(&mut self, stmt: &'a dyn Statement, entry: Point) -> Point {
+        let exit = match stmt.node() {
+            StatementNode::Assign(a) => {
+                let exit = self.new_point();
+                self.add_edge(entry, exit, Action::Assign(a));
+                exit
+            }
+            StatementNode::Skip(_) => {
+                let exit = self.new_point();
+                self.add_edge(entry, exit, Action::Skip);
+                exit
+            }
+            StatementNode::Concat(c) => {
+                let middle = self.build(&*c.first, entry);
+                self.build(&*c.second, middle)
+            }
+            StatementNode::IfThenElse(i) => {
+                let then_entry = self.new_point();
+                self.add_edge(entry, then_entry, Action::Assume(&*i.guard, true));
+                let else_entry = self.new_point();
+                self.add_edge(entry, else_entry, Action::Assume(&*i.guard, false));
+                let then_exit = self.build(&*i.true_expr, then_entry);
+                let else_exit = self.build(&*i.false_expr, else_entry);
+                let exit = self.new_point();
+                self.add_edge(then_exit, exit, Action::Skip);
+                self.add_edge(else_exit, exit, Action::Skip);
+                self.branches.push((then_exit, exit, i, true));
+                self.branches.push((else_exit, exit, i, false));
+                exit
+            }
+            // il punto di ingresso fa da testa del ciclo
+            StatementNode::While(w) => {
+                self.loop_heads.push((entry, w));
+                let body_entry = self.new_point();
+                self.add_edge(entry, body_entry, Action::Assume(&*w.guard, true));
+                let body_exit = self.build(&*w.body, body_entry);
+                self.add_edge(body_exit, entry, Action::Skip);
+                let exit = self.new_point();
+                self.add_edge(entry, exit, Action::Assume(&*w.guard, false));
+                exit
+            }
+            // un assert fallito ferma l'esecuzione come un assume falso:
+            // dopo entrambi la condizione vale
+            StatementNode::Assert(a) => {
+                let exit = self.new_point();
+                self.add_edge(entry, exit, Action::Assume(&*a.condition, true));
+                exit
+            }
+            StatementNode::Assume(a) => {
+                let exit = self.new_point();
+                self.add_edge(entry, exit, Action::Assume(&*a.condition, true));
+                exit
+            }
+        };
+        self.statements.push((stmt.id(), stmt, entry, exit));
+        exit
+    }
+
+    pub fn entry(&self) -> Point {
+        self.entry
+    }
+
+    pub fn exit(&self) -> Point {
+        self.exit
+    }
+
+    pub fn points(&self) -> usize {
+        self.points
+    }
+
+    pub fn edges(&self) -> &[Edge<'a>] {
+        &self.edges
+    }
+
+    pub fn successors(&self, point: Point) -> impl Iterator<Item = &Edge<'a>> {
+        self.edges.iter().filter(move |e| e.from == point)
+    }
+
+    pub fn predecessors(&self, point: Point) -> impl Iterator<Item = &Edge<'a>> {
+        self.edges.iter().filter(move |e| e.to == point)
+    }
+
+    pub fn loop_heads(&self) -> impl Iterator<Item = (Point, &'a While)> + '_ {
+        self.loop_heads.iter().copied()
+    }
+
+    pub fn is_loop_head(&self, point: Point) -> bool {
+        self.loop_heads.iter().any(|(p, _)| *p == point)
+    }
+
+    // Ciclo che ha la testa nel punto dato
+    pub fn loop_at(&self, point: Point) -> Option<&'a While> {
+        self.loop_heads
+            .iter()
+            .find(|(p, _)| *p == point)
+            .map(|(_, w)| *w)
+    }
+
+    // If e ramo chiusi dall'arco, se l'arco porta alla fine di un if
+    pub fn branch_of(&self, edge: &Edge) -> Option<(&'a IfThenElse, bool)> {
+        self.branches
+            .iter()
+            .find(|(from, to, _, _)| *from == edge.from && *to == edge.to)
+            .map(|(_, _, i, taken)| (*i, *taken))
+    }
+
+    // Punti di ingresso e di uscita di uno statement dell'AST
+    pub fn span_of(&self, stmt: &dyn Statement) -> Option<(Point, Point)> {
+        let key = stmt.id();
+        self.statements
+            .iter()
+            .find(|(k, _, _, _)| *k == key)
+            .map(|(_, _, entry, exit)| (*entry, *exit))
+    }
+
+    // Statement che finiscono nel punto dato, dal più interno al più esterno
+    pub fn statements_ending_at(
+        &self,
+        point: Point,
+    ) -> impl Iterator<Item = &'a dyn Statement> + '_ {
+        self.statements
+            .iter()
+            .filter(move |(_, _, _, exit)| *exit == point)
+            .map(|(_, stmt, _, _)| *stmt)
+    }
+
+    // Blocchi di base: un punto apre un nuovo blocco se è l'ingresso, una testa di
+    // ciclo, se non ha esattamente un predecessore o se il predecessore si dirama
+    pub fn basic_blocks(&self) -> Vec<BasicBlock> {
+        let starts_block = |p: Point| {
+            let mut predecessors = self.predecessors(p);
+            let single = match (predecessors.next(), predecessors.next()) {
+                (Some(e), None) => Some(e.from),
+                _ => None,
+            };
+            p == self.entry
+                || self.is_loop_head(p)
+                || single.is_none_or(|q| self.successors(q).count() != 1)
+        };
+        let mut blocks = Vec::new();
+        for start in (0..self.points).filter(|p| starts_block(*p)) {
+            let mut points = vec![start];
+            let mut current = start;
+            loop {
+                let mut successors = self.successors(current);
+                let next = match (successors.next(), successors.next()) {
+                    (Some(e), None) => e.to,
+                    _ => break,
+                };
+                if starts_block(next) {
+                    break;
+                }
+                points.push(next);
+                current = next;
+            }
+            blocks.push(BasicBlock { points });
+        }
+        blocks
+    }
+
+    // Indice del blocco di base che contiene il punto
+    pub fn block_of(&self, point: Point) -> Option<usize> {
+        self.basic_blocks()
+            .iter()
+            .position(|b| b.points.contains(&point))
+    }
+}
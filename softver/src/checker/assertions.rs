@@ -0,0 +1,96 @@
+use crate::analyzer::fixpoint::Solver;
+use crate::analyzer::AbstractState;
+use crate::ast::statement::{Statement, StatementNode};
+use crate::cfg::Cfg;
+use crate::source::{SourceMap, Span};
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssertionStatus {
+    // vale in ogni stato in cui si arriva all'assert (anche se non ci si arriva mai)
+    Proved,
+    // falsa in ogni stato in cui si arriva all'assert
+    Violated,
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssertionReport {
+    pub span: Option<Span>,
+    pub condition: String,
+    pub status: AssertionStatus,
+}
+
+impl Display for AssertionReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.span {
+            Some(span) => write!(f, "{}: ", span.start)?,
+            None => write!(f, "?: ")?,
+        }
+        let status = match self.status {
+            AssertionStatus::Proved => "proved",
+            AssertionStatus::Violated => "violated",
+            AssertionStatus::Unknown => "unknown",
+        };
+        write!(f, "assert({}): {}", self.condition, status)
+    }
+}
+
+fn collect<S: AbstractState>(
+    stmt: &dyn Statement,
+    cfg: &Cfg,
+    solver: &Solver<S>,
+    spans: &SourceMap,
+    reports: &mut Vec<AssertionReport>,
+) {
+    match stmt.node() {
+        StatementNode::Assert(a) => {
+            let state = match cfg.span_of(stmt) {
+                Some((entry, _)) => solver.state_at(entry).clone(),
+                None => S::bottom(),
+            };
+            let mut holds = state.clone();
+            holds.assume(&*a.condition, true);
+            let mut fails = state.clone();
+            fails.assume(&*a.condition, false);
+            let status = if fails.is_bottom() {
+                AssertionStatus::Proved
+            } else if holds.is_bottom() {
+                AssertionStatus::Violated
+            } else {
+                AssertionStatus::Unknown
+            };
+            reports.push(AssertionReport {
+                span: spans.span_of_statement(stmt),
+                condition: a.condition.to_string(),
+                status,
+            });
+        }
+        StatementNode::Concat(c) => {
+            collect(&*c.first, cfg, solver, spans, reports);
+            collect(&*c.second, cfg, solver, spans, reports);
+        }
+        StatementNode::IfThenElse(i) => {
+            collect(&*i.true_expr, cfg, solver, spans, reports);
+            collect(&*i.false_expr, cfg, solver, spans, reports);
+        }
+        StatementNode::While(w) => collect(&*w.body, cfg, solver, spans, reports),
+        StatementNode::Assign(_) | StatementNode::Skip(_) | StatementNode::Assume(_) => {}
+    }
+}
+
+// Classifica ogni assert del programma con lo stato astratto in cui viene
+// valutato. Gli assume e gli assert precedenti restringono gli stati successivi.
+pub fn check_assertions<S: AbstractState>(
+    program: &dyn Statement,
+    spans: &SourceMap,
+    initial: &S,
+) -> Vec<AssertionReport> {
+    let cfg = Cfg::new(program);
+    let mut solver = Solver::new(&cfg);
+    solver.solve(initial);
+    let mut reports = Vec::new();
+    collect(program, &cfg, &solver, spans, &mut reports);
+    reports
+}
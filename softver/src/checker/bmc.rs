@@ -0,0 +1,479 @@
+use crate::ast::arithmetic::{ArithmeticExpression, ArithmeticNode, Variable};
+use crate::ast::boolean::{And, Boolean, BooleanExpression, BooleanNode, Equal, Not, Or};
+use crate::ast::statement::{Assert, Halt, Statement, StatementNode, While};
+use crate::ast::{OverflowSemantics, State};
+use crate::dataflow::{guard_operands, variables};
+use crate::hoare::lia::{check_condition, Validity};
+use crate::hoare::wp::{replace_arithmetic, replace_boolean};
+use crate::hoare::VerificationCondition;
+use crate::optimizer::simplify::{simplify_arithmetic, simplify_boolean};
+use crate::source::{SourceMap, Span};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+// Versione SSA di una variabile: x@0 è il valore iniziale
+fn version(var: &str, n: usize) -> String {
+    format!("{}@{}", var, n)
+}
+
+fn and(
+    left: Box<dyn BooleanExpression>,
+    right: Box<dyn BooleanExpression>,
+) -> Box<dyn BooleanExpression> {
+    simplify_boolean(&And { left, right }, OverflowSemantics::Error)
+}
+
+fn or(
+    left: Box<dyn BooleanExpression>,
+    right: Box<dyn BooleanExpression>,
+) -> Box<dyn BooleanExpression> {
+    simplify_boolean(&Or { left, right }, OverflowSemantics::Error)
+}
+
+fn not(expression: Box<dyn BooleanExpression>) -> Box<dyn BooleanExpression> {
+    simplify_boolean(&Not { expression }, OverflowSemantics::Error)
+}
+
+fn conjunction(formulas: &[Box<dyn BooleanExpression>]) -> Box<dyn BooleanExpression> {
+    formulas
+        .iter()
+        .fold(Box::new(Boolean(true)), |result, f| and(result, f.clone()))
+}
+
+// Codifica del programma srotolato in forma SSA. Il valore di una variabile è
+// un'espressione sui valori iniziali x@0, semplificata a ogni assegnamento;
+// dopo un if con guardia non costante i valori diversi dei due rami diventano
+// una nuova versione x@n, definita con una disgiunzione sulla guardia.
+struct Encoder<'a> {
+    bound: usize,
+    current: BTreeMap<String, Box<dyn ArithmeticExpression>>,
+    last: BTreeMap<String, usize>,
+    // variabili lette prima di essere assegnate: gli ingressi del programma
+    inputs: BTreeSet<String>,
+    definitions: Vec<Box<dyn BooleanExpression>>,
+    // assume e assert già attraversati: limitano gli stati che arrivano più avanti
+    context: Vec<Box<dyn BooleanExpression>>,
+    // per ogni assert, le formule "se arrivo qui la condizione vale" di ogni copia
+    assertions: Vec<(&'a Assert, Vec<Box<dyn BooleanExpression>>)>,
+}
+
+fn is_false(guard: &dyn BooleanExpression) -> bool {
+    matches!(guard.node(), BooleanNode::Boolean(Boolean(false)))
+}
+
+impl<'a> Encoder<'a> {
+    fn value_of(&self, var: &str) -> Box<dyn ArithmeticExpression> {
+        match self.current.get(var) {
+            Some(value) => value.clone(),
+            None => Box::new(Variable {
+                value: version(var, 0),
+            }),
+        }
+    }
+
+    fn read(&mut self, operands: Vec<&dyn ArithmeticExpression>) {
+        let mut read = BTreeSet::new();
+        for operand in operands {
+            variables(operand, &mut read);
+        }
+        let inputs = read
+            .into_iter()
+            .filter(|var| !self.current.contains_key(var));
+        self.inputs.extend(inputs);
+    }
+
+    fn rename(&mut self, guard: &dyn BooleanExpression) -> Box<dyn BooleanExpression> {
+        self.read(guard_operands(guard));
+        let renamed = replace_boolean(guard, &|var| Some(self.value_of(var)));
+        simplify_boolean(&*renamed, OverflowSemantics::Error)
+    }
+
+    // `reached` vale negli stati iniziali che arrivano allo statement
+    fn statement(&mut self, stmt: &'a dyn Statement, reached: &dyn BooleanExpression) {
+        if is_false(reached) {
+            return;
+        }
+        match stmt.node() {
+            StatementNode::Assign(a) => {
+                self.read(vec![&*a.expr]);
+                let value = replace_arithmetic(&*a.expr, &|var| Some(self.value_of(var)));
+                let value = simplify_arithmetic(&*value, OverflowSemantics::Error);
+                self.current.insert(a.var_name.clone(), value);
+            }
+            StatementNode::Skip(_) => {}
+            StatementNode::Concat(c) => {
+                self.statement(&*c.first, reached);
+                self.statement(&*c.second, reached);
+            }
+            StatementNode::IfThenElse(i) => {
+                let guard = self.rename(&*i.guard);
+                let before = self.current.clone();
+                self.statement(&*i.true_expr, &*and(reached.to_boxed(), guard.clone()));
+                let after_then = std::mem::replace(&mut self.current, before);
+                self.statement(
+                    &*i.false_expr,
+                    &*and(reached.to_boxed(), not(guard.clone())),
+                );
+                self.merge(&*guard, after_then);
+            }
+            StatementNode::While(w) => self.unroll(w, reached, self.bound),
+            StatementNode::Assert(a) => {
+                let condition = self.rename(&*a.condition);
+                let holds = or(
+                    not(and(conjunction(&self.context), reached.to_boxed())),
+                    condition.clone(),
+                );
+                match self
+                    .assertions
+                    .iter_mut()
+                    .find(|(k, _)| std::ptr::eq(*k, a))
+                {
+                    Some((_, copies)) => copies.push(holds),
+                    None => self.assertions.push((a, vec![holds])),
+                }
+                // se l'assert fallisce l'esecuzione si ferma
+                self.context.push(or(not(reached.to_boxed()), condition));
+            }
+            StatementNode::Assume(a) => {
+                let condition = self.rename(&*a.condition);
+                self.context.push(or(not(reached.to_boxed()), condition));
+            }
+        }
+    }
+
+    // Dopo un if: i valori del ramo vero (`taken`) e quelli correnti, del ramo
+    // falso, si uniscono secondo la guardia
+    fn merge(
+        &mut self,
+        guard: &dyn BooleanExpression,
+        taken: BTreeMap<String, Box<dyn ArithmeticExpression>>,
+    ) {
+        match guard.node() {
+            BooleanNode::Boolean(Boolean(true)) => {
+                self.current = taken;
+                return;
+            }
+            BooleanNode::Boolean(Boolean(false)) => return,
+            _ => {}
+        }
+        let vars: BTreeSet<String> = taken.keys().chain(self.current.keys()).cloned().collect();
+        for var in vars {
+            // il valore iniziale sopravvive in uno dei rami
+            if !taken.contains_key(&var) || !self.current.contains_key(&var) {
+                self.inputs.insert(var.clone());
+            }
+            let taken_value = taken
+                .get(&var)
+                .cloned()
+                .unwrap_or_else(|| self.value_of(&var));
+            let other_value = self.value_of(&var);
+            if *taken_value == *other_value {
+                continue;
+            }
+            let n = self.last.get(&var).copied().unwrap_or(0) + 1;
+            self.last.insert(var.clone(), n);
+            let merged: Box<dyn ArithmeticExpression> = Box::new(Variable {
+                value: version(&var, n),
+            });
+            let equal = |value: Box<dyn ArithmeticExpression>| -> Box<dyn BooleanExpression> {
+                Box::new(Equal {
+                    left: merged.clone(),
+                    right: value,
+                })
+            };
+            self.definitions.push(or(
+                and(guard.to_boxed(), equal(taken_value)),
+                and(not(guard.to_boxed()), equal(other_value)),
+            ));
+            self.current.insert(var, merged);
+        }
+    }
+
+    // while b { S } srotolato come if b then { S; while b { S } }; dopo `bound`
+    // iterazioni si assume che la guardia sia falsa
+    fn unroll(&mut self, w: &'a While, reached: &dyn BooleanExpression, remaining: usize) {
+        let guard = self.rename(&*w.guard);
+        if remaining == 0 {
+            self.context.push(or(not(reached.to_boxed()), not(guard)));
+            return;
+        }
+        let inside = and(reached.to_boxed(), guard.clone());
+        if is_false(&*inside) {
+            return;
+        }
+        let before = self.current.clone();
+        self.statement(&*w.body, &*inside);
+        self.unroll(w, &*inside, remaining - 1);
+        let after_loop = std::mem::replace(&mut self.current, before);
+        self.merge(&*guard, after_loop);
+    }
+}
+
+// Valore concreto di un'espressione; None se un'operazione va in overflow,
+// divide per zero o legge una variabile senza valore
+fn value(expr: &dyn ArithmeticExpression, state: &State) -> Option<i32> {
+    let semantics = OverflowSemantics::Error;
+    match expr.node() {
+        ArithmeticNode::Numeral(n) => Some(n.0),
+        ArithmeticNode::Variable(v) => state.get(&v.value).copied(),
+        ArithmeticNode::Add(e) => semantics.add(value(&*e.left, state)?, value(&*e.right, state)?),
+        ArithmeticNode::Minus(e) => {
+            semantics.minus(value(&*e.left, state)?, value(&*e.right, state)?)
+        }
+        ArithmeticNode::Product(e) => {
+            semantics.product(value(&*e.left, state)?, value(&*e.right, state)?)
+        }
+        ArithmeticNode::Divide(e) => {
+            semantics.divide(value(&*e.left, state)?, value(&*e.right, state)?)
+        }
+        ArithmeticNode::Uminus(e) => semantics.uminus(value(&*e.right, state)?),
+    }
+}
+
+fn holds(guard: &dyn BooleanExpression, state: &State) -> Option<bool> {
+    let compare = |left: &dyn ArithmeticExpression, right: &dyn ArithmeticExpression| {
+        Some(value(left, state)?.cmp(&value(right, state)?))
+    };
+    match guard.node() {
+        BooleanNode::Boolean(b) => Some(b.0),
+        BooleanNode::Equal(e) => Some(compare(&*e.left, &*e.right)?.is_eq()),
+        BooleanNode::GreatEqual(e) => Some(compare(&*e.left, &*e.right)?.is_ge()),
+        BooleanNode::Great(e) => Some(compare(&*e.left, &*e.right)?.is_gt()),
+        BooleanNode::LessEqual(e) => Some(compare(&*e.left, &*e.right)?.is_le()),
+        BooleanNode::Less(e) => Some(compare(&*e.left, &*e.right)?.is_lt()),
+        BooleanNode::And(e) => Some(holds(&*e.left, state)? && holds(&*e.right, state)?),
+        BooleanNode::Or(e) => Some(holds(&*e.left, state)? || holds(&*e.right, state)?),
+        BooleanNode::Not(e) => Some(!holds(&*e.expression, state)?),
+    }
+}
+
+// Riesecuzione concreta di un controesempio, con lo stato dopo ogni assegnamento.
+// Un ciclo che supera il limite di iterazioni o un'operazione in overflow o con
+// divisore nullo interrompono la riesecuzione (Err(None)): il decisore ragiona
+// sugli interi matematici, l'interprete no.
+struct Replay {
+    bound: usize,
+    trace: Vec<(String, State)>,
+}
+
+impl Replay {
+    fn guard(guard: &dyn BooleanExpression, state: &State) -> Result<bool, Option<Halt>> {
+        holds(guard, state).ok_or(None)
+    }
+
+    fn run(&mut self, stmt: &dyn Statement, state: &mut State) -> Result<(), Option<Halt>> {
+        match stmt.node() {
+            StatementNode::Assign(a) => {
+                let result = value(&*a.expr, state).ok_or(None)?;
+                state.insert(a.var_name.clone(), result);
+                self.trace
+                    .push((format!("{} := {}", a.var_name, a.expr), state.clone()));
+                Ok(())
+            }
+            StatementNode::Skip(_) => Ok(()),
+            StatementNode::Concat(c) => {
+                self.run(&*c.first, state)?;
+                self.run(&*c.second, state)
+            }
+            StatementNode::IfThenElse(i) => {
+                if Self::guard(&*i.guard, state)? {
+                    self.run(&*i.true_expr, state)
+                } else {
+                    self.run(&*i.false_expr, state)
+                }
+            }
+            StatementNode::While(w) => {
+                let mut iterations = 0;
+                while Self::guard(&*w.guard, state)? {
+                    if iterations == self.bound {
+                        return Err(None);
+                    }
+                    self.run(&*w.body, state)?;
+                    iterations += 1;
+                }
+                Ok(())
+            }
+            StatementNode::Assert(a) => match Self::guard(&*a.condition, state)? {
+                true => Ok(()),
+                false => Err(Some(Halt::AssertionFailed(a.condition.to_string()))),
+            },
+            StatementNode::Assume(a) => match Self::guard(&*a.condition, state)? {
+                true => Ok(()),
+                false => Err(Some(Halt::Blocked)),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Counterexample {
+    pub initial: State,
+    // ogni assegnamento eseguito con lo stato che produce
+    pub trace: Vec<(String, State)>,
+    // l'interprete concreto, partendo da `initial`, fallisce proprio su questo assert
+    pub replayed: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BmcResult {
+    // nessuna violazione entro il limite di iterazioni
+    Safe,
+    Violated(Counterexample),
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BmcReport {
+    pub span: Option<Span>,
+    pub condition: String,
+    pub bound: usize,
+    pub result: BmcResult,
+}
+
+impl Display for BmcReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.span {
+            Some(span) => write!(f, "{}: ", span.start)?,
+            None => write!(f, "?: ")?,
+        }
+        write!(f, "assert({}): ", self.condition)?;
+        match &self.result {
+            BmcResult::Safe => write!(f, "no violation within {} iterations", self.bound),
+            BmcResult::Unknown => write!(f, "unknown"),
+            BmcResult::Violated(counterexample) => {
+                write!(
+                    f,
+                    "violated from {}",
+                    crate::printer::state(&counterexample.initial)
+                )?;
+                if !counterexample.replayed {
+                    write!(f, " (not reproduced by the concrete interpreter)")?;
+                }
+                for (step, state) in &counterexample.trace {
+                    write!(f, "\n  {} -> {}", step, crate::printer::state(state))?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+// La condizione di un assert, con la posizione e il testo per il report
+struct EncodedAssertion {
+    span: Option<Span>,
+    condition: String,
+    vc: VerificationCondition,
+}
+
+struct Encoding {
+    // ordinate per posizione nel sorgente
+    assertions: Vec<EncodedAssertion>,
+    // variabili il cui valore iniziale compare nelle condizioni
+    inputs: BTreeSet<String>,
+}
+
+fn encode_assertions(program: &dyn Statement, spans: &SourceMap, bound: usize) -> Encoding {
+    let mut encoder = Encoder {
+        bound,
+        current: BTreeMap::new(),
+        last: BTreeMap::new(),
+        inputs: BTreeSet::new(),
+        definitions: Vec::new(),
+        context: Vec::new(),
+        assertions: Vec::new(),
+    };
+    encoder.statement(program, &Boolean(true));
+    let mut assertions = Vec::new();
+    for (assert, copies) in &encoder.assertions {
+        let span = spans.span_of_statement(*assert);
+        let condition = assert.condition.to_string();
+        let description = match span {
+            Some(span) => format!(
+                "{}: assert({}) within {} iterations",
+                span.start, condition, bound
+            ),
+            None => format!("assert({}) within {} iterations", condition, bound),
+        };
+        assertions.push(EncodedAssertion {
+            span,
+            condition,
+            vc: VerificationCondition {
+                description,
+                hypotheses: encoder.definitions.clone(),
+                goal: conjunction(copies),
+            },
+        });
+    }
+    assertions.sort_by_key(|a| a.span.map(|s| s.start));
+    Encoding {
+        assertions,
+        inputs: encoder.inputs,
+    }
+}
+
+// Una condizione per assert: vale se nessuna esecuzione che fa al più `bound`
+// iterazioni di ogni ciclo lo viola. Le variabili x@n sono le versioni SSA
+// create dove i cammini si uniscono, x@0 il valore iniziale. Si possono esportare in SMT-LIB come le altre.
+pub fn encode(
+    program: &dyn Statement,
+    spans: &SourceMap,
+    bound: usize,
+) -> Vec<VerificationCondition> {
+    encode_assertions(program, spans, bound)
+        .assertions
+        .into_iter()
+        .map(|a| a.vc)
+        .collect()
+}
+
+// Cerca con il decisore interno stati iniziali che violano ogni assert
+// entro `bound` iterazioni dei cicli, e li riesegue concretamente
+pub fn bounded_model_check(
+    program: &dyn Statement,
+    spans: &SourceMap,
+    bound: usize,
+) -> Vec<BmcReport> {
+    let Encoding { assertions, inputs } = encode_assertions(program, spans, bound);
+    let mut reports = Vec::new();
+    for EncodedAssertion {
+        span,
+        condition,
+        vc,
+    } in assertions
+    {
+        let result = match check_condition(&vc) {
+            Validity::Valid => BmcResult::Safe,
+            Validity::Unknown => BmcResult::Unknown,
+            Validity::Invalid(model) => {
+                // le variabili mai vincolate partono da 0
+                let initial: State = inputs
+                    .iter()
+                    .map(|var| {
+                        (
+                            var.clone(),
+                            model.get(&version(var, 0)).copied().unwrap_or(0),
+                        )
+                    })
+                    .collect();
+                let mut replay = Replay {
+                    bound,
+                    trace: Vec::new(),
+                };
+                let outcome = replay.run(program, &mut initial.clone());
+                BmcResult::Violated(Counterexample {
+                    initial,
+                    trace: replay.trace,
+                    replayed: outcome == Err(Some(Halt::AssertionFailed(condition.clone()))),
+                })
+            }
+        };
+        reports.push(BmcReport {
+            span,
+            condition,
+            bound,
+            result,
+        });
+    }
+    reports
+}
@@ -0,0 +1,129 @@
+use crate::analyzer::interpreter::Interpreter;
+use crate::analyzer::interval::Bound;
+use crate::analyzer::linear::LinearExpression;
+use crate::analyzer::polyhedron::Polyhedron;
+use crate::analyzer::{AbstractDomain, AbstractState};
+use crate::ast::statement::{Statement, StatementNode};
+use crate::ast::State;
+use crate::checker::termination::{prove_termination, Termination};
+use crate::source::{SourceMap, Span};
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+// Limite al numero di iterazioni di un while: simbolico, in funzione dei valori
+// all'ingresso del ciclo, e numerico, sul caso peggiore degli stati d'ingresso
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoopBound {
+    pub span: Option<Span>,
+    pub guard: String,
+    pub symbolic: Option<LinearExpression>,
+    pub iterations: Bound,
+}
+
+impl Display for LoopBound {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.span {
+            Some(span) => write!(f, "{}: ", span.start)?,
+            None => write!(f, "?: ")?,
+        }
+        write!(f, "while {}: ", self.guard)?;
+        match self.iterations {
+            Bound::Finite(n) => write!(f, "at most {} iterations", n)?,
+            _ => write!(f, "unbounded iterations")?,
+        }
+        match &self.symbolic {
+            Some(bound) => write!(f, " (max(0, {}) at loop entry)", bound),
+            None => Ok(()),
+        }
+    }
+}
+
+// Modello di costo: un'unità per ogni assegnamento, skip, assert o assume eseguito
+// e per ogni valutazione della guardia di un if o di un while
+struct CostAnalyzer<'s> {
+    interpreter: Interpreter<Polyhedron>,
+    spans: &'s SourceMap,
+    loops: Vec<LoopBound>,
+}
+
+impl CostAnalyzer<'_> {
+    // Costo nel caso peggiore partendo da `pre` e stato dopo lo statement
+    fn statement(&mut self, stmt: &dyn Statement, pre: &Polyhedron) -> (Bound, Polyhedron) {
+        if pre.is_bottom() {
+            return (Bound::Finite(0), Polyhedron::bottom());
+        }
+        match stmt.node() {
+            StatementNode::Assign(_)
+            | StatementNode::Skip(_)
+            | StatementNode::Assert(_)
+            | StatementNode::Assume(_) => {
+                (Bound::Finite(1), self.interpreter.run(stmt, pre))
+            }
+            StatementNode::Concat(c) => {
+                let (first, middle) = self.statement(&*c.first, pre);
+                let (second, post) = self.statement(&*c.second, &middle);
+                (first.add(second), post)
+            }
+            StatementNode::IfThenElse(i) => {
+                let mut then_pre = pre.clone();
+                then_pre.assume(&*i.guard, true);
+                let mut else_pre = pre.clone();
+                else_pre.assume(&*i.guard, false);
+                let (then_cost, then_post) = self.statement(&*i.true_expr, &then_pre);
+                let (else_cost, else_post) = self.statement(&*i.false_expr, &else_pre);
+                let cost = Bound::Finite(1).add(then_cost.max(else_cost));
+                (cost, then_post.join(&else_post))
+            }
+            StatementNode::While(w) => {
+                let invariant = self.interpreter.loop_invariant(w, pre);
+                let symbolic = match prove_termination(&mut self.interpreter, w, pre, &invariant) {
+                    Termination::Terminating { bound, .. } => Some(bound),
+                    _ => None,
+                };
+                // il caso peggiore tra gli stati d'ingresso in cui si entra nel ciclo
+                let mut entry = pre.clone();
+                entry.assume(&*w.guard, true);
+                let iterations = match &symbolic {
+                    _ if entry.is_bottom() => Bound::Finite(0),
+                    Some(bound) => entry
+                        .range_of(bound)
+                        .high()
+                        .map_or(Bound::Finite(0), |n| n.max(Bound::Finite(0))),
+                    None => Bound::PosInf,
+                };
+                self.loops.push(LoopBound {
+                    span: self.spans.span_of_statement(stmt),
+                    guard: w.guard.to_string(),
+                    symbolic,
+                    iterations,
+                });
+                // ogni iterazione costa al più quanto il corpo dal caso peggiore
+                // dell'invariante; la guardia si valuta una volta in più
+                let mut body_pre = invariant.clone();
+                body_pre.assume(&*w.guard, true);
+                let (body, _) = self.statement(&*w.body, &body_pre);
+                let guards = iterations.add(Bound::Finite(1));
+                let mut post = invariant;
+                post.assume(&*w.guard, false);
+                (guards.add(iterations.mul(body)), post)
+            }
+        }
+    }
+}
+
+// Limiti sulle iterazioni di ogni while, in ordine di sorgente, e costo totale
+// del programma nel caso peggiore (+∞ se qualche ciclo non è limitato)
+pub fn analyze_cost(
+    program: &dyn Statement,
+    spans: &SourceMap,
+    initial: &State,
+) -> (Vec<LoopBound>, Bound) {
+    let mut analyzer = CostAnalyzer {
+        interpreter: Interpreter::new(),
+        spans,
+        loops: Vec::new(),
+    };
+    let (cost, _) = analyzer.statement(program, &Polyhedron::from_state(initial));
+    analyzer.loops.sort_by_key(|l| l.span.map(|s| s.start));
+    (analyzer.loops, cost)
+}
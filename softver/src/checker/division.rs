@@ -0,0 +1,100 @@
+use crate::analyzer::value_state::ValueState;
+use crate::analyzer::{Comparison, ValueDomain};
+use crate::ast::arithmetic::{ArithmeticExpression, ArithmeticNode};
+use crate::ast::statement::Statement;
+use crate::checker::{analyze_expressions, Verdict};
+use crate::source::{SourceMap, Span};
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+// Esito del controllo su un singolo nodo Divide
+#[derive(Debug, Clone, PartialEq)]
+pub struct DivisionReport {
+    pub span: Option<Span>,
+    pub verdict: Verdict,
+    // divisore come compare nel sorgente e suo valore astratto
+    pub divisor: String,
+    pub value: String,
+}
+
+// Una riga per controllo, nella forma
+//   riga:colonna: esito: divisor <espressione> ∈ <valore astratto>
+// (con '?' al posto della posizione per i nodi costruiti senza parser)
+impl Display for DivisionReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.span {
+            Some(span) => write!(f, "{}: ", span.start)?,
+            None => write!(f, "?: ")?,
+        }
+        let verdict = match self.verdict {
+            Verdict::Safe => "safe",
+            Verdict::Possible => "possible division by zero",
+            Verdict::Definite => "definitely divides by zero",
+        };
+        write!(f, "{}: divisor {} ∈ {}", verdict, self.divisor, self.value)
+    }
+}
+
+// Zero certo se il divisore è la costante 0, escluso se il dominio riesce a
+// dimostrare che divisore = 0 è impossibile. Un divisore ⊥ sta in codice irraggiungibile.
+fn verdict<V: ValueDomain>(value: &V) -> Verdict {
+    if value.is_bottom() {
+        Verdict::Safe
+    } else if value.as_constant() == Some(0) {
+        Verdict::Definite
+    } else if V::refine(Comparison::Equal, value, &V::constant(0)).0.is_bottom() {
+        Verdict::Safe
+    } else {
+        Verdict::Possible
+    }
+}
+
+fn check_expression<V: ValueDomain>(
+    expr: &dyn ArithmeticExpression,
+    state: &ValueState<V>,
+    spans: &SourceMap,
+    reports: &mut Vec<DivisionReport>,
+) {
+    match expr.node() {
+        ArithmeticNode::Numeral(_) | ArithmeticNode::Variable(_) => {}
+        ArithmeticNode::Uminus(e) => check_expression(&*e.right, state, spans, reports),
+        ArithmeticNode::Add(e) => {
+            check_expression(&*e.left, state, spans, reports);
+            check_expression(&*e.right, state, spans, reports);
+        }
+        ArithmeticNode::Minus(e) => {
+            check_expression(&*e.left, state, spans, reports);
+            check_expression(&*e.right, state, spans, reports);
+        }
+        ArithmeticNode::Product(e) => {
+            check_expression(&*e.left, state, spans, reports);
+            check_expression(&*e.right, state, spans, reports);
+        }
+        ArithmeticNode::Divide(e) => {
+            let value = state.eval(&*e.right);
+            reports.push(DivisionReport {
+                span: spans.span_of(expr),
+                verdict: verdict(&value),
+                divisor: e.right.to_string(),
+                value: value.to_string(),
+            });
+            check_expression(&*e.left, state, spans, reports);
+            check_expression(&*e.right, state, spans, reports);
+        }
+    }
+}
+
+// Controlla tutte le divisioni del programma a partire dallo stato iniziale.
+// I risultati sono ordinati per posizione, così l'uscita resta stabile tra un'esecuzione e l'altra.
+pub fn check_divisions<V: ValueDomain>(
+    program: &dyn Statement,
+    spans: &SourceMap,
+    initial: &ValueState<V>,
+) -> Vec<DivisionReport> {
+    let mut reports = Vec::new();
+    analyze_expressions(program, initial, &mut |expr, state| {
+        check_expression(expr, state, spans, &mut reports)
+    });
+    reports.sort_by_key(|r| r.span.map(|s| s.start));
+    reports
+}
@@ -0,0 +1,114 @@
+pub mod assertions;
+pub mod bmc;
+pub mod cost;
+pub mod division;
+pub mod overflow;
+pub mod termination;
+pub mod uninitialized;
+
+use crate::analyzer::fixpoint::Solver;
+use crate::analyzer::AbstractState;
+use crate::ast::arithmetic::ArithmeticExpression;
+use crate::ast::boolean::{BooleanExpression, BooleanNode};
+use crate::ast::statement::{Statement, StatementNode};
+use crate::cfg::Cfg;
+
+// Esito di un controllo su un'operazione: sicura, errore possibile o certo
+// (se l'operazione viene eseguita)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verdict {
+    Safe,
+    Possible,
+    Definite,
+}
+
+// Visita, in ordine di sorgente, ogni espressione aritmetica del programma insieme
+// allo stato astratto in cui viene valutata, preso dal risolutore sul grafo
+pub fn for_each_expression<S: AbstractState>(
+    program: &dyn Statement,
+    cfg: &Cfg,
+    solver: &Solver<S>,
+    f: &mut dyn FnMut(&dyn ArithmeticExpression, &S),
+) {
+    let state_at = |stmt: &dyn Statement| match cfg.span_of(stmt) {
+        Some((entry, _)) => solver.state_at(entry).clone(),
+        None => S::bottom(),
+    };
+    match program.node() {
+        StatementNode::Assign(a) => f(&*a.expr, &state_at(program)),
+        StatementNode::Skip(_) => {}
+        StatementNode::Assert(a) => guard_expressions(&*a.condition, &state_at(program), f),
+        StatementNode::Assume(a) => guard_expressions(&*a.condition, &state_at(program), f),
+        StatementNode::Concat(c) => {
+            for_each_expression(&*c.first, cfg, solver, f);
+            for_each_expression(&*c.second, cfg, solver, f);
+        }
+        StatementNode::IfThenElse(i) => {
+            guard_expressions(&*i.guard, &state_at(program), f);
+            for_each_expression(&*i.true_expr, cfg, solver, f);
+            for_each_expression(&*i.false_expr, cfg, solver, f);
+        }
+        // la guardia si valuta in testa al ciclo, nello stato invariante
+        StatementNode::While(w) => {
+            guard_expressions(&*w.guard, &state_at(program), f);
+            for_each_expression(&*w.body, cfg, solver, f);
+        }
+    }
+}
+
+// Come for_each_expression, risolvendo prima il programma dallo stato iniziale
+pub fn analyze_expressions<S: AbstractState>(
+    program: &dyn Statement,
+    initial: &S,
+    f: &mut dyn FnMut(&dyn ArithmeticExpression, &S),
+) {
+    let cfg = Cfg::new(program);
+    let mut solver = Solver::new(&cfg);
+    solver.solve(initial);
+    for_each_expression(program, &cfg, &solver, f);
+}
+
+// Le guardie si valutano in corto circuito: il secondo operando di a && b
+// si valuta solo se a è vero, quello di a || b solo se a è falso
+fn guard_expressions<S: AbstractState>(
+    guard: &dyn BooleanExpression,
+    state: &S,
+    f: &mut dyn FnMut(&dyn ArithmeticExpression, &S),
+) {
+    match guard.node() {
+        BooleanNode::Boolean(_) => {}
+        BooleanNode::Equal(e) => {
+            f(&*e.left, state);
+            f(&*e.right, state);
+        }
+        BooleanNode::GreatEqual(e) => {
+            f(&*e.left, state);
+            f(&*e.right, state);
+        }
+        BooleanNode::Great(e) => {
+            f(&*e.left, state);
+            f(&*e.right, state);
+        }
+        BooleanNode::LessEqual(e) => {
+            f(&*e.left, state);
+            f(&*e.right, state);
+        }
+        BooleanNode::Less(e) => {
+            f(&*e.left, state);
+            f(&*e.right, state);
+        }
+        BooleanNode::And(e) => {
+            guard_expressions(&*e.left, state, f);
+            let mut rest = state.clone();
+            rest.assume(&*e.left, true);
+            guard_expressions(&*e.right, &rest, f);
+        }
+        BooleanNode::Or(e) => {
+            guard_expressions(&*e.left, state, f);
+            let mut rest = state.clone();
+            rest.assume(&*e.left, false);
+            guard_expressions(&*e.right, &rest, f);
+        }
+        BooleanNode::Not(e) => guard_expressions(&*e.expression, state, f),
+    }
+}
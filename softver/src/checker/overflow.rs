@@ -0,0 +1,110 @@
+use crate::analyzer::value_state::ValueState;
+use crate::analyzer::{Comparison, ValueDomain};
+use crate::ast::arithmetic::{ArithmeticExpression, ArithmeticNode};
+use crate::ast::statement::Statement;
+use crate::checker::{analyze_expressions, Verdict};
+use crate::source::{SourceMap, Span};
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+// Operazione il cui risultato può uscire dal range di i32
+#[derive(Debug, Clone, PartialEq)]
+pub struct OverflowAlarm {
+    pub span: Option<Span>,
+    // solo Possible o Definite: le operazioni sicure non producono allarmi
+    pub verdict: Verdict,
+    pub operation: String,
+    // risultato astratto calcolato sugli interi matematici
+    pub value: String,
+}
+
+// riga:colonna: esito: <operazione> ∈ <valore astratto>
+impl Display for OverflowAlarm {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.span {
+            Some(span) => write!(f, "{}: ", span.start)?,
+            None => write!(f, "?: ")?,
+        }
+        let verdict = match self.verdict {
+            Verdict::Definite => "definite overflow",
+            _ => "possible overflow",
+        };
+        write!(f, "{}: {} ∈ {}", verdict, self.operation, self.value)
+    }
+}
+
+// Il dominio non esclude valori fuori dal range di i32. Si chiede se sono possibili
+// value > MAX o value < MIN invece di confrontare con clamp, perché un dominio che non
+// sa rappresentare il range (per esempio le costanti con top) non restringerebbe nulla.
+fn may_overflow<V: ValueDomain>(value: &V) -> bool {
+    let (above, _) = V::refine(Comparison::Great, value, &V::constant(i32::MAX));
+    let (below, _) = V::refine(Comparison::Less, value, &V::constant(i32::MIN));
+    !above.is_bottom() || !below.is_bottom()
+}
+
+// Parte del valore che sta nel range di i32
+fn clamp<V: ValueDomain>(value: &V) -> V {
+    let (below_max, _) = V::refine(Comparison::LessEqual, value, &V::constant(i32::MAX));
+    let (in_range, _) = V::refine(Comparison::GreatEqual, &below_max, &V::constant(i32::MIN));
+    in_range
+}
+
+struct Checker<'s, V> {
+    state: &'s ValueState<V>,
+    spans: &'s SourceMap,
+    alarms: Vec<OverflowAlarm>,
+}
+
+impl<V: ValueDomain> Checker<'_, V> {
+    // Valuta l'espressione segnalando le operazioni che escono da i32. Il valore
+    // restituito è già ristretto a i32: se un'operazione va in overflow
+    // l'esecuzione si ferma lì e le operazioni che la contengono non arrivano a eseguire.
+    fn eval(&mut self, expr: &dyn ArithmeticExpression) -> V {
+        let result = match expr.node() {
+            ArithmeticNode::Numeral(n) => return V::constant(n.0),
+            ArithmeticNode::Variable(v) => return self.state.get(&v.value),
+            ArithmeticNode::Add(e) => self.eval(&*e.left).add(&self.eval(&*e.right)),
+            ArithmeticNode::Minus(e) => self.eval(&*e.left).minus(&self.eval(&*e.right)),
+            ArithmeticNode::Product(e) => self.eval(&*e.left).product(&self.eval(&*e.right)),
+            ArithmeticNode::Divide(e) => self.eval(&*e.left).divide(&self.eval(&*e.right)),
+            ArithmeticNode::Uminus(e) => self.eval(&*e.right).uminus(),
+        };
+        let in_range = clamp(&result);
+        if !may_overflow(&result) {
+            return in_range;
+        }
+        let verdict = if in_range.is_bottom() {
+            Verdict::Definite
+        } else {
+            Verdict::Possible
+        };
+        self.alarms.push(OverflowAlarm {
+            span: self.spans.span_of(expr),
+            verdict,
+            operation: expr.to_string(),
+            value: result.to_string(),
+        });
+        in_range
+    }
+}
+
+// Allarmi di overflow di tutte le operazioni aritmetiche, compresi -i32::MIN e
+// i32::MIN / -1, ordinati per posizione
+pub fn check_overflows<V: ValueDomain>(
+    program: &dyn Statement,
+    spans: &SourceMap,
+    initial: &ValueState<V>,
+) -> Vec<OverflowAlarm> {
+    let mut alarms = Vec::new();
+    analyze_expressions(program, initial, &mut |expr, state| {
+        let mut checker = Checker {
+            state,
+            spans,
+            alarms: Vec::new(),
+        };
+        checker.eval(expr);
+        alarms.append(&mut checker.alarms);
+    });
+    alarms.sort_by_key(|a| a.span);
+    alarms
+}
@@ -0,0 +1,250 @@
+use crate::analyzer::interpreter::Interpreter;
+use crate::analyzer::linear::{linearize, LinearExpression};
+use crate::analyzer::polyhedron::Polyhedron;
+use crate::analyzer::{comparison_of, AbstractDomain, AbstractState, Comparison};
+use crate::ast::arithmetic::Variable;
+use crate::ast::boolean::{BooleanExpression, BooleanNode};
+use crate::ast::statement::{Statement, StatementNode, While};
+use crate::ast::State;
+use crate::source::{SourceMap, Span};
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Termination {
+    // funzione di rango: limitata inferiormente quando la guardia vale e
+    // decrescente di almeno 1 a ogni iterazione. `bound` limita il numero di
+    // iterazioni in funzione dei valori all'ingresso del ciclo.
+    Terminating {
+        ranking: LinearExpression,
+        bound: LinearExpression,
+    },
+    // il ciclo non può uscire una volta entrato, o il corpo può lasciare lo stato invariato
+    PossiblyNonTerminating,
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoopReport {
+    pub span: Option<Span>,
+    pub guard: String,
+    pub termination: Termination,
+}
+
+impl Display for LoopReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.span {
+            Some(span) => write!(f, "{}: ", span.start)?,
+            None => write!(f, "?: ")?,
+        }
+        write!(f, "while {}: ", self.guard)?;
+        match &self.termination {
+            Termination::Terminating { ranking, .. } => {
+                write!(f, "terminating, ranking function {}", ranking)
+            }
+            Termination::PossiblyNonTerminating => write!(f, "possibly non-terminating"),
+            Termination::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+// Copia di una variabile che conserva il valore all'inizio dell'iterazione
+// (il carattere @ non può comparire negli identificatori del linguaggio)
+fn old(var: &str) -> String {
+    format!("{}@pre", var)
+}
+
+fn rename_old(e: &LinearExpression) -> Option<LinearExpression> {
+    let mut result = LinearExpression::constant(e.constant);
+    for (var, coefficient) in &e.coefficients {
+        let term = LinearExpression::variable(&old(var));
+        result = result.combine(1, &term, *coefficient)?;
+    }
+    Some(result)
+}
+
+// Le disuguaglianze e ≠ 0 implicate dalla guardia, con e = left - right
+fn disequalities(
+    guard: &dyn BooleanExpression,
+    polarity: bool,
+    result: &mut Vec<LinearExpression>,
+) {
+    if let Some((op, left, right)) = comparison_of(guard) {
+        let op = if polarity { op } else { op.negate() };
+        if op == Comparison::NotEqual {
+            if let Some(e) = linearize(left)
+                .zip(linearize(right))
+                .and_then(|(l, r)| l.combine(1, &r, -1))
+            {
+                result.push(e);
+            }
+        }
+        return;
+    }
+    match guard.node() {
+        BooleanNode::Not(n) => disequalities(&*n.expression, !polarity, result),
+        BooleanNode::And(a) if polarity => {
+            disequalities(&*a.left, true, result);
+            disequalities(&*a.right, true, result);
+        }
+        BooleanNode::Or(o) if !polarity => {
+            disequalities(&*o.left, false, result);
+            disequalities(&*o.right, false, result);
+        }
+        _ => {}
+    }
+}
+
+// Cerca una funzione di rango per il ciclo `w`, dati lo stato all'ingresso e
+// l'invariante in testa
+pub fn prove_termination(
+    interpreter: &mut Interpreter<Polyhedron>,
+    w: &While,
+    pre: &Polyhedron,
+    invariant: &Polyhedron,
+) -> Termination {
+    // stati in cui si esegue il corpo
+    let mut inside = invariant.clone();
+    inside.assume(&*w.guard, true);
+    if inside.is_bottom() {
+        return Termination::Terminating {
+            ranking: LinearExpression::constant(0),
+            bound: LinearExpression::constant(0),
+        };
+    }
+    // relazione di transizione di un'iterazione: le copie @pre tengono
+    // i valori prima del corpo
+    let mut relation = inside.clone();
+    for var in inside.variables() {
+        relation.assign(&old(var), &Variable { value: var.clone() });
+    }
+    let relation = interpreter.run(&*w.body, &relation);
+    // f(x@pre) - f(x) - 1 ≥ 0
+    let decreases = |f: &LinearExpression| {
+        rename_old(f)
+            .and_then(|s| s.combine(1, f, -1))
+            .and_then(|s| s.combine(1, &LinearExpression::constant(1), -1))
+            .is_some_and(|s| relation.entails(&s))
+    };
+    // f ≥ 0 finché la guardia vale: al più f + 1 iterazioni
+    let at_most_one_more = |f: LinearExpression| {
+        let bound = f.combine(1, &LinearExpression::constant(1), 1);
+        bound.map(|bound| Termination::Terminating { ranking: f, bound })
+    };
+
+    // ogni vincolo dell'invariante con la guardia è già limitato da 0
+    let bounded = inside.inequalities();
+    if let Some(f) = bounded.iter().find(|f| decreases(f)) {
+        if let Some(result) = at_most_one_more(f.clone()) {
+            return result;
+        }
+    }
+
+    // guardia e ≠ 0 con e ≥ 0 all'ingresso e che cala esattamente di 1:
+    // e ≥ 0 resta invariante perché e arriva a 0 senza saltarlo, e il ciclo
+    // fa esattamente e iterazioni
+    let mut candidates = Vec::new();
+    disequalities(&*w.guard, true, &mut candidates);
+    for e in candidates {
+        for f in [Some(e.clone()), e.scale(-1)].into_iter().flatten() {
+            // f(x) - f(x@pre) + 1 ≥ 0
+            let increases_back = rename_old(&f)
+                .and_then(|s| s.combine(-1, &f, 1))
+                .and_then(|s| s.combine(1, &LinearExpression::constant(1), 1));
+            if pre.entails(&f)
+                && decreases(&f)
+                && increases_back.is_some_and(|s| relation.entails(&s))
+            {
+                return Termination::Terminating {
+                    ranking: f.clone(),
+                    bound: f,
+                };
+            }
+        }
+    }
+
+    // somme di due vincoli, per i cicli che fanno calare ora l'uno ora l'altro
+    for (i, f) in bounded.iter().enumerate() {
+        for g in &bounded[i + 1..] {
+            let sum = f.combine(1, g, 1).filter(|sum| decreases(sum));
+            if let Some(result) = sum.and_then(at_most_one_more) {
+                return result;
+            }
+        }
+    }
+
+    let mut exit = invariant.clone();
+    exit.assume(&*w.guard, false);
+    let mut unchanged = relation.clone();
+    for var in inside.variables() {
+        unchanged.assume_comparison(
+            Comparison::Equal,
+            &Variable { value: var.clone() },
+            &Variable { value: old(var) },
+        );
+    }
+    if exit.is_bottom() || !unchanged.is_bottom() {
+        Termination::PossiblyNonTerminating
+    } else {
+        Termination::Unknown
+    }
+}
+
+struct TerminationChecker<'s> {
+    interpreter: Interpreter<Polyhedron>,
+    spans: &'s SourceMap,
+    reports: Vec<LoopReport>,
+}
+
+impl TerminationChecker<'_> {
+    fn statement(&mut self, stmt: &dyn Statement, pre: &Polyhedron) -> Polyhedron {
+        match stmt.node() {
+            StatementNode::Concat(c) => {
+                let middle = self.statement(&*c.first, pre);
+                self.statement(&*c.second, &middle)
+            }
+            StatementNode::IfThenElse(i) => {
+                let mut then_pre = pre.clone();
+                then_pre.assume(&*i.guard, true);
+                let mut else_pre = pre.clone();
+                else_pre.assume(&*i.guard, false);
+                let then_post = self.statement(&*i.true_expr, &then_pre);
+                let else_post = self.statement(&*i.false_expr, &else_pre);
+                then_post.join(&else_post)
+            }
+            StatementNode::While(w) => {
+                let invariant = self.interpreter.loop_invariant(w, pre);
+                let termination = prove_termination(&mut self.interpreter, w, pre, &invariant);
+                self.reports.push(LoopReport {
+                    span: self.spans.span_of_statement(stmt),
+                    guard: w.guard.to_string(),
+                    termination,
+                });
+                // i cicli annidati si analizzano a partire dall'invariante
+                let mut body_pre = invariant.clone();
+                body_pre.assume(&*w.guard, true);
+                self.statement(&*w.body, &body_pre);
+                let mut post = invariant;
+                post.assume(&*w.guard, false);
+                post
+            }
+            _ => self.interpreter.run(stmt, pre),
+        }
+    }
+}
+
+// Prova la terminazione di ogni while cercando una funzione di rango lineare
+// a partire dagli invarianti calcolati con i poliedri
+pub fn check_termination(
+    program: &dyn Statement,
+    spans: &SourceMap,
+    initial: &State,
+) -> Vec<LoopReport> {
+    let mut checker = TerminationChecker {
+        interpreter: Interpreter::new(),
+        spans,
+        reports: Vec::new(),
+    };
+    checker.statement(program, &Polyhedron::from_state(initial));
+    checker.reports
+}
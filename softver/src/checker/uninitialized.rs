@@ -0,0 +1,84 @@
+use crate::analyzer::initialized::Initialized;
+use crate::analyzer::AbstractState;
+use crate::ast::arithmetic::{ArithmeticExpression, ArithmeticNode};
+use crate::ast::statement::Statement;
+use crate::ast::State;
+use crate::checker::analyze_expressions;
+use crate::source::{SourceMap, Span};
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+// Lettura di una variabile che su qualche cammino non è ancora stata assegnata
+#[derive(Debug, Clone, PartialEq)]
+pub struct UninitializedRead {
+    pub span: Option<Span>,
+    pub variable: String,
+}
+
+impl Display for UninitializedRead {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.span {
+            Some(span) => write!(f, "{}: ", span.start)?,
+            None => write!(f, "?: ")?,
+        }
+        write!(
+            f,
+            "warning: {} may be read before being assigned",
+            self.variable
+        )
+    }
+}
+
+fn check_expression(
+    expr: &dyn ArithmeticExpression,
+    initialized: &Initialized,
+    spans: &SourceMap,
+    reads: &mut Vec<UninitializedRead>,
+) {
+    match expr.node() {
+        ArithmeticNode::Numeral(_) => {}
+        ArithmeticNode::Variable(v) => {
+            if !initialized.contains(&v.value) {
+                reads.push(UninitializedRead {
+                    span: spans.span_of(expr),
+                    variable: v.value.clone(),
+                });
+            }
+        }
+        ArithmeticNode::Uminus(e) => check_expression(&*e.right, initialized, spans, reads),
+        ArithmeticNode::Add(e) => {
+            check_expression(&*e.left, initialized, spans, reads);
+            check_expression(&*e.right, initialized, spans, reads);
+        }
+        ArithmeticNode::Minus(e) => {
+            check_expression(&*e.left, initialized, spans, reads);
+            check_expression(&*e.right, initialized, spans, reads);
+        }
+        ArithmeticNode::Product(e) => {
+            check_expression(&*e.left, initialized, spans, reads);
+            check_expression(&*e.right, initialized, spans, reads);
+        }
+        ArithmeticNode::Divide(e) => {
+            check_expression(&*e.left, initialized, spans, reads);
+            check_expression(&*e.right, initialized, spans, reads);
+        }
+    }
+}
+
+// Letture di variabili non inizializzate su qualche cammino, partendo dalle
+// variabili definite nello stato iniziale. Le letture nel codice irraggiungibile
+// non vengono segnalate.
+pub fn check_uninitialized(
+    program: &dyn Statement,
+    spans: &SourceMap,
+    initial: &State,
+) -> Vec<UninitializedRead> {
+    let mut reads = Vec::new();
+    analyze_expressions(
+        program,
+        &Initialized::from_state(initial),
+        &mut |expr, initialized| check_expression(expr, initialized, spans, &mut reads),
+    );
+    reads.sort_by_key(|r| r.span.map(|s| s.start));
+    reads
+}
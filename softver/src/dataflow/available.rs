@@ -0,0 +1,60 @@
+use crate::cfg::{Cfg, Edge};
+use crate::dataflow::{defined, evaluated_subexpressions, Analysis, Direction, Mode};
+use std::collections::{BTreeMap, BTreeSet};
+
+// Espressioni disponibili: già calcolate su tutti i cammini e con gli operandi
+// non più modificati da allora. In avanti, must.
+pub struct AvailableExpressions {
+    // ogni espressione del programma con le variabili da cui dipende
+    expressions: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl AvailableExpressions {
+    pub fn new(cfg: &Cfg) -> Self {
+        let expressions = cfg
+            .edges()
+            .iter()
+            .flat_map(|edge| evaluated_subexpressions(&edge.action))
+            .collect();
+        AvailableExpressions { expressions }
+    }
+}
+
+impl<'a> Analysis<'a> for AvailableExpressions {
+    type Fact = String;
+
+    fn direction(&self) -> Direction {
+        Direction::Forward
+    }
+
+    fn mode(&self) -> Mode {
+        Mode::Must
+    }
+
+    fn universe(&self) -> BTreeSet<String> {
+        self.expressions.keys().cloned().collect()
+    }
+
+    // Le espressioni calcolate dall'arco, tranne quelle che l'assegnamento
+    // invalida subito (in x := x + 1, x + 1 non resta disponibile)
+    fn gen(&self, edge: &Edge<'a>) -> BTreeSet<String> {
+        let assigned = defined(&edge.action);
+        evaluated_subexpressions(&edge.action)
+            .into_iter()
+            .filter(|(_, vars)| assigned.is_none_or(|x| !vars.contains(x)))
+            .map(|(text, _)| text)
+            .collect()
+    }
+
+    fn kill(&self, edge: &Edge<'a>) -> BTreeSet<String> {
+        match defined(&edge.action) {
+            Some(var) => self
+                .expressions
+                .iter()
+                .filter(|(_, vars)| vars.contains(var))
+                .map(|(text, _)| text.clone())
+                .collect(),
+            None => BTreeSet::new(),
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use crate::cfg::{Cfg, Edge};
+use crate::dataflow::{defined, evaluated, variables, Analysis, Direction, Mode};
+use std::collections::BTreeSet;
+
+// Variabili vive: lette su almeno un cammino prima di essere riassegnate.
+// All'indietro, may.
+pub struct LiveVariables {
+    universe: BTreeSet<String>,
+    // variabili considerate lette alla fine del programma (di default nessuna)
+    live_at_exit: BTreeSet<String>,
+}
+
+impl LiveVariables {
+    pub fn new(cfg: &Cfg) -> Self {
+        let mut universe = BTreeSet::new();
+        for edge in cfg.edges() {
+            for expr in evaluated(&edge.action) {
+                variables(expr, &mut universe);
+            }
+            if let Some(var) = defined(&edge.action) {
+                universe.insert(var.to_string());
+            }
+        }
+        LiveVariables {
+            universe,
+            live_at_exit: BTreeSet::new(),
+        }
+    }
+
+    pub fn with_live_at_exit(mut self, vars: BTreeSet<String>) -> Self {
+        self.live_at_exit = vars;
+        self
+    }
+}
+
+impl<'a> Analysis<'a> for LiveVariables {
+    type Fact = String;
+
+    fn direction(&self) -> Direction {
+        Direction::Backward
+    }
+
+    fn mode(&self) -> Mode {
+        Mode::May
+    }
+
+    fn universe(&self) -> BTreeSet<String> {
+        self.universe.clone()
+    }
+
+    fn boundary(&self) -> BTreeSet<String> {
+        self.live_at_exit.clone()
+    }
+
+    fn gen(&self, edge: &Edge<'a>) -> BTreeSet<String> {
+        let mut result = BTreeSet::new();
+        for expr in evaluated(&edge.action) {
+            variables(expr, &mut result);
+        }
+        result
+    }
+
+    fn kill(&self, edge: &Edge<'a>) -> BTreeSet<String> {
+        defined(&edge.action).map(|v| v.to_string()).into_iter().collect()
+    }
+}
@@ -0,0 +1,231 @@
+pub mod available;
+pub mod live;
+pub mod reaching;
+pub mod very_busy;
+
+use crate::ast::arithmetic::{ArithmeticExpression, ArithmeticNode};
+use crate::ast::boolean::{BooleanExpression, BooleanNode};
+use crate::cfg::{Action, Cfg, Edge, Point};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+// Analisi classiche a vettori di bit nel framework monotono: i fatti sono insiemi
+// finiti di elementi, e ogni arco del grafo li trasforma con gen e kill.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Forward,
+    Backward,
+}
+
+// May: un fatto vale se vale su almeno un cammino (si uniscono gli insiemi).
+// Must: vale solo se vale su tutti i cammini (si intersecano).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    May,
+    Must,
+}
+
+pub trait Analysis<'a> {
+    type Fact: Ord + Clone + Display;
+
+    fn direction(&self) -> Direction;
+    fn mode(&self) -> Mode;
+
+    // Tutti i fatti possibili nel programma: il punto di partenza delle analisi must
+    fn universe(&self) -> BTreeSet<Self::Fact>;
+
+    // Fatti all'ingresso (in avanti) o all'uscita (all'indietro) del programma
+    fn boundary(&self) -> BTreeSet<Self::Fact> {
+        BTreeSet::new()
+    }
+
+    fn gen(&self, edge: &Edge<'a>) -> BTreeSet<Self::Fact>;
+    fn kill(&self, edge: &Edge<'a>) -> BTreeSet<Self::Fact>;
+
+    // gen ∪ (fatti \ kill)
+    fn transfer(&self, edge: &Edge<'a>, facts: &BTreeSet<Self::Fact>) -> BTreeSet<Self::Fact> {
+        let kill = self.kill(edge);
+        let mut result: BTreeSet<Self::Fact> = facts.difference(&kill).cloned().collect();
+        result.extend(self.gen(edge));
+        result
+    }
+}
+
+// Fatti calcolati in ogni punto di programma. In avanti sono i fatti validi
+// quando l'esecuzione arriva nel punto, all'indietro quelli validi quando ne riparte.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Solution<F> {
+    facts: Vec<BTreeSet<F>>,
+}
+
+impl<F: Ord + Clone + Display> Solution<F> {
+    pub fn at(&self, point: Point) -> &BTreeSet<F> {
+        &self.facts[point]
+    }
+
+    // Fatti del punto nella forma { a, b }
+    pub fn show(&self, point: Point) -> String {
+        if self.facts[point].is_empty() {
+            return "{}".to_string();
+        }
+        let facts: Vec<String> = self.facts[point].iter().map(|x| x.to_string()).collect();
+        format!("{{ {} }}", facts.join(", "))
+    }
+}
+
+// Una riga per punto: "punto: { fatti }"
+impl<F: Ord + Clone + Display> Display for Solution<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for point in 0..self.facts.len() {
+            writeln!(f, "{}: {}", point, self.show(point))?;
+        }
+        Ok(())
+    }
+}
+
+// Risolutore iterativo con lista di lavoro. Si parte da ⊥ del reticolo dei fatti
+// (l'insieme vuoto per le may, l'universo per le must) e si ricalcolano i punti
+// finché qualcosa cambia; gli insiemi sono finiti, quindi si termina sempre.
+pub fn solve<'a, A: Analysis<'a>>(analysis: &A, cfg: &Cfg<'a>) -> Solution<A::Fact> {
+    let start = match analysis.mode() {
+        Mode::May => BTreeSet::new(),
+        Mode::Must => analysis.universe(),
+    };
+    let boundary_point = match analysis.direction() {
+        Direction::Forward => cfg.entry(),
+        Direction::Backward => cfg.exit(),
+    };
+    let mut facts = vec![start; cfg.points()];
+    facts[boundary_point] = analysis.boundary();
+    let mut worklist: Vec<Point> = (0..cfg.points()).collect();
+    while let Some(point) = worklist.pop() {
+        // in avanti si combinano gli archi entranti, all'indietro quelli uscenti
+        let incoming: Vec<(&Edge<'a>, Point)> = match analysis.direction() {
+            Direction::Forward => cfg.predecessors(point).map(|e| (e, e.from)).collect(),
+            Direction::Backward => cfg.successors(point).map(|e| (e, e.to)).collect(),
+        };
+        // nel punto di confine arrivano anche i fatti del confine: l'ingresso può
+        // essere la testa di un while, l'uscita può avere archi uscenti
+        let boundary = (point == boundary_point).then(|| analysis.boundary());
+        let mut contributions = boundary.into_iter().chain(
+            incoming
+                .iter()
+                .map(|(edge, source)| analysis.transfer(edge, &facts[*source])),
+        );
+        let first = contributions.next().unwrap_or_default();
+        let new = contributions.fold(first, |result, next| match analysis.mode() {
+            Mode::May => result.union(&next).cloned().collect(),
+            Mode::Must => result.intersection(&next).cloned().collect(),
+        });
+        if new != facts[point] {
+            facts[point] = new;
+            let dependents: Vec<Point> = match analysis.direction() {
+                Direction::Forward => cfg.successors(point).map(|e| e.to).collect(),
+                Direction::Backward => cfg.predecessors(point).map(|e| e.from).collect(),
+            };
+            for dependent in dependents {
+                if !worklist.contains(&dependent) {
+                    worklist.push(dependent);
+                }
+            }
+        }
+    }
+    Solution { facts }
+}
+
+// Variabili lette da un'espressione
+pub fn variables(expr: &dyn ArithmeticExpression, result: &mut BTreeSet<String>) {
+    match expr.node() {
+        ArithmeticNode::Numeral(_) => {}
+        ArithmeticNode::Variable(v) => {
+            result.insert(v.value.clone());
+        }
+        ArithmeticNode::Uminus(e) => variables(&*e.right, result),
+        ArithmeticNode::Add(e) => {
+            variables(&*e.left, result);
+            variables(&*e.right, result);
+        }
+        ArithmeticNode::Minus(e) => {
+            variables(&*e.left, result);
+            variables(&*e.right, result);
+        }
+        ArithmeticNode::Product(e) => {
+            variables(&*e.left, result);
+            variables(&*e.right, result);
+        }
+        ArithmeticNode::Divide(e) => {
+            variables(&*e.left, result);
+            variables(&*e.right, result);
+        }
+    }
+}
+
+// Espressioni aritmetiche che compaiono direttamente in una guardia
+pub fn guard_operands(guard: &dyn BooleanExpression) -> Vec<&dyn ArithmeticExpression> {
+    match guard.node() {
+        BooleanNode::Boolean(_) => Vec::new(),
+        BooleanNode::Equal(e) => vec![&*e.left, &*e.right],
+        BooleanNode::GreatEqual(e) => vec![&*e.left, &*e.right],
+        BooleanNode::Great(e) => vec![&*e.left, &*e.right],
+        BooleanNode::LessEqual(e) => vec![&*e.left, &*e.right],
+        BooleanNode::Less(e) => vec![&*e.left, &*e.right],
+        BooleanNode::And(e) => {
+            let mut result = guard_operands(&*e.left);
+            result.extend(guard_operands(&*e.right));
+            result
+        }
+        BooleanNode::Or(e) => {
+            let mut result = guard_operands(&*e.left);
+            result.extend(guard_operands(&*e.right));
+            result
+        }
+        BooleanNode::Not(e) => guard_operands(&*e.expression),
+    }
+}
+
+// Espressioni valutate da un arco
+pub fn evaluated<'a>(action: &Action<'a>) -> Vec<&'a dyn ArithmeticExpression> {
+    match *action {
+        Action::Assign(a) => vec![&*a.expr],
+        Action::Assume(guard, _) => guard_operands(guard),
+        Action::Skip => Vec::new(),
+    }
+}
+
+// Variabile scritta da un arco
+pub fn defined<'a>(action: &Action<'a>) -> Option<&'a str> {
+    match *action {
+        Action::Assign(a) => Some(&a.var_name),
+        _ => None,
+    }
+}
+
+// Sottoespressioni non banali (né numerali né variabili), indicate dal testo
+// stampato, insieme alle variabili da cui dipendono
+pub fn subexpressions(expr: &dyn ArithmeticExpression, result: &mut Vec<(String, BTreeSet<String>)>) {
+    let children: Vec<&dyn ArithmeticExpression> = match expr.node() {
+        ArithmeticNode::Numeral(_) | ArithmeticNode::Variable(_) => return,
+        ArithmeticNode::Uminus(e) => vec![&*e.right],
+        ArithmeticNode::Add(e) => vec![&*e.left, &*e.right],
+        ArithmeticNode::Minus(e) => vec![&*e.left, &*e.right],
+        ArithmeticNode::Product(e) => vec![&*e.left, &*e.right],
+        ArithmeticNode::Divide(e) => vec![&*e.left, &*e.right],
+    };
+    for child in children {
+        subexpressions(child, result);
+    }
+    let mut vars = BTreeSet::new();
+    variables(expr, &mut vars);
+    result.push((expr.to_string(), vars));
+}
+
+// Sottoespressioni non banali valutate da un arco
+pub fn evaluated_subexpressions(action: &Action) -> Vec<(String, BTreeSet<String>)> {
+    let mut result = Vec::new();
+    for expr in evaluated(action) {
+        subexpressions(expr, &mut result);
+    }
+    result
+}
@@ -0,0 +1,80 @@
+use crate::cfg::{Cfg, Edge, Point};
+use crate::dataflow::{defined, Analysis, Direction, Mode};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+// Definizione: l'assegnamento a `var` che parte dal punto `point`
+// (da ogni punto parte al più un assegnamento)
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Definition {
+    pub var: String,
+    pub point: Point,
+}
+
+impl Display for Definition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.var, self.point)
+    }
+}
+
+// Definizioni raggiungenti: assegnamenti che arrivano al punto su almeno un
+// cammino senza essere sovrascritti. In avanti, may.
+pub struct ReachingDefinitions {
+    universe: BTreeSet<Definition>,
+}
+
+impl ReachingDefinitions {
+    pub fn new(cfg: &Cfg) -> Self {
+        let universe = cfg
+            .edges()
+            .iter()
+            .filter_map(|edge| {
+                defined(&edge.action).map(|var| Definition {
+                    var: var.to_string(),
+                    point: edge.from,
+                })
+            })
+            .collect();
+        ReachingDefinitions { universe }
+    }
+}
+
+impl<'a> Analysis<'a> for ReachingDefinitions {
+    type Fact = Definition;
+
+    fn direction(&self) -> Direction {
+        Direction::Forward
+    }
+
+    fn mode(&self) -> Mode {
+        Mode::May
+    }
+
+    fn universe(&self) -> BTreeSet<Definition> {
+        self.universe.clone()
+    }
+
+    fn gen(&self, edge: &Edge<'a>) -> BTreeSet<Definition> {
+        defined(&edge.action)
+            .map(|var| Definition {
+                var: var.to_string(),
+                point: edge.from,
+            })
+            .into_iter()
+            .collect()
+    }
+
+    // Tutte le altre definizioni della stessa variabile
+    fn kill(&self, edge: &Edge<'a>) -> BTreeSet<Definition> {
+        match defined(&edge.action) {
+            Some(var) => self
+                .universe
+                .iter()
+                .filter(|d| d.var == var)
+                .cloned()
+                .collect(),
+            None => BTreeSet::new(),
+        }
+    }
+}
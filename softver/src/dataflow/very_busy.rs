@@ -0,0 +1,57 @@
+use crate::cfg::{Cfg, Edge};
+use crate::dataflow::{defined, evaluated_subexpressions, Analysis, Direction, Mode};
+use std::collections::{BTreeMap, BTreeSet};
+
+// Espressioni molto usate: su tutti i cammini verranno calcolate prima che un
+// loro operando cambi, quindi si possono anticipare. All'indietro, must.
+pub struct VeryBusyExpressions {
+    expressions: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl VeryBusyExpressions {
+    pub fn new(cfg: &Cfg) -> Self {
+        let expressions = cfg
+            .edges()
+            .iter()
+            .flat_map(|edge| evaluated_subexpressions(&edge.action))
+            .collect();
+        VeryBusyExpressions { expressions }
+    }
+}
+
+impl<'a> Analysis<'a> for VeryBusyExpressions {
+    type Fact = String;
+
+    fn direction(&self) -> Direction {
+        Direction::Backward
+    }
+
+    fn mode(&self) -> Mode {
+        Mode::Must
+    }
+
+    fn universe(&self) -> BTreeSet<String> {
+        self.expressions.keys().cloned().collect()
+    }
+
+    // All'indietro gen si aggiunge dopo kill: in x := x + 1 l'espressione x + 1
+    // è calcolata prima dell'assegnamento, quindi è molto usata all'ingresso
+    fn gen(&self, edge: &Edge<'a>) -> BTreeSet<String> {
+        evaluated_subexpressions(&edge.action)
+            .into_iter()
+            .map(|(text, _)| text)
+            .collect()
+    }
+
+    fn kill(&self, edge: &Edge<'a>) -> BTreeSet<String> {
+        match defined(&edge.action) {
+            Some(var) => self
+                .expressions
+                .iter()
+                .filter(|(_, vars)| vars.contains(var))
+                .map(|(text, _)| text.clone())
+                .collect(),
+            None => BTreeSet::new(),
+        }
+    }
+}
@@ -0,0 +1,547 @@
+use crate::analyzer::linear::LinearExpression;
+use crate::analyzer::rational::{gcd, Rational};
+use crate::analyzer::{comparison_of, Comparison};
+use crate::ast::arithmetic::{ArithmeticExpression, ArithmeticNode};
+use crate::ast::boolean::{BooleanExpression, BooleanNode};
+use crate::ast::State;
+use crate::dataflow::{guard_operands, variables};
+use crate::hoare::VerificationCondition;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+// Limiti oltre i quali la procedura si arrende e risponde Unknown
+const MAX_CONSTRAINTS: usize = 2000;
+const MAX_BRANCHES: usize = 256;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Validity {
+    Valid,
+    // stato in cui la formula è falsa
+    Invalid(State),
+    Unknown,
+}
+
+impl Display for Validity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Validity::Valid => write!(f, "valid"),
+            Validity::Invalid(state) => {
+                let mut values: Vec<_> = state.iter().collect();
+                values.sort();
+                let values: Vec<String> = values
+                    .iter()
+                    .map(|(var, value)| format!("{} = {}", var, value))
+                    .collect();
+                write!(f, "invalid, counterexample: {}", values.join(", "))
+            }
+            Validity::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+// Esito di una ricerca di modelli, sulle variabili numerate da 0
+enum Outcome {
+    Sat(Vec<i128>),
+    Unsat,
+    Unknown,
+}
+
+// Formula in forma normale negata: le negazioni sono assorbite negli atomi,
+// che sono tutti della forma e ≥ 0. And vuoto è vero, Or vuoto è falso.
+enum Formula {
+    Atom(LinearExpression),
+    And(Vec<Formula>),
+    Or(Vec<Formula>),
+}
+
+// Forma lineare di un'espressione. I prodotti tra variabili e le divisioni
+// diventano variabili opache, che si chiamano come il sottotermine (non sono
+// identificatori validi e non si confondono con quelle del programma).
+fn term(
+    expr: &dyn ArithmeticExpression,
+    opaque: &mut BTreeSet<String>,
+) -> Option<LinearExpression> {
+    let abstracted = |opaque: &mut BTreeSet<String>| {
+        let name = expr.to_string();
+        let result = LinearExpression::variable(&name);
+        opaque.insert(name);
+        Some(result)
+    };
+    match expr.node() {
+        ArithmeticNode::Numeral(n) => Some(LinearExpression::constant(n.0 as i64)),
+        ArithmeticNode::Variable(v) => Some(LinearExpression::variable(&v.value)),
+        ArithmeticNode::Add(e) => term(&*e.left, opaque)?.combine(1, &term(&*e.right, opaque)?, 1),
+        ArithmeticNode::Minus(e) => {
+            term(&*e.left, opaque)?.combine(1, &term(&*e.right, opaque)?, -1)
+        }
+        ArithmeticNode::Uminus(e) => term(&*e.right, opaque)?.scale(-1),
+        ArithmeticNode::Product(e) => {
+            let left = term(&*e.left, opaque)?;
+            let right = term(&*e.right, opaque)?;
+            if left.is_constant() {
+                right.scale(left.constant)
+            } else if right.is_constant() {
+                left.scale(right.constant)
+            } else {
+                abstracted(opaque)
+            }
+        }
+        ArithmeticNode::Divide(_) => abstracted(opaque),
+    }
+}
+
+fn formula(
+    guard: &dyn BooleanExpression,
+    polarity: bool,
+    opaque: &mut BTreeSet<String>,
+) -> Option<Formula> {
+    if let Some((op, left, right)) = comparison_of(guard) {
+        let op = if polarity { op } else { op.negate() };
+        // d = left - right
+        let d = term(left, opaque)?.combine(1, &term(right, opaque)?, -1)?;
+        let minus_one = LinearExpression::constant(-1);
+        let atom = |a: i64, b: i64| d.combine(a, &minus_one, b).map(Formula::Atom);
+        return Some(match op {
+            Comparison::GreatEqual => atom(1, 0)?,
+            Comparison::Great => atom(1, 1)?,
+            Comparison::LessEqual => atom(-1, 0)?,
+            Comparison::Less => atom(-1, 1)?,
+            Comparison::Equal => Formula::And(vec![atom(1, 0)?, atom(-1, 0)?]),
+            Comparison::NotEqual => Formula::Or(vec![atom(1, 1)?, atom(-1, 1)?]),
+        });
+    }
+    match guard.node() {
+        BooleanNode::Boolean(b) if b.0 == polarity => Some(Formula::And(Vec::new())),
+        BooleanNode::Boolean(_) => Some(Formula::Or(Vec::new())),
+        BooleanNode::Not(n) => formula(&*n.expression, !polarity, opaque),
+        BooleanNode::And(a) => {
+            let children = vec![
+                formula(&*a.left, polarity, opaque)?,
+                formula(&*a.right, polarity, opaque)?,
+            ];
+            Some(if polarity {
+                Formula::And(children)
+            } else {
+                Formula::Or(children)
+            })
+        }
+        BooleanNode::Or(o) => {
+            let children = vec![
+                formula(&*o.left, polarity, opaque)?,
+                formula(&*o.right, polarity, opaque)?,
+            ];
+            Some(if polarity {
+                Formula::Or(children)
+            } else {
+                Formula::And(children)
+            })
+        }
+        _ => unreachable!("confronto già gestito"),
+    }
+}
+
+// Vincolo Σ aᵢ·xᵢ + c ≥ 0: i coefficienti delle variabili seguiti dal termine noto
+type Vector = Vec<i128>;
+
+// Divide per il MCD dei coefficienti e arrotonda per difetto il termine noto:
+// gli stessi punti interi con un vincolo più stretto sui reali.
+// None se il vincolo non ha più variabili.
+fn tighten(mut v: Vector) -> Option<Vector> {
+    let n = v.len() - 1;
+    let g = v[..n].iter().fold(0, |g, a| gcd(g, *a));
+    if g == 0 {
+        return None;
+    }
+    for a in &mut v[..n] {
+        *a /= g;
+    }
+    v[n] = v[n].div_euclid(g);
+    Some(v)
+}
+
+// Stringe ogni vincolo ed elimina i duplicati; None se un vincolo senza
+// variabili è falso
+fn normalize(system: Vec<Vector>) -> Option<Vec<Vector>> {
+    let mut result = Vec::new();
+    for v in system {
+        let constant = v[v.len() - 1];
+        match tighten(v) {
+            Some(v) => result.push(v),
+            None if constant < 0 => return None,
+            None => {}
+        }
+    }
+    result.sort();
+    result.dedup();
+    Some(result)
+}
+
+// Valore da dare a una variabile tra i suoi limiti: l'intero più vicino a 0
+// se ce n'è uno, altrimenti il limite inferiore (che il branch and bound scarterà)
+fn choose(low: Option<Rational>, high: Option<Rational>) -> Rational {
+    let low_int = low.map(|l| l.ceil());
+    let high_int = high.map(|h| h.floor());
+    match (low_int, high_int) {
+        (Some(l), Some(h)) if l > h => low.unwrap(),
+        (l, h) => Rational::from(
+            0i128
+                .max(l.unwrap_or(i128::MIN))
+                .min(h.unwrap_or(i128::MAX)),
+        ),
+    }
+}
+
+// -factor·w + scale·v, None in caso di overflow
+fn eliminate(w: &Vector, scale: i128, v: &Vector, factor: i128) -> Option<Vector> {
+    w.iter()
+        .zip(v.iter())
+        .map(|(a, b)| scale.checked_mul(*a)?.checked_sub(factor.checked_mul(*b)?))
+        .collect()
+}
+
+// La prossima variabile da eliminare: prima quelle fissate da un'uguaglianza
+// con coefficiente ±1, che si sostituiscono senza perdere precisione, poi
+// quella che produce meno combinazioni. Restituisce anche l'uguaglianza.
+fn pivot(current: &[Vector], n: usize) -> Option<(usize, Option<&Vector>)> {
+    for v in current {
+        let negated: Vector = v.iter().map(|a| -a).collect();
+        if let Some(k) = (0..n).find(|k| v[*k].abs() == 1) {
+            if current.binary_search(&negated).is_ok() {
+                return Some((k, Some(v)));
+            }
+        }
+    }
+    (0..n)
+        .filter(|k| current.iter().any(|v| v[*k] != 0))
+        .min_by_key(|k| {
+            let lower = current.iter().filter(|v| v[*k] > 0).count();
+            let upper = current.iter().filter(|v| v[*k] < 0).count();
+            lower * upper
+        })
+        .map(|k| (k, None))
+}
+
+// Eliminazione di Fourier–Motzkin, con i vincoli stretti sugli interi a ogni
+// passo, e ricostruzione all'indietro di un punto razionale che li soddisfa.
+// Some(None) se il sistema non ha soluzioni, None se i vincoli crescono
+// troppo o i coefficienti vanno in overflow.
+fn real_model(system: Vec<Vector>, n: usize) -> Option<Option<Vec<Rational>>> {
+    // per ogni variabile eliminata, i vincoli in cui compariva ancora
+    let mut stages: Vec<(usize, Vec<Vector>)> = Vec::new();
+    let Some(mut current) = normalize(system) else {
+        return Some(None);
+    };
+    while let Some((k, equality)) = pivot(&current, n) {
+        let mut next: Vec<Vector> = current.iter().filter(|v| v[k] == 0).cloned().collect();
+        match equality {
+            // x_k = -v[k]·(resto): si sostituisce negli altri vincoli
+            Some(v) => {
+                for w in current.iter().filter(|w| w[k] != 0) {
+                    next.push(eliminate(w, 1, v, w[k] * v[k])?);
+                }
+            }
+            None => {
+                let (lower, upper): (Vec<&Vector>, Vec<&Vector>) =
+                    current.iter().filter(|v| v[k] != 0).partition(|v| v[k] > 0);
+                for l in &lower {
+                    for u in &upper {
+                        // -u[k]·l + l[k]·u: il coefficiente di x_k si annulla
+                        next.push(eliminate(l, -u[k], u, -l[k])?);
+                    }
+                }
+            }
+        }
+        if next.len() > MAX_CONSTRAINTS {
+            return None;
+        }
+        let Some(normalized) = normalize(next) else {
+            return Some(None);
+        };
+        stages.push((k, std::mem::replace(&mut current, normalized)));
+    }
+    // le variabili mai vincolate restano a 0
+    let mut model = vec![Rational::zero(); n];
+    for (k, stage) in stages.iter().rev() {
+        let k = *k;
+        let (mut low, mut high): (Option<Rational>, Option<Rational>) = (None, None);
+        for v in stage.iter().filter(|v| v[k] != 0) {
+            // v[k]·x_k + rest ≥ 0; le variabili eliminate prima hanno coefficiente nullo
+            let rest = (0..n)
+                .filter(|j| *j != k)
+                .try_fold(Rational::from(v[n]), |rest, j| {
+                    rest.checked_add(Rational::from(v[j]).checked_mul(model[j])?)
+                })?;
+            let bound = (-rest).checked_div(Rational::from(v[k]))?;
+            if v[k] > 0 {
+                low = Some(low.map_or(bound, |l| l.max(bound)));
+            } else {
+                high = Some(high.map_or(bound, |h| h.min(bound)));
+            }
+        }
+        model[k] = choose(low, high);
+    }
+    Some(Some(model))
+}
+
+// Branch and bound sul primo valore non intero del modello reale
+fn integer_model(system: Vec<Vector>, n: usize, budget: &mut usize) -> Outcome {
+    let model = match real_model(system.clone(), n) {
+        None => return Outcome::Unknown,
+        Some(None) => return Outcome::Unsat,
+        Some(Some(model)) => model,
+    };
+    let Some(k) = model.iter().position(|x| !x.is_integer()) else {
+        return Outcome::Sat(model.iter().map(|x| x.numerator()).collect());
+    };
+    if *budget == 0 {
+        return Outcome::Unknown;
+    }
+    *budget -= 1;
+    // x_k ≤ ⌊v⌋ oppure x_k ≥ ⌈v⌉
+    let mut below = vec![0; n + 1];
+    below[k] = -1;
+    below[n] = model[k].floor();
+    let mut above = vec![0; n + 1];
+    above[k] = 1;
+    above[n] = -model[k].ceil();
+    let mut unknown = false;
+    for branch in [below, above] {
+        let mut system = system.clone();
+        system.push(branch);
+        match integer_model(system, n, budget) {
+            Outcome::Sat(model) => return Outcome::Sat(model),
+            Outcome::Unsat => {}
+            Outcome::Unknown => unknown = true,
+        }
+    }
+    if unknown {
+        Outcome::Unknown
+    } else {
+        Outcome::Unsat
+    }
+}
+
+// DPLL(T) senza apprendimento di clausole sulla codifica di Plaisted–Greenbaum
+// della formula: le variabili booleane sono gli atomi e i nodi And/Or. Si decide
+// solo sui letterali delle clausole ancora aperte.
+struct Dpll {
+    // l'atomo associato a ogni variabile booleana, se non è un nodo
+    atoms: Vec<Option<Vector>>,
+    clauses: Vec<Vec<(usize, bool)>>,
+    // vincoli sempre presenti: le variabili del programma stanno negli i32
+    bounds: Vec<Vector>,
+    names: Vec<String>,
+}
+
+impl Dpll {
+    fn fresh(&mut self, atom: Option<Vector>) -> usize {
+        self.atoms.push(atom);
+        self.atoms.len() - 1
+    }
+
+    fn vector(&self, e: &LinearExpression) -> Vector {
+        let mut v: Vector = self
+            .names
+            .iter()
+            .map(|name| e.coefficient(name) as i128)
+            .collect();
+        v.push(e.constant as i128);
+        v
+    }
+
+    // Variabile booleana equivalente al nodo; ogni nodo implica i suoi figli
+    fn encode(&mut self, f: &Formula) -> usize {
+        match f {
+            Formula::Atom(e) => {
+                let v = self.vector(e);
+                match self.atoms.iter().position(|a| a.as_ref() == Some(&v)) {
+                    Some(var) => var,
+                    None => self.fresh(Some(v)),
+                }
+            }
+            Formula::And(children) => {
+                let children: Vec<usize> = children.iter().map(|c| self.encode(c)).collect();
+                let node = self.fresh(None);
+                for child in children {
+                    self.clauses.push(vec![(node, false), (child, true)]);
+                }
+                node
+            }
+            Formula::Or(children) => {
+                let children: Vec<usize> = children.iter().map(|c| self.encode(c)).collect();
+                let node = self.fresh(None);
+                let mut clause = vec![(node, false)];
+                clause.extend(children.iter().map(|child| (*child, true)));
+                self.clauses.push(clause);
+                node
+            }
+        }
+    }
+
+    // Congiunzione degli atomi assegnati: e ≥ 0 se veri, -e - 1 ≥ 0 se falsi
+    fn theory(&self, assignment: &[Option<bool>]) -> Outcome {
+        let mut system = self.bounds.clone();
+        for (atom, value) in self.atoms.iter().zip(assignment) {
+            match (atom, value) {
+                (Some(v), Some(true)) => system.push(v.clone()),
+                (Some(v), Some(false)) => {
+                    let mut negated: Vector = v.iter().map(|a| -a).collect();
+                    negated[self.names.len()] -= 1;
+                    system.push(negated);
+                }
+                _ => {}
+            }
+        }
+        let mut budget = MAX_BRANCHES;
+        integer_model(system, self.names.len(), &mut budget)
+    }
+
+    // `checked`: atomi assegnati all'ultimo controllo della teoria
+    fn search(&self, mut assignment: Vec<Option<bool>>, checked: usize) -> Outcome {
+        // propagazione unitaria; intanto si cerca una clausola ancora aperta
+        let mut changed = true;
+        let mut open = None;
+        while changed {
+            changed = false;
+            open = None;
+            for clause in &self.clauses {
+                let mut unassigned = Vec::new();
+                let mut satisfied = false;
+                for &(var, sign) in clause {
+                    match assignment[var] {
+                        Some(value) if value == sign => satisfied = true,
+                        Some(_) => {}
+                        None => unassigned.push((var, sign)),
+                    }
+                }
+                if satisfied {
+                    continue;
+                }
+                match unassigned[..] {
+                    [] => return Outcome::Unsat,
+                    [(var, sign)] => {
+                        assignment[var] = Some(sign);
+                        changed = true;
+                    }
+                    _ => open = open.or(Some(unassigned[0])),
+                }
+            }
+        }
+        // gli atomi già assegnati devono essere consistenti tra loro; se tutte
+        // le clausole sono soddisfatte gli atomi liberi non servono
+        let Some((var, sign)) = open else {
+            return self.theory(&assignment);
+        };
+        let assigned = (self.atoms.iter().zip(&assignment))
+            .filter(|(atom, value)| atom.is_some() && value.is_some())
+            .count();
+        if assigned > checked {
+            if let Outcome::Unsat = self.theory(&assignment) {
+                return Outcome::Unsat;
+            }
+        }
+        let mut unknown = false;
+        for value in [sign, !sign] {
+            let mut next = assignment.clone();
+            next[var] = Some(value);
+            match self.search(next, assigned) {
+                Outcome::Sat(model) => return Outcome::Sat(model),
+                Outcome::Unsat => {}
+                Outcome::Unknown => unknown = true,
+            }
+        }
+        if unknown {
+            Outcome::Unknown
+        } else {
+            Outcome::Unsat
+        }
+    }
+}
+
+// Valore di un'espressione sugli interi matematici (None per divisione per zero)
+fn evaluate(expr: &dyn ArithmeticExpression, state: &State) -> Option<i64> {
+    match expr.node() {
+        ArithmeticNode::Numeral(n) => Some(n.0 as i64),
+        ArithmeticNode::Variable(v) => state.get(&v.value).map(|x| *x as i64),
+        ArithmeticNode::Add(e) => {
+            evaluate(&*e.left, state)?.checked_add(evaluate(&*e.right, state)?)
+        }
+        ArithmeticNode::Minus(e) => {
+            evaluate(&*e.left, state)?.checked_sub(evaluate(&*e.right, state)?)
+        }
+        ArithmeticNode::Product(e) => {
+            evaluate(&*e.left, state)?.checked_mul(evaluate(&*e.right, state)?)
+        }
+        ArithmeticNode::Divide(e) => {
+            evaluate(&*e.left, state)?.checked_div(evaluate(&*e.right, state)?)
+        }
+        ArithmeticNode::Uminus(e) => evaluate(&*e.right, state)?.checked_neg(),
+    }
+}
+
+fn holds(guard: &dyn BooleanExpression, state: &State) -> Option<bool> {
+    if let Some((op, left, right)) = comparison_of(guard) {
+        return Some(op.holds(evaluate(left, state)?, evaluate(right, state)?));
+    }
+    match guard.node() {
+        BooleanNode::Boolean(b) => Some(b.0),
+        BooleanNode::Not(n) => Some(!holds(&*n.expression, state)?),
+        BooleanNode::And(a) => Some(holds(&*a.left, state)? && holds(&*a.right, state)?),
+        BooleanNode::Or(o) => Some(holds(&*o.left, state)? || holds(&*o.right, state)?),
+        _ => unreachable!("confronto già gestito"),
+    }
+}
+
+// Decide se la formula vale per ogni valore a 32 bit delle sue variabili,
+// cercando un modello della negazione. I sottotermini non lineari sono
+// opachi: un modello della negazione diventa un controesempio solo se la
+// formula è davvero falsa nello stato trovato.
+pub fn check_validity(guard: &dyn BooleanExpression) -> Validity {
+    let mut opaque = BTreeSet::new();
+    let Some(negation) = formula(guard, false, &mut opaque) else {
+        return Validity::Unknown;
+    };
+    let mut program_variables = BTreeSet::new();
+    for operand in guard_operands(guard) {
+        variables(operand, &mut program_variables);
+    }
+    let names: Vec<String> = program_variables.iter().chain(&opaque).cloned().collect();
+    let n = names.len();
+    let mut dpll = Dpll {
+        atoms: Vec::new(),
+        clauses: Vec::new(),
+        bounds: Vec::new(),
+        names,
+    };
+    for k in 0..program_variables.len() {
+        let mut low = vec![0; n + 1];
+        low[k] = 1;
+        low[n] = -(i32::MIN as i128);
+        let mut high = vec![0; n + 1];
+        high[k] = -1;
+        high[n] = i32::MAX as i128;
+        dpll.bounds.extend([low, high]);
+    }
+    let root = dpll.encode(&negation);
+    dpll.clauses.push(vec![(root, true)]);
+    match dpll.search(vec![None; dpll.atoms.len()], 0) {
+        Outcome::Unsat => Validity::Valid,
+        Outcome::Unknown => Validity::Unknown,
+        Outcome::Sat(model) => {
+            let state: State = program_variables
+                .iter()
+                .zip(model)
+                .map(|(var, value)| (var.clone(), value as i32))
+                .collect();
+            match holds(guard, &state) {
+                Some(false) => Validity::Invalid(state),
+                _ => Validity::Unknown,
+            }
+        }
+    }
+}
+
+pub fn check_condition(condition: &VerificationCondition) -> Validity {
+    check_validity(&*condition.formula())
+}
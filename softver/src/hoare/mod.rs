@@ -0,0 +1,44 @@
+pub mod lia;
+pub mod smtlib;
+pub mod wp;
+
+use crate::ast::boolean::{BooleanExpression, Not, Or};
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+// Condizione di verifica: la congiunzione delle ipotesi implica l'obiettivo,
+// per ogni valore a 32 bit delle variabili libere. Le operazioni invece sono
+// sugli interi senza limiti: gli overflow sono compito del loro checker.
+#[derive(Debug, Clone)]
+pub struct VerificationCondition {
+    pub description: String,
+    pub hypotheses: Vec<Box<dyn BooleanExpression>>,
+    pub goal: Box<dyn BooleanExpression>,
+}
+
+impl VerificationCondition {
+    // La condizione come un'unica formula: !(h1 && ... && hn) || goal
+    pub fn formula(&self) -> Box<dyn BooleanExpression> {
+        let mut result = self.goal.clone();
+        for hypothesis in self.hypotheses.iter().rev() {
+            result = Box::new(Or {
+                left: Box::new(Not {
+                    expression: hypothesis.clone(),
+                }),
+                right: result,
+            });
+        }
+        result
+    }
+}
+
+impl Display for VerificationCondition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.description)?;
+        let hypotheses: Vec<String> = self.hypotheses.iter().map(|h| h.to_string()).collect();
+        if !hypotheses.is_empty() {
+            write!(f, "{} ", hypotheses.join(", "))?;
+        }
+        write!(f, "⊢ {}", self.goal)
+    }
+}
@@ -0,0 +1,97 @@
+use crate::ast::arithmetic::{ArithmeticExpression, ArithmeticNode};
+use crate::ast::boolean::{BooleanExpression, BooleanNode};
+use crate::dataflow::{guard_operands, variables};
+use crate::hoare::VerificationCondition;
+use std::collections::BTreeSet;
+
+// La divisione del linguaggio tronca verso zero, mentre `div` di SMT-LIB
+// arrotonda verso il basso per divisori positivi: si definisce quella troncata
+const TRUNCATED_DIVISION: &str = "(define-fun tdiv ((a Int) (b Int)) Int \
+    (let ((q (div (abs a) (abs b)))) (ite (= (< a 0) (< b 0)) q (- q))))";
+
+pub fn arithmetic(expr: &dyn ArithmeticExpression) -> String {
+    match expr.node() {
+        ArithmeticNode::Numeral(n) if n.0 < 0 => format!("(- {})", -(n.0 as i64)),
+        ArithmeticNode::Numeral(n) => n.0.to_string(),
+        ArithmeticNode::Variable(v) => v.value.clone(),
+        ArithmeticNode::Add(e) => format!("(+ {} {})", arithmetic(&*e.left), arithmetic(&*e.right)),
+        ArithmeticNode::Minus(e) => {
+            format!("(- {} {})", arithmetic(&*e.left), arithmetic(&*e.right))
+        }
+        ArithmeticNode::Product(e) => {
+            format!("(* {} {})", arithmetic(&*e.left), arithmetic(&*e.right))
+        }
+        ArithmeticNode::Divide(e) => {
+            format!("(tdiv {} {})", arithmetic(&*e.left), arithmetic(&*e.right))
+        }
+        ArithmeticNode::Uminus(e) => format!("(- {})", arithmetic(&*e.right)),
+    }
+}
+
+pub fn boolean(guard: &dyn BooleanExpression) -> String {
+    let comparison =
+        |op: &str, left: &dyn ArithmeticExpression, right: &dyn ArithmeticExpression| {
+            format!("({} {} {})", op, arithmetic(left), arithmetic(right))
+        };
+    match guard.node() {
+        BooleanNode::Boolean(b) => b.0.to_string(),
+        BooleanNode::Equal(e) => comparison("=", &*e.left, &*e.right),
+        BooleanNode::GreatEqual(e) => comparison(">=", &*e.left, &*e.right),
+        BooleanNode::Great(e) => comparison(">", &*e.left, &*e.right),
+        BooleanNode::LessEqual(e) => comparison("<=", &*e.left, &*e.right),
+        BooleanNode::Less(e) => comparison("<", &*e.left, &*e.right),
+        BooleanNode::And(e) => format!("(and {} {})", boolean(&*e.left), boolean(&*e.right)),
+        BooleanNode::Or(e) => format!("(or {} {})", boolean(&*e.left), boolean(&*e.right)),
+        BooleanNode::Not(e) => format!("(not {})", boolean(&*e.expression)),
+    }
+}
+
+// Script SMT-LIB2 che controlla ogni condizione separatamente: si asserisce la
+// negazione, quindi `unsat` significa che la condizione è valida. Le variabili
+// valgono su 32 bit come nel decisore interno, le operazioni sono sugli interi.
+pub fn script(conditions: &[VerificationCondition]) -> String {
+    let mut names = BTreeSet::new();
+    for condition in conditions {
+        for formula in condition.hypotheses.iter().chain([&condition.goal]) {
+            for operand in guard_operands(&**formula) {
+                variables(operand, &mut names);
+            }
+        }
+    }
+    let mut lines = vec![
+        "(set-logic ALL)".to_string(),
+        TRUNCATED_DIVISION.to_string(),
+    ];
+    for name in &names {
+        lines.push(format!("(declare-const {} Int)", name));
+        lines.push(format!(
+            "(assert (and (<= (- {}) {}) (<= {} {})))",
+            -(i32::MIN as i64),
+            name,
+            name,
+            i32::MAX
+        ));
+    }
+    for condition in conditions {
+        let hypotheses: Vec<String> = condition.hypotheses.iter().map(|h| boolean(&**h)).collect();
+        let hypothesis = match hypotheses.len() {
+            0 => "true".to_string(),
+            1 => hypotheses[0].clone(),
+            _ => format!("(and {})", hypotheses.join(" ")),
+        };
+        lines.push(format!(
+            "(echo \"{}\")",
+            condition.description.replace('"', "\"\"")
+        ));
+        lines.push("(push 1)".to_string());
+        lines.push(format!(
+            "(assert (not (=> {} {})))",
+            hypothesis,
+            boolean(&*condition.goal)
+        ));
+        lines.push("(check-sat)".to_string());
+        lines.push("(pop 1)".to_string());
+    }
+    lines.push(String::new());
+    lines.join("\n")
+}
@@ -0,0 +1,194 @@
+use crate::ast::arithmetic::{
+    Add, ArithmeticExpression, ArithmeticNode, Divide, Minus, Product, Uminus,
+};
+use crate::ast::boolean::{
+    And, Boolean, BooleanExpression, BooleanNode, Equal, Great, GreatEqual, Less, LessEqual, Not,
+    Or,
+};
+use crate::ast::statement::{Statement, StatementNode};
+use crate::ast::{OverflowSemantics, Specification};
+use crate::hoare::VerificationCondition;
+use crate::optimizer::simplify::simplify_boolean;
+
+// Sostituzione simultanea: ogni variabile per cui `value_of` restituisce
+// un'espressione viene rimpiazzata, le altre restano
+pub fn replace_arithmetic(
+    expr: &dyn ArithmeticExpression,
+    value_of: &dyn Fn(&str) -> Option<Box<dyn ArithmeticExpression>>,
+) -> Box<dyn ArithmeticExpression> {
+    let sub = |e: &dyn ArithmeticExpression| replace_arithmetic(e, value_of);
+    match expr.node() {
+        ArithmeticNode::Numeral(n) => Box::new(n.clone()),
+        ArithmeticNode::Variable(v) => value_of(&v.value).unwrap_or_else(|| Box::new(v.clone())),
+        ArithmeticNode::Add(e) => Box::new(Add {
+            left: sub(&*e.left),
+            right: sub(&*e.right),
+        }),
+        ArithmeticNode::Minus(e) => Box::new(Minus {
+            left: sub(&*e.left),
+            right: sub(&*e.right),
+        }),
+        ArithmeticNode::Product(e) => Box::new(Product {
+            left: sub(&*e.left),
+            right: sub(&*e.right),
+        }),
+        ArithmeticNode::Divide(e) => Box::new(Divide {
+            left: sub(&*e.left),
+            right: sub(&*e.right),
+        }),
+        ArithmeticNode::Uminus(e) => Box::new(Uminus {
+            right: sub(&*e.right),
+        }),
+    }
+}
+
+pub fn replace_boolean(
+    guard: &dyn BooleanExpression,
+    value_of: &dyn Fn(&str) -> Option<Box<dyn ArithmeticExpression>>,
+) -> Box<dyn BooleanExpression> {
+    let sub = |e: &dyn ArithmeticExpression| replace_arithmetic(e, value_of);
+    let sub_boolean = |g: &dyn BooleanExpression| replace_boolean(g, value_of);
+    match guard.node() {
+        BooleanNode::Boolean(b) => Box::new(b.clone()),
+        BooleanNode::Equal(e) => Box::new(Equal {
+            left: sub(&*e.left),
+            right: sub(&*e.right),
+        }),
+        BooleanNode::GreatEqual(e) => Box::new(GreatEqual {
+            left: sub(&*e.left),
+            right: sub(&*e.right),
+        }),
+        BooleanNode::Great(e) => Box::new(Great {
+            left: sub(&*e.left),
+            right: sub(&*e.right),
+        }),
+        BooleanNode::LessEqual(e) => Box::new(LessEqual {
+            left: sub(&*e.left),
+            right: sub(&*e.right),
+        }),
+        BooleanNode::Less(e) => Box::new(Less {
+            left: sub(&*e.left),
+            right: sub(&*e.right),
+        }),
+        BooleanNode::And(e) => Box::new(And {
+            left: sub_boolean(&*e.left),
+            right: sub_boolean(&*e.right),
+        }),
+        BooleanNode::Or(e) => Box::new(Or {
+            left: sub_boolean(&*e.left),
+            right: sub_boolean(&*e.right),
+        }),
+        BooleanNode::Not(e) => Box::new(Not {
+            expression: sub_boolean(&*e.expression),
+        }),
+    }
+}
+
+// guard[value/var]
+pub fn substitute_boolean(
+    guard: &dyn BooleanExpression,
+    var: &str,
+    value: &dyn ArithmeticExpression,
+) -> Box<dyn BooleanExpression> {
+    replace_boolean(guard, &|name| (name == var).then(|| value.to_boxed()))
+}
+
+fn not(guard: &dyn BooleanExpression) -> Box<dyn BooleanExpression> {
+    Box::new(Not {
+        expression: guard.to_boxed(),
+    })
+}
+
+// Le formule si semplificano con le identità valide anche sugli interi
+// matematici: con la semantica Error non si riassocia e non si tolgono
+// negazioni doppie che potrebbero andare in overflow
+fn simplify(guard: &dyn BooleanExpression) -> Box<dyn BooleanExpression> {
+    simplify_boolean(guard, OverflowSemantics::Error)
+}
+
+struct Generator {
+    conditions: Vec<VerificationCondition>,
+}
+
+impl Generator {
+    // Precondizione più debole di stmt rispetto a post. Ogni while restituisce il
+    // suo invariante (true se non è dichiarato) e aggiunge le due condizioni che
+    // lo rendono corretto.
+    fn wp(
+        &mut self,
+        stmt: &dyn Statement,
+        post: &dyn BooleanExpression,
+    ) -> Box<dyn BooleanExpression> {
+        match stmt.node() {
+            StatementNode::Assign(a) => simplify(&*substitute_boolean(post, &a.var_name, &*a.expr)),
+            StatementNode::Skip(_) => post.to_boxed(),
+            StatementNode::Concat(c) => {
+                let middle = self.wp(&*c.second, post);
+                self.wp(&*c.first, &*middle)
+            }
+            // (b && wp(S1, Q)) || (!b && wp(S2, Q))
+            StatementNode::IfThenElse(i) => {
+                let then_wp = self.wp(&*i.true_expr, post);
+                let else_wp = self.wp(&*i.false_expr, post);
+                let formula = Or {
+                    left: Box::new(And {
+                        left: i.guard.clone(),
+                        right: then_wp,
+                    }),
+                    right: Box::new(And {
+                        left: not(&*i.guard),
+                        right: else_wp,
+                    }),
+                };
+                simplify(&formula)
+            }
+            StatementNode::While(w) => {
+                let invariant: Box<dyn BooleanExpression> = match &w.invariant {
+                    Some(invariant) => invariant.clone(),
+                    None => Box::new(Boolean(true)),
+                };
+                let body_wp = self.wp(&*w.body, &*invariant);
+                self.conditions.push(VerificationCondition {
+                    description: format!("invariant {} preserved by while {}", invariant, w.guard),
+                    hypotheses: vec![invariant.clone(), w.guard.clone()],
+                    goal: body_wp,
+                });
+                self.conditions.push(VerificationCondition {
+                    description: format!(
+                        "invariant {} implies the post of while {}",
+                        invariant, w.guard
+                    ),
+                    hypotheses: vec![invariant.clone(), not(&*w.guard)],
+                    goal: post.to_boxed(),
+                });
+                invariant
+            }
+            StatementNode::Assert(a) => simplify(&And {
+                left: a.condition.clone(),
+                right: post.to_boxed(),
+            }),
+            StatementNode::Assume(a) => simplify(&Or {
+                left: not(&*a.condition),
+                right: post.to_boxed(),
+            }),
+        }
+    }
+}
+
+// Condizioni di verifica della tripla: la precondizione implica la wp del
+// programma, più le condizioni di ogni ciclo, nell'ordine del sorgente.
+// Le variabili valgono su 32 bit ma le operazioni sono sugli interi
+// matematici: overflow e divisioni per zero sono compito dei rispettivi checker.
+pub fn verification_conditions(specification: &Specification) -> Vec<VerificationCondition> {
+    let mut generator = Generator {
+        conditions: Vec::new(),
+    };
+    let wp = generator.wp(&*specification.program, &*specification.post);
+    let mut conditions = vec![VerificationCondition {
+        description: "precondition implies the weakest precondition".to_string(),
+        hypotheses: vec![specification.pre.clone()],
+        goal: wp,
+    }];
+    conditions.append(&mut generator.conditions);
+    conditions
+}
@@ -1,10 +1,12 @@
-use std::fmt::Debug;
+use crate::source::Location;
+use std::fmt;
+use std::fmt::{Debug, Display, Formatter};
 
 
 #[derive(Debug)]
 pub enum TokenType {
     // Numeri
-    Number(i32),
+    Number(i64),
 
     // Variabili (identificatori)
     Identifier(String),
@@ -30,10 +32,13 @@ pub enum TokenType {
     Then,
     Else,
     While,
+    Invariant,
     Repeat,
     Until,
     For,
     Skip,
+    Assert,
+    Assume,
     True,
     False,
 
@@ -48,17 +53,42 @@ pub enum TokenType {
 pub struct Token {
     pub value: String,
     pub token_ty: TokenType,
+    // posizione del primo carattere del token
+    pub location: Location,
 }
 
 impl Token {
-    pub fn new(value: String, token: TokenType) -> Self {
-        Token { value, token_ty: token }
+    pub fn new(value: String, token: TokenType, location: Location) -> Self {
+        Token { value, token_ty: token, location }
+    }
+
+    // Posizione subito dopo l'ultimo carattere (un token non va mai a capo)
+    pub fn end(&self) -> Location {
+        Location {
+            line: self.location.line,
+            column: self.location.column + self.value.chars().count(),
+        }
+    }
+}
+
+// Carattere che non inizia nessun token, con la sua posizione
+#[derive(Debug, Clone, PartialEq)]
+pub struct LexError {
+    pub character: String,
+    pub location: Location,
+}
+
+impl Display for LexError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: carattere non riconosciuto '{}'", self.location, self.character)
     }
 }
 
 pub struct Lexer {
     input: Vec<char>, // Input trattato come una sequenza di caratteri
     pos: usize,       // Posizione corrente nell'input
+    line: usize,      // Riga e colonna del carattere corrente
+    column: usize,
 }
 
 impl Lexer {
@@ -67,6 +97,8 @@ impl Lexer {
         Lexer {
             input: input.chars().collect(),
             pos: 0,
+            line: 1,
+            column: 1,
         }
     }
 
@@ -95,9 +127,22 @@ impl Lexer {
 
     // Avanza di un carattere
     fn advance(&mut self) {
+        if self.current_char() == Some('\n') {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
         self.pos += 1;
     }
 
+    fn location(&self) -> Location {
+        Location {
+            line: self.line,
+            column: self.column,
+        }
+    }
+
     // Skippa gli spazi bianchi
     fn skip_whitespace(&mut self) {
         while let Some(c) = self.current_char() {
@@ -109,147 +154,157 @@ impl Lexer {
         }
     }
 
-    // Estrai il prossimo token
-    pub fn next_token(&mut self) -> Option<Token> {
+    // Estrai il prossimo token; None alla fine dell'input
+    pub fn next_token(&mut self) -> Result<Option<Token>, LexError> {
         self.skip_whitespace();
 
         if let Some(current) = self.current_char() {
+            let start = self.location();
             let current_clone = current.to_string();
 
+            let unrecognized = LexError {
+                character: current_clone,
+                location: start,
+            };
             let curr_token = match current {
                 // Operatori singoli e doppi
                 '+' => {
                     self.advance();
                     if let Some('+') = self.current_char() {
                         self.advance();
-                        Token::new("++".to_string(), TokenType::PlusPlus)
+                        Token::new("++".to_string(), TokenType::PlusPlus, start)
                     } else {
-                        Token::new("+".to_string(), TokenType::Plus)
+                        Token::new("+".to_string(), TokenType::Plus, start)
                     }
                 }
                 '-' => {
                     self.advance();
-                    Token::new("-".to_string(), TokenType::Minus)
+                    Token::new("-".to_string(), TokenType::Minus, start)
                 }
                 '*' => {
                     self.advance();
-                    Token::new("*".to_string(), TokenType::Multiply)
+                    Token::new("*".to_string(), TokenType::Multiply, start)
                 }
                 '/' => {
                     self.advance();
-                    Token::new("/".to_string(), TokenType::Divide)
+                    Token::new("/".to_string(), TokenType::Divide, start)
                 }
                 ':' => {
                     self.advance();
                     if let Some('=') = self.current_char() {
                         self.advance();
-                        Token::new(":=".to_string(), TokenType::Assign)
+                        Token::new(":=".to_string(), TokenType::Assign, start)
                     } else {
-                        return None; // Errore sintattico
+                        return Err(unrecognized); // Errore sintattico
                     }
                 }
                 '<' => {
                     self.advance();
                     if let Some('=') = self.current_char() {
                         self.advance();
-                        Token::new("<=".to_string(), TokenType::LessEqual)
+                        Token::new("<=".to_string(), TokenType::LessEqual, start)
                     } else {
-                        Token::new("<".to_string(), TokenType::Less)
+                        Token::new("<".to_string(), TokenType::Less, start)
                     }
                 }
                 '>' => {
                     self.advance();
                     if let Some('=') = self.current_char() {
                         self.advance();
-                        Token::new(">=".to_string(), TokenType::GreatEqual)
+                        Token::new(">=".to_string(), TokenType::GreatEqual, start)
                     } else {
-                        Token::new(">".to_string(), TokenType::Greater)
+                        Token::new(">".to_string(), TokenType::Greater, start)
                     }
                 }
                 '=' => {
                     self.advance();
-                    Token::new("=".to_string(), TokenType::Equal)
+                    Token::new("=".to_string(), TokenType::Equal, start)
                 }
                 '&' => {
                     self.advance();
                     if let Some('&') = self.current_char() {
                         self.advance();
-                        Token::new("&&".to_string(), TokenType::And)
+                        Token::new("&&".to_string(), TokenType::And, start)
                     } else {
-                        return None; // Errore sintattico
+                        return Err(unrecognized); // Errore sintattico
                     }
                 }
                 '|' => {
                     self.advance();
                     if let Some('|') = self.current_char() {
                         self.advance();
-                        Token::new("||".to_string(), TokenType::Or)
+                        Token::new("||".to_string(), TokenType::Or, start)
                     } else {
-                        return None; // Errore sintattico
+                        return Err(unrecognized); // Errore sintattico
                     }
                 }
                 '!' => {
                     self.advance();
-                    Token::new("!".to_string(), TokenType::Not)
+                    Token::new("!".to_string(), TokenType::Not, start)
                 }
 
                 // Simboli
                 '(' => {
                     self.advance();
-                    Token::new("(".to_string(), TokenType::Bra)
+                    Token::new("(".to_string(), TokenType::Bra, start)
                 }
                 ')' => {
                     self.advance();
-                    Token::new(")".to_string(), TokenType::Ket)
+                    Token::new(")".to_string(), TokenType::Ket, start)
                 }
                 '{' => {
                     self.advance();
-                    Token::new("{".to_string(), TokenType::CBra)
+                    Token::new("{".to_string(), TokenType::CBra, start)
                 }
                 '}' => {
                     self.advance();
-                    Token::new("}".to_string(), TokenType::Cket)
+                    Token::new("}".to_string(), TokenType::Cket, start)
                 }
                 ';' => {
                     self.advance();
-                    Token::new(";".to_string(), TokenType::Semicolon)
+                    Token::new(";".to_string(), TokenType::Semicolon, start)
                 }
 
                 // Identificatori o parole chiave (analisi per stringa completa)
                 _ if current.is_alphabetic() => {
                     let identifier = self.consume_identifier();
                     match identifier.as_str() {
-                        "if" => Token::new(identifier.clone(), TokenType::If),
-                        "then" => Token::new(identifier.clone(), TokenType::Then),
-                        "else" => Token::new(identifier.clone(), TokenType::Else),
-                        "while" => Token::new(identifier.clone(), TokenType::While),
-                        "repeat" => Token::new(identifier.clone(), TokenType::Repeat),
-                        "until" => Token::new(identifier.clone(), TokenType::Until),
-                        "for" => Token::new(identifier.clone(), TokenType::For),
-                        "skip" => Token::new(identifier.clone(), TokenType::Skip),
-                        "true" => Token::new(identifier.clone(), TokenType::True),
-                        "false" => Token::new(identifier.clone(), TokenType::False),
-                        _ => Token::new(identifier.clone(), TokenType::Identifier(identifier)),
+                        "if" => Token::new(identifier.clone(), TokenType::If, start),
+                        "then" => Token::new(identifier.clone(), TokenType::Then, start),
+                        "else" => Token::new(identifier.clone(), TokenType::Else, start),
+                        "while" => Token::new(identifier.clone(), TokenType::While, start),
+                        "invariant" => Token::new(identifier.clone(), TokenType::Invariant, start),
+                        "repeat" => Token::new(identifier.clone(), TokenType::Repeat, start),
+                        "until" => Token::new(identifier.clone(), TokenType::Until, start),
+                        "for" => Token::new(identifier.clone(), TokenType::For, start),
+                        "skip" => Token::new(identifier.clone(), TokenType::Skip, start),
+                        "assert" => Token::new(identifier.clone(), TokenType::Assert, start),
+                        "assume" => Token::new(identifier.clone(), TokenType::Assume, start),
+                        "true" => Token::new(identifier.clone(), TokenType::True, start),
+                        "false" => Token::new(identifier.clone(), TokenType::False, start),
+                        _ => Token::new(identifier.clone(), TokenType::Identifier(identifier), start),
                     }
                 }
 
                 // Numeri
                 _ if current.is_digit(10) => {
                     let number = self.consume_number();
-                    Token::new(number.to_string(), TokenType::Number(number))
+                    Token::new(number.to_string(), TokenType::Number(number), start)
                 }
 
-                _ => return None, // Carattere non riconosciuto
+                _ => return Err(unrecognized), // Carattere non riconosciuto
             };
 
-            Some(curr_token)
+            Ok(Some(curr_token))
         } else {
-            None // Fine dell'input
+            Ok(None) // Fine dell'input
         }
     }
 
-    // Consuma numeri
-    fn consume_number(&mut self) -> i32 {
+    // Consuma numeri. Il valore resta positivo e più largo di un i32: il segno
+    // lo mette il parser, che rifiuta i valori fuori intervallo. Le cifre che non
+    // stanno neanche in un i64 sono comunque fuori intervallo.
+    fn consume_number(&mut self) -> i64 {
         let mut number_str = String::new();
         while let Some(c) = self.current_char() {
             if c.is_digit(10) {
@@ -259,7 +314,7 @@ impl Lexer {
                 break;
             }
         }
-        number_str.parse::<i32>().unwrap()
+        number_str.parse::<i64>().unwrap_or(i64::MAX)
     }
 
     // Consuma identificatori o parole chiave
@@ -276,15 +331,15 @@ impl Lexer {
         ident
     }
 
-    // Tokenizza l'input completo
-    pub fn tokenize(input: String) -> Vec<Token> {
+    // Tokenizza l'input completo, fermandosi al primo carattere non riconosciuto
+    pub fn tokenize(input: String) -> Result<Vec<Token>, LexError> {
         let mut lexer = Lexer::new(input);
         let mut tokens = Vec::new();
 
-        while let Some(token) = lexer.next_token() {
+        while let Some(token) = lexer.next_token()? {
             tokens.push(token);
         }
 
-        tokens
+        Ok(tokens)
     }
 }
@@ -1,5 +1,14 @@
+mod analyzer;
 mod ast;
+mod cfg;
+mod checker;
+mod dataflow;
+mod hoare;
+mod optimizer;
 mod parser;
+mod printer;
+mod source;
+mod symbolic;
 pub mod lexer;
 
 use std::env;
@@ -9,36 +18,284 @@ use ast::boolean::GreatEqual;
 use ast::statement::Statement;
 use ast::arithmetic::Numeral;
 use ast::arithmetic::Add;
-use ast::statement::Assign;
+use ast::statement::{Assign, NodeId};
 use ast::statement::While;
+use analyzer::AbstractState;
+use analyzer::affine::AffineEqualities;
+use analyzer::congruence::Congruence;
+use analyzer::constant::Constant;
+use analyzer::fixpoint::{Solver, Strategy};
+use analyzer::interval::Interval;
+use analyzer::octagon::Octagon;
+use analyzer::partition::{Directive, Partitioned};
+use analyzer::polyhedron::Polyhedron;
+use analyzer::powerset::Powerset;
+use analyzer::product::{IntervalCongruenceProduct, SignConstantProduct};
+use analyzer::sign::Sign;
+use analyzer::interpreter::Interpreter;
+use analyzer::value_state::ValueState;
+use analyzer::zone::Zone;
+use cfg::Cfg;
+use dataflow::available::AvailableExpressions;
+use dataflow::live::LiveVariables;
+use dataflow::reaching::ReachingDefinitions;
+use dataflow::very_busy::VeryBusyExpressions;
+use checker::division::check_divisions;
+use checker::overflow::check_overflows;
+use checker::termination::check_termination;
+use checker::cost::analyze_cost;
+use checker::assertions::check_assertions;
+use checker::bmc::bounded_model_check;
+use checker::uninitialized::check_uninitialized;
+use optimizer::constant_propagation::propagate_constants;
+use optimizer::dead_code::eliminate_dead_code;
+use optimizer::simplify::{simplify_program, simplify_statement};
+use hoare::wp::verification_conditions;
+use hoare::lia::check_condition;
+use symbolic::SymbolicExecutor;
+use ast::OverflowSemantics;
 
 fn main() {
     let mut state = ast::State::new();
     
     let stmt = Assign {
+        id: NodeId::fresh(),
         var_name: "x".to_string(),
         expr: Box::new(Add {
             left: Box::new(Numeral(5)),
             right: Box::new(Numeral(3)),
         }),
     };
+    //semplificazione prima dell'analisi: l'overflow è un errore, come per gli allarmi
+    let stmt = simplify_statement(&stmt, OverflowSemantics::Error);
+    println!("simplified: {}", stmt);
 
-    //test file path
-    let program_file_path = Path::new("/home/alberto/Desktop/softver/src/test/pio");
-    let state_file_path = Path::new("/home/alberto/Desktop/softver/src/test/factorialState");
-    
-    //read from the file
-    let contents = fs::read_to_string(program_file_path)
+    //propagazione delle costanti sullo statement di esempio
+    let initial = ValueState::<Constant>::from_state(&state);
+    let mut interpreter = Interpreter::new();
+    let constants = interpreter.run(&*stmt, &initial);
+    println!("constants: {}", constants);
+    for invariant in interpreter.loop_invariants() {
+        println!("loop invariant: {}", invariant);
+    }
+    println!("folded: {}", propagate_constants(&*stmt, &initial));
+
+    //programma annotato con gli intervalli in ogni punto
+    let cfg = Cfg::new(&*stmt);
+    let mut solver = Solver::new(&cfg);
+    solver.solve(&ValueState::<Interval>::from_state(&state));
+    print!("{}", printer::annotated_program(&*stmt, &cfg, &solver));
+
+    //programma e stato iniziale da file:
+    //softver [--domain <dominio>] [--strategy <strategia>]
+    //        [--partition <riga if>[:<riga fine>]]... <programma> [stato]
+    let mut domain = "interval".to_string();
+    let mut strategy = "recursive".to_string();
+    let mut partitions = Vec::new();
+    let mut files = Vec::new();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--domain" => domain = args.next().unwrap_or_default(),
+            "--strategy" => strategy = args.next().unwrap_or_default(),
+            "--partition" => partitions.push(args.next().unwrap_or_default()),
+            _ => files.push(arg),
+        }
+    }
+    if files.is_empty() {
+        return;
+    }
+    let contents = fs::read_to_string(Path::new(&files[0]))
         .expect("Should have been able to read the program code");
-    let initial_state = fs::read_to_string(state_file_path).expect("Should have been able to read the state");
+    let (specification, mut spans) = match parser::parse_specification(&contents) {
+        Ok(parsed) => parsed,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+    };
+    //il programma stampato si rilegge nello stesso albero
+    let original = &*specification.program;
+    let round_trip = parser::parse_program(&original.to_string()).is_ok_and(|p| *p == *original);
+    println!("round-trip: {}", if round_trip { "ok" } else { "failed" });
+    //le analisi lavorano sul programma semplificato, con le posizioni del sorgente
+    let simplified = simplify_program(original, OverflowSemantics::Error, &mut spans);
+    let program = &*simplified;
+    if let Some(state_file_path) = files.get(1) {
+        let initial_state = fs::read_to_string(Path::new(state_file_path))
+            .expect("Should have been able to read the state");
+        state = parser::parse_state(&initial_state).unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        });
+    }
+    //partizionamento delle tracce sugli if scelti per riga
+    let mut directives = Vec::new();
+    for partition in &partitions {
+        let (branch, merge) = match partition.split_once(':') {
+            Some((branch, merge)) => (branch, Some(merge)),
+            None => (partition.as_str(), None),
+        };
+        let directive = match (branch.parse(), merge.map(str::parse).transpose()) {
+            (Ok(branch), Ok(merge)) => Directive::at_lines(program, &spans, branch, merge),
+            _ => None,
+        };
+        match directive {
+            Some(directive) => directives.push(directive),
+            None => {
+                eprintln!("invalid partition: {}", partition);
+                std::process::exit(1);
+            }
+        }
+    }
+    let cfg = Cfg::new(program);
+    println!("control-flow graph:");
+    print!("{}", printer::basic_blocks(&cfg));
+    let initial = ValueState::<Interval>::from_state(&state);
+    let strategy = match strategy.as_str() {
+        "recursive" => Strategy::Recursive,
+        "iterative" => Strategy::Iterative,
+        "round-robin" => Strategy::RoundRobin,
+        _ => {
+            eprintln!("unknown strategy: {}", strategy);
+            std::process::exit(1);
+        }
+    };
+    match annotate_with(&domain, program, &cfg, &state, &directives, strategy) {
+        Some(annotated) => print!("{}", annotated),
+        None => {
+            eprintln!("unknown domain: {}", domain);
+            std::process::exit(1);
+        }
+    }
 
-    //parse the program
-    parser::parse(contents, initial_state);
-    
-    //evaluate the program
+    //controllo delle divisioni per zero
+    for report in check_divisions(program, &spans, &initial) {
+        println!("{}", report);
+    }
 
-    
+    //allarmi di overflow
+    for alarm in check_overflows(program, &spans, &initial) {
+        println!("{}", alarm);
+    }
 
+    //assert dimostrati o violati, con gli invarianti dei poliedri
+    for report in check_assertions(program, &spans, &Polyhedron::from_state(&state)) {
+        println!("{}", report);
+    }
 
-    
-}
\ No newline at end of file
+    //terminazione dei cicli con funzioni di rango
+    for report in check_termination(program, &spans, &state) {
+        println!("{}", report);
+    }
+
+    //condizioni di verifica della specifica {P} programma {Q} ed export SMT-LIB
+    let conditions = verification_conditions(&specification);
+    for condition in &conditions {
+        println!("{}", condition);
+    }
+    print!("{}", hoare::smtlib::script(&conditions));
+
+    //le stesse condizioni decise internamente, con un controesempio se falliscono
+    for condition in &conditions {
+        println!("{}: {}", condition.description, check_condition(condition));
+    }
+
+    //esecuzione simbolica, con un ingresso di test per ogni cammino
+    for (index, path) in SymbolicExecutor::new().execute(program).iter().enumerate() {
+        println!("path {}: {}", index + 1, path);
+    }
+
+    //bounded model checking degli assert, srotolando ogni while fino a 4 volte,
+    //con la stessa codifica esportata in SMT-LIB
+    for report in bounded_model_check(program, &spans, 4) {
+        println!("{}", report);
+    }
+    print!("{}", hoare::smtlib::script(&checker::bmc::encode(program, &spans, 4)));
+
+    //limiti sulle iterazioni e costo nel caso peggiore
+    let (bounds, cost) = analyze_cost(program, &spans, &state);
+    for bound in bounds {
+        println!("{}", bound);
+    }
+    println!("worst-case cost: {}", cost);
+
+    //analisi dataflow classiche, con i fatti in ogni punto
+    let live = dataflow::solve(&LiveVariables::new(&cfg), &cfg);
+    let reaching = dataflow::solve(&ReachingDefinitions::new(&cfg), &cfg);
+    let available = dataflow::solve(&AvailableExpressions::new(&cfg), &cfg);
+    let busy = dataflow::solve(&VeryBusyExpressions::new(&cfg), &cfg);
+    println!("live variables:");
+    print!("{}", printer::annotated_points(program, &cfg, &|p| live.show(p)));
+    println!("reaching definitions:");
+    print!("{}", printer::annotated_points(program, &cfg, &|p| reaching.show(p)));
+    println!("available expressions:");
+    print!("{}", printer::annotated_points(program, &cfg, &|p| available.show(p)));
+    println!("very busy expressions:");
+    print!("{}", printer::annotated_points(program, &cfg, &|p| busy.show(p)));
+
+    //letture di variabili non inizializzate
+    for warning in check_uninitialized(program, &spans, &state) {
+        println!("{}", warning);
+    }
+
+    //propagazione delle costanti sul programma
+    let constants = ValueState::<Constant>::from_state(&state);
+    println!("folded:");
+    println!("{}", propagate_constants(program, &constants));
+
+    //eliminazione del codice morto
+    let (cleaned, removed) = eliminate_dead_code(program, &initial);
+    for removal in &removed {
+        println!("{}", removal);
+    }
+    println!("{}", cleaned);
+}
+
+// Programma annotato con gli stati di un dominio astratto in ogni punto,
+// partizionati secondo le direttive se ce ne sono, seguito dalle statistiche
+// del risolutore
+fn annotate<S: AbstractState>(
+    program: &dyn Statement,
+    cfg: &Cfg,
+    state: &ast::State,
+    directives: &[Directive],
+    strategy: Strategy,
+) -> String {
+    if directives.is_empty() {
+        let mut solver = Solver::new(cfg).with_strategy(strategy);
+        solver.solve(&S::from_state(state));
+        let annotated = printer::annotated_program(program, cfg, &solver);
+        return format!("{}statistics: {}\n", annotated, solver.statistics());
+    }
+    let mut solver = Solver::new(cfg).with_strategy(strategy);
+    solver.solve(&Partitioned::new(S::from_state(state), directives.to_vec()));
+    let annotated = printer::annotated_program(program, cfg, &solver);
+    format!("{}statistics: {}\n", annotated, solver.statistics())
+}
+
+// Domini che si possono scegliere da riga di comando
+fn annotate_with(
+    domain: &str,
+    program: &dyn Statement,
+    cfg: &Cfg,
+    state: &ast::State,
+    directives: &[Directive],
+    strategy: Strategy,
+) -> Option<String> {
+    let (p, d, s) = (program, directives, strategy);
+    Some(match domain {
+        "interval" => annotate::<ValueState<Interval>>(p, cfg, state, d, s),
+        "powerset" => annotate::<Powerset<ValueState<Interval>>>(p, cfg, state, d, s),
+        "constant" => annotate::<ValueState<Constant>>(p, cfg, state, d, s),
+        "congruence" => annotate::<ValueState<Congruence>>(p, cfg, state, d, s),
+        "sign" => annotate::<ValueState<Sign>>(p, cfg, state, d, s),
+        "interval-congruence" => annotate::<ValueState<IntervalCongruenceProduct>>(p, cfg, state, d, s),
+        "sign-constant" => annotate::<ValueState<SignConstantProduct>>(p, cfg, state, d, s),
+        "zone" => annotate::<Zone>(p, cfg, state, d, s),
+        "octagon" => annotate::<Octagon>(p, cfg, state, d, s),
+        "polyhedron" => annotate::<Polyhedron>(p, cfg, state, d, s),
+        "affine" => annotate::<AffineEqualities>(p, cfg, state, d, s),
+        _ => return None,
+    })
+}
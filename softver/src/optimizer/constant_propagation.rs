@@ -0,0 +1,236 @@
+use crate::analyzer::interpreter::Interpreter;
+use crate::analyzer::value_state::ValueState;
+use crate::analyzer::{comparison_of, AbstractDomain, AbstractState, Comparison, ValueDomain};
+use crate::ast::arithmetic::{
+    Add, ArithmeticExpression, ArithmeticNode, Divide, Minus, Numeral, Product, Uminus,
+};
+use crate::ast::boolean::{
+    And, Boolean, BooleanExpression, BooleanNode, Equal, Great, GreatEqual, Less, LessEqual, Not,
+    Or,
+};
+use crate::ast::statement::{
+    Assert, Assign, Assume, Concat, IfThenElse, Statement, StatementNode, While,
+};
+use crate::ast::OverflowSemantics;
+
+// Propagazione delle costanti: sostituisce con un Numeral ogni sottoespressione
+// che l'analisi nel dominio V dimostra costante nel punto in cui viene valutata,
+// purché valutarla non possa fallire.
+pub fn propagate_constants<V: ValueDomain>(
+    stmt: &dyn Statement,
+    initial: &ValueState<V>,
+) -> Box<dyn Statement> {
+    let mut interpreter = Interpreter::new();
+    fold_statement(&mut interpreter, stmt, initial).0
+}
+
+fn fold_statement<V: ValueDomain>(
+    interpreter: &mut Interpreter<ValueState<V>>,
+    stmt: &dyn Statement,
+    pre: &ValueState<V>,
+) -> (Box<dyn Statement>, ValueState<V>) {
+    match stmt.node() {
+        StatementNode::Assign(a) => {
+            let folded = Assign {
+                id: a.id,
+                var_name: a.var_name.clone(),
+                expr: fold_arithmetic(&*a.expr, pre),
+            };
+            let mut post = pre.clone();
+            post.assign(&a.var_name, &*a.expr);
+            (Box::new(folded), post)
+        }
+        StatementNode::Skip(s) => (Box::new(s.clone()), pre.clone()),
+        StatementNode::Assert(a) => {
+            let folded = Assert {
+                id: a.id,
+                condition: fold_boolean(&*a.condition, pre),
+            };
+            let mut post = pre.clone();
+            post.assume(&*a.condition, true);
+            (Box::new(folded), post)
+        }
+        StatementNode::Assume(a) => {
+            let folded = Assume {
+                id: a.id,
+                condition: fold_boolean(&*a.condition, pre),
+            };
+            let mut post = pre.clone();
+            post.assume(&*a.condition, true);
+            (Box::new(folded), post)
+        }
+        StatementNode::Concat(c) => {
+            let (first, middle) = fold_statement(interpreter, &*c.first, pre);
+            let (second, post) = fold_statement(interpreter, &*c.second, &middle);
+            (
+                Box::new(Concat {
+                    id: c.id,
+                    first,
+                    second,
+                }),
+                post,
+            )
+        }
+        StatementNode::IfThenElse(i) => {
+            let mut then_pre = pre.clone();
+            then_pre.assume(&*i.guard, true);
+            let mut else_pre = pre.clone();
+            else_pre.assume(&*i.guard, false);
+            let (true_expr, then_post) = fold_statement(interpreter, &*i.true_expr, &then_pre);
+            let (false_expr, else_post) = fold_statement(interpreter, &*i.false_expr, &else_pre);
+            let folded = IfThenElse {
+                id: i.id,
+                guard: fold_boolean(&*i.guard, pre),
+                true_expr,
+                false_expr,
+            };
+            (Box::new(folded), then_post.join(&else_post))
+        }
+        StatementNode::While(w) => {
+            if pre.is_bottom() {
+                return (Box::new(w.clone()), ValueState::bottom());
+            }
+            // il corpo va riscritto con l'invariante, non con lo stato d'ingresso
+            let invariant = interpreter.loop_invariant(w, pre);
+            let mut body_pre = invariant.clone();
+            body_pre.assume(&*w.guard, true);
+            let (body, _) = fold_statement(interpreter, &*w.body, &body_pre);
+            let folded = While {
+                id: w.id,
+                guard: fold_boolean(&*w.guard, &invariant),
+                body,
+                invariant: w.invariant.clone(),
+            };
+            let mut post = invariant;
+            post.assume(&*w.guard, false);
+            (Box::new(folded), post)
+        }
+    }
+}
+
+// Le variabili costanti diventano Numeral, poi le operazioni con soli Numeral come
+// operandi si calcolano se non falliscono. Non si sostituisce un nodo solo perché
+// il suo valore astratto è costante: in `(y / z) * 0` sparirebbe una possibile
+// divisione per zero, come nella semplificazione.
+pub fn fold_arithmetic<V: ValueDomain>(
+    expr: &dyn ArithmeticExpression,
+    state: &ValueState<V>,
+) -> Box<dyn ArithmeticExpression> {
+    let fold = |e: &dyn ArithmeticExpression| fold_arithmetic(e, state);
+    match expr.node() {
+        ArithmeticNode::Numeral(n) => Box::new(n.clone()),
+        ArithmeticNode::Variable(v) => match state.eval(expr).as_constant() {
+            Some(value) => Box::new(Numeral(value)),
+            None => Box::new(v.clone()),
+        },
+        ArithmeticNode::Add(e) => fold_binary(
+            fold(&*e.left),
+            fold(&*e.right),
+            OverflowSemantics::add,
+            |left, right| Box::new(Add { left, right }),
+        ),
+        ArithmeticNode::Minus(e) => fold_binary(
+            fold(&*e.left),
+            fold(&*e.right),
+            OverflowSemantics::minus,
+            |left, right| Box::new(Minus { left, right }),
+        ),
+        ArithmeticNode::Product(e) => fold_binary(
+            fold(&*e.left),
+            fold(&*e.right),
+            OverflowSemantics::product,
+            |left, right| Box::new(Product { left, right }),
+        ),
+        ArithmeticNode::Divide(e) => fold_binary(
+            fold(&*e.left),
+            fold(&*e.right),
+            OverflowSemantics::divide,
+            |left, right| Box::new(Divide { left, right }),
+        ),
+        ArithmeticNode::Uminus(e) => {
+            let right = fold(&*e.right);
+            let value = match right.node() {
+                ArithmeticNode::Numeral(n) => OverflowSemantics::Error.uminus(n.0),
+                _ => None,
+            };
+            match value {
+                Some(value) => Box::new(Numeral(value)),
+                None => Box::new(Uminus { right }),
+            }
+        }
+    }
+}
+
+// Costruttore del nodo di un'operazione binaria
+type Binary = fn(
+    Box<dyn ArithmeticExpression>,
+    Box<dyn ArithmeticExpression>,
+) -> Box<dyn ArithmeticExpression>;
+
+// Operazione binaria sugli operandi già ripiegati: l'overflow è un errore, come
+// negli allarmi, quindi un'operazione che fallisce resta nel programma
+fn fold_binary(
+    left: Box<dyn ArithmeticExpression>,
+    right: Box<dyn ArithmeticExpression>,
+    operation: fn(OverflowSemantics, i32, i32) -> Option<i32>,
+    node: Binary,
+) -> Box<dyn ArithmeticExpression> {
+    if let (ArithmeticNode::Numeral(l), ArithmeticNode::Numeral(r)) = (left.node(), right.node()) {
+        if let Some(value) = operation(OverflowSemantics::Error, l.0, r.0) {
+            return Box::new(Numeral(value));
+        }
+    }
+    node(left, right)
+}
+
+pub fn fold_boolean<V: ValueDomain>(
+    guard: &dyn BooleanExpression,
+    state: &ValueState<V>,
+) -> Box<dyn BooleanExpression> {
+    if let Some((op, left, right)) = comparison_of(guard) {
+        let left = fold_arithmetic(left, state);
+        let right = fold_arithmetic(right, state);
+        // confronto tra due costanti: si valuta direttamente
+        if let (ArithmeticNode::Numeral(l), ArithmeticNode::Numeral(r)) = (left.node(), right.node())
+        {
+            return Box::new(Boolean(op.holds(l.0 as i64, r.0 as i64)));
+        }
+        return match op {
+            Comparison::Equal => Box::new(Equal { left, right }),
+            Comparison::Less => Box::new(Less { left, right }),
+            Comparison::LessEqual => Box::new(LessEqual { left, right }),
+            Comparison::Great => Box::new(Great { left, right }),
+            Comparison::GreatEqual => Box::new(GreatEqual { left, right }),
+            Comparison::NotEqual => Box::new(Not {
+                expression: Box::new(Equal { left, right }),
+            }),
+        };
+    }
+    match guard.node() {
+        BooleanNode::Not(n) => {
+            let expression = fold_boolean(&*n.expression, state);
+            match expression.node() {
+                BooleanNode::Boolean(b) => Box::new(Boolean(!b.0)),
+                _ => Box::new(Not { expression }),
+            }
+        }
+        BooleanNode::And(a) => {
+            let left = fold_boolean(&*a.left, state);
+            let right = fold_boolean(&*a.right, state);
+            match (left.node(), right.node()) {
+                (BooleanNode::Boolean(l), BooleanNode::Boolean(r)) => Box::new(Boolean(l.0 && r.0)),
+                _ => Box::new(And { left, right }),
+            }
+        }
+        BooleanNode::Or(o) => {
+            let left = fold_boolean(&*o.left, state);
+            let right = fold_boolean(&*o.right, state);
+            match (left.node(), right.node()) {
+                (BooleanNode::Boolean(l), BooleanNode::Boolean(r)) => Box::new(Boolean(l.0 || r.0)),
+                _ => Box::new(Or { left, right }),
+            }
+        }
+        BooleanNode::Boolean(b) => Box::new(b.clone()),
+        _ => unreachable!("i confronti sono già stati gestiti"),
+    }
+}
@@ -0,0 +1,166 @@
+use crate::analyzer::interpreter::Interpreter;
+use crate::analyzer::value_state::ValueState;
+use crate::analyzer::{AbstractDomain, AbstractState, ValueDomain};
+use crate::ast::statement::{Concat, IfThenElse, Skip, Statement, StatementNode, While};
+use crate::cfg::Cfg;
+use crate::dataflow::live::LiveVariables;
+use crate::dataflow::{solve, Analysis, Solution};
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+// Una parte del programma tolta dall'eliminazione del codice morto
+#[derive(Debug, Clone, PartialEq)]
+pub enum Removal {
+    // assegnamento a una variabile non più letta prima di essere riassegnata
+    DeadStore(String),
+    // if con la guardia sempre uguale a `kept`: resta solo quel ramo
+    Branch { guard: String, kept: bool },
+    // while con la guardia falsa già all'ingresso
+    Loop(String),
+}
+
+impl Display for Removal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Removal::DeadStore(assign) => write!(f, "dead store removed: {}", assign),
+            Removal::Branch { guard, kept } => write!(
+                f,
+                "if {}: guard always {}, {} branch removed",
+                guard,
+                kept,
+                if *kept { "else" } else { "then" }
+            ),
+            Removal::Loop(guard) => write!(f, "while {}: guard initially false, loop removed", guard),
+        }
+    }
+}
+
+struct Eliminator<'c, 'a, V> {
+    interpreter: Interpreter<ValueState<V>>,
+    cfg: &'c Cfg<'a>,
+    live: Solution<String>,
+    removed: Vec<Removal>,
+}
+
+impl<V: ValueDomain> Eliminator<'_, '_, V> {
+    // Statement ripulito (None se sparisce del tutto) e stato dopo lo statement
+    fn statement(
+        &mut self,
+        stmt: &dyn Statement,
+        pre: &ValueState<V>,
+    ) -> (Option<Box<dyn Statement>>, ValueState<V>) {
+        // il codice irraggiungibile si lascia com'è
+        if pre.is_bottom() {
+            return (Some(stmt.to_boxed()), ValueState::bottom());
+        }
+        match stmt.node() {
+            StatementNode::Assign(a) => {
+                let mut post = pre.clone();
+                post.assign(&a.var_name, &*a.expr);
+                let exit = self.cfg.span_of(stmt).map(|(_, exit)| exit);
+                if exit.is_some_and(|p| !self.live.at(p).contains(&a.var_name)) {
+                    self.removed.push(Removal::DeadStore(a.to_string()));
+                    return (None, post);
+                }
+                (Some(Box::new(a.clone())), post)
+            }
+            StatementNode::Skip(s) => (Some(Box::new(s.clone())), pre.clone()),
+            // le condizioni restano anche se sempre vere: sono la specifica
+            StatementNode::Assert(_) | StatementNode::Assume(_) => {
+                (Some(stmt.to_boxed()), self.interpreter.run(stmt, pre))
+            }
+            StatementNode::Concat(c) => {
+                let (first, middle) = self.statement(&*c.first, pre);
+                let (second, post) = self.statement(&*c.second, &middle);
+                let result: Option<Box<dyn Statement>> = match (first, second) {
+                    (Some(first), Some(second)) => Some(Box::new(Concat {
+                        id: c.id,
+                        first,
+                        second,
+                    })),
+                    (first, second) => first.or(second),
+                };
+                (result, post)
+            }
+            StatementNode::IfThenElse(i) => {
+                let mut then_pre = pre.clone();
+                then_pre.assume(&*i.guard, true);
+                let mut else_pre = pre.clone();
+                else_pre.assume(&*i.guard, false);
+                if then_pre.is_bottom() || else_pre.is_bottom() {
+                    let kept = else_pre.is_bottom();
+                    self.removed.push(Removal::Branch {
+                        guard: i.guard.to_string(),
+                        kept,
+                    });
+                    return if kept {
+                        self.statement(&*i.true_expr, &then_pre)
+                    } else {
+                        self.statement(&*i.false_expr, &else_pre)
+                    };
+                }
+                let (true_expr, then_post) = self.statement(&*i.true_expr, &then_pre);
+                let (false_expr, else_post) = self.statement(&*i.false_expr, &else_pre);
+                let cleaned = IfThenElse {
+                    id: i.id,
+                    guard: i.guard.clone(),
+                    true_expr: true_expr.unwrap_or_else(|| Box::new(Skip::new())),
+                    false_expr: false_expr.unwrap_or_else(|| Box::new(Skip::new())),
+                };
+                (Some(Box::new(cleaned)), then_post.join(&else_post))
+            }
+            StatementNode::While(w) => {
+                let mut enter = pre.clone();
+                enter.assume(&*w.guard, true);
+                if enter.is_bottom() {
+                    self.removed.push(Removal::Loop(w.guard.to_string()));
+                    return (None, pre.clone());
+                }
+                // il corpo si ripulisce con l'invariante, come nella propagazione delle costanti
+                let invariant = self.interpreter.loop_invariant(w, pre);
+                let mut body_pre = invariant.clone();
+                body_pre.assume(&*w.guard, true);
+                let (body, _) = self.statement(&*w.body, &body_pre);
+                let cleaned = While {
+                    id: w.id,
+                    guard: w.guard.clone(),
+                    body: body.unwrap_or_else(|| Box::new(Skip::new())),
+                    invariant: w.invariant.clone(),
+                };
+                let mut post = invariant;
+                post.assume(&*w.guard, false);
+                (Some(Box::new(cleaned)), post)
+            }
+        }
+    }
+}
+
+// Elimina gli assegnamenti morti, i rami di if mai presi e i while mai eseguiti.
+// La vivezza si calcola considerando lette alla fine tutte le variabili, perché lo
+// stato finale è il risultato del programma. Togliere qualcosa può rendere morto
+// altro codice, quindi si ripete finché il programma non cambia più.
+pub fn eliminate_dead_code<V: ValueDomain>(
+    program: &dyn Statement,
+    initial: &ValueState<V>,
+) -> (Box<dyn Statement>, Vec<Removal>) {
+    let mut current = program.to_boxed();
+    let mut removed = Vec::new();
+    loop {
+        let cfg = Cfg::new(&*current);
+        let analysis = LiveVariables::new(&cfg);
+        let observable = analysis.universe();
+        let live = solve(&analysis.with_live_at_exit(observable), &cfg);
+        let mut eliminator = Eliminator {
+            interpreter: Interpreter::new(),
+            cfg: &cfg,
+            live,
+            removed: Vec::new(),
+        };
+        let (result, _) = eliminator.statement(&*current, initial);
+        if eliminator.removed.is_empty() {
+            return (current, removed);
+        }
+        removed.append(&mut eliminator.removed);
+        current = result.unwrap_or_else(|| Box::new(Skip::new()));
+    }
+}
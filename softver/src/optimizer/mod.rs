@@ -0,0 +1,3 @@
+pub mod dead_code;
+pub mod simplify;
+pub mod constant_propagation;
@@ -0,0 +1,446 @@
+use crate::analyzer::{comparison_of, Comparison};
+use crate::ast::arithmetic::{
+    Add, ArithmeticExpression, ArithmeticNode, Divide, Minus, Numeral, Product, Uminus,
+};
+use crate::ast::boolean::{
+    And, Boolean, BooleanExpression, BooleanNode, Equal, Great, GreatEqual, Less, LessEqual, Not,
+    Or,
+};
+use crate::ast::statement::{
+    Assert, Assign, Assume, Concat, IfThenElse, Statement, StatementNode, While,
+};
+use crate::ast::OverflowSemantics;
+use crate::source::SourceMap;
+
+// Riscrittura locale delle espressioni: calcola le operazioni tra costanti e
+// applica le identità algebriche che non cambiano il comportamento del programma
+// con la semantica dell'overflow scelta. Un'identità che fa sparire una
+// sottoespressione (x * 0, x - x, ...) si applica solo se questa non può dare
+// errore, altrimenti l'errore sparirebbe insieme a lei. Le variabili lette si
+// assumono presenti nello stato.
+pub fn simplify_statement(
+    stmt: &dyn Statement,
+    semantics: OverflowSemantics,
+) -> Box<dyn Statement> {
+    match stmt.node() {
+        StatementNode::Assign(a) => Box::new(Assign {
+            id: a.id,
+            var_name: a.var_name.clone(),
+            expr: simplify_arithmetic(&*a.expr, semantics),
+        }),
+        StatementNode::Skip(s) => Box::new(s.clone()),
+        StatementNode::Assert(a) => Box::new(Assert {
+            id: a.id,
+            condition: simplify_boolean(&*a.condition, semantics),
+        }),
+        StatementNode::Assume(a) => Box::new(Assume {
+            id: a.id,
+            condition: simplify_boolean(&*a.condition, semantics),
+        }),
+        StatementNode::Concat(c) => Box::new(Concat {
+            id: c.id,
+            first: simplify_statement(&*c.first, semantics),
+            second: simplify_statement(&*c.second, semantics),
+        }),
+        StatementNode::IfThenElse(i) => Box::new(IfThenElse {
+            id: i.id,
+            guard: simplify_boolean(&*i.guard, semantics),
+            true_expr: simplify_statement(&*i.true_expr, semantics),
+            false_expr: simplify_statement(&*i.false_expr, semantics),
+        }),
+        StatementNode::While(w) => Box::new(While {
+            id: w.id,
+            guard: simplify_boolean(&*w.guard, semantics),
+            body: simplify_statement(&*w.body, semantics),
+            invariant: w
+                .invariant
+                .as_ref()
+                .map(|invariant| simplify_boolean(&**invariant, semantics)),
+        }),
+    }
+}
+
+// Semplifica il programma letto dal file riportando le posizioni sul nuovo
+// albero: gli statement tengono il loro id, mentre ogni espressione riscritta
+// prende la posizione di quella che sostituisce e si scende nei figli finché
+// i due alberi hanno la stessa forma
+pub fn simplify_program(
+    program: &dyn Statement,
+    semantics: OverflowSemantics,
+    spans: &mut SourceMap,
+) -> Box<dyn Statement> {
+    let simplified = simplify_statement(program, semantics);
+    locate_statement(program, &*simplified, spans);
+    simplified
+}
+
+fn locate_statement(old: &dyn Statement, new: &dyn Statement, spans: &mut SourceMap) {
+    match (old.node(), new.node()) {
+        (StatementNode::Assign(a), StatementNode::Assign(b)) => {
+            locate_arithmetic(&*a.expr, &*b.expr, spans)
+        }
+        (StatementNode::Assert(a), StatementNode::Assert(b)) => {
+            locate_boolean(&*a.condition, &*b.condition, spans)
+        }
+        (StatementNode::Assume(a), StatementNode::Assume(b)) => {
+            locate_boolean(&*a.condition, &*b.condition, spans)
+        }
+        (StatementNode::Concat(a), StatementNode::Concat(b)) => {
+            locate_statement(&*a.first, &*b.first, spans);
+            locate_statement(&*a.second, &*b.second, spans);
+        }
+        (StatementNode::IfThenElse(a), StatementNode::IfThenElse(b)) => {
+            locate_boolean(&*a.guard, &*b.guard, spans);
+            locate_statement(&*a.true_expr, &*b.true_expr, spans);
+            locate_statement(&*a.false_expr, &*b.false_expr, spans);
+        }
+        (StatementNode::While(a), StatementNode::While(b)) => {
+            locate_boolean(&*a.guard, &*b.guard, spans);
+            locate_statement(&*a.body, &*b.body, spans);
+            if let (Some(old), Some(new)) = (&a.invariant, &b.invariant) {
+                locate_boolean(&**old, &**new, spans);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn locate_arithmetic(
+    old: &dyn ArithmeticExpression,
+    new: &dyn ArithmeticExpression,
+    spans: &mut SourceMap,
+) {
+    if let Some(span) = spans.span_of(old) {
+        spans.insert(new, span);
+    }
+    let children: Vec<(&dyn ArithmeticExpression, &dyn ArithmeticExpression)> =
+        match (old.node(), new.node()) {
+            (ArithmeticNode::Add(a), ArithmeticNode::Add(b)) => {
+                vec![(&*a.left, &*b.left), (&*a.right, &*b.right)]
+            }
+            (ArithmeticNode::Minus(a), ArithmeticNode::Minus(b)) => {
+                vec![(&*a.left, &*b.left), (&*a.right, &*b.right)]
+            }
+            (ArithmeticNode::Product(a), ArithmeticNode::Product(b)) => {
+                vec![(&*a.left, &*b.left), (&*a.right, &*b.right)]
+            }
+            (ArithmeticNode::Divide(a), ArithmeticNode::Divide(b)) => {
+                vec![(&*a.left, &*b.left), (&*a.right, &*b.right)]
+            }
+            (ArithmeticNode::Uminus(a), ArithmeticNode::Uminus(b)) => vec![(&*a.right, &*b.right)],
+            _ => Vec::new(),
+        };
+    for (old, new) in children {
+        locate_arithmetic(old, new, spans);
+    }
+}
+
+fn locate_boolean(old: &dyn BooleanExpression, new: &dyn BooleanExpression, spans: &mut SourceMap) {
+    if let Some(span) = spans.span_of(old) {
+        spans.insert(new, span);
+    }
+    if let (Some((op, left, right)), Some((new_op, new_left, new_right))) =
+        (comparison_of(old), comparison_of(new))
+    {
+        if op == new_op {
+            locate_arithmetic(left, new_left, spans);
+            locate_arithmetic(right, new_right, spans);
+        }
+        return;
+    }
+    match (old.node(), new.node()) {
+        (BooleanNode::And(a), BooleanNode::And(b)) => {
+            locate_boolean(&*a.left, &*b.left, spans);
+            locate_boolean(&*a.right, &*b.right, spans);
+        }
+        (BooleanNode::Or(a), BooleanNode::Or(b)) => {
+            locate_boolean(&*a.left, &*b.left, spans);
+            locate_boolean(&*a.right, &*b.right, spans);
+        }
+        (BooleanNode::Not(a), BooleanNode::Not(b)) => {
+            locate_boolean(&*a.expression, &*b.expression, spans)
+        }
+        _ => {}
+    }
+}
+
+fn numeral(expr: &dyn ArithmeticExpression) -> Option<i32> {
+    match expr.node() {
+        ArithmeticNode::Numeral(n) => Some(n.0),
+        _ => None,
+    }
+}
+
+// Vero se valutare l'espressione non può mai dare errore
+fn cannot_fail(expr: &dyn ArithmeticExpression, semantics: OverflowSemantics) -> bool {
+    let wrapping = semantics == OverflowSemantics::Wrapping;
+    match expr.node() {
+        ArithmeticNode::Numeral(_) | ArithmeticNode::Variable(_) => true,
+        ArithmeticNode::Add(e) => {
+            wrapping && cannot_fail(&*e.left, semantics) && cannot_fail(&*e.right, semantics)
+        }
+        ArithmeticNode::Minus(e) => {
+            wrapping && cannot_fail(&*e.left, semantics) && cannot_fail(&*e.right, semantics)
+        }
+        ArithmeticNode::Product(e) => {
+            wrapping && cannot_fail(&*e.left, semantics) && cannot_fail(&*e.right, semantics)
+        }
+        ArithmeticNode::Uminus(e) => wrapping && cannot_fail(&*e.right, semantics),
+        // solo un divisore costante diverso da 0 (e da -1 se l'overflow è un errore)
+        ArithmeticNode::Divide(e) => {
+            let safe_divisor = match numeral(&*e.right) {
+                Some(d) => d != 0 && (wrapping || d != -1),
+                None => false,
+            };
+            safe_divisor && cannot_fail(&*e.left, semantics)
+        }
+    }
+}
+
+fn cannot_fail_boolean(guard: &dyn BooleanExpression, semantics: OverflowSemantics) -> bool {
+    if let Some((_, left, right)) = comparison_of(guard) {
+        return cannot_fail(left, semantics) && cannot_fail(right, semantics);
+    }
+    match guard.node() {
+        BooleanNode::Boolean(_) => true,
+        BooleanNode::Not(n) => cannot_fail_boolean(&*n.expression, semantics),
+        BooleanNode::And(a) => {
+            cannot_fail_boolean(&*a.left, semantics) && cannot_fail_boolean(&*a.right, semantics)
+        }
+        BooleanNode::Or(o) => {
+            cannot_fail_boolean(&*o.left, semantics) && cannot_fail_boolean(&*o.right, semantics)
+        }
+        _ => unreachable!("i confronti sono già stati gestiti"),
+    }
+}
+
+pub fn simplify_arithmetic(
+    expr: &dyn ArithmeticExpression,
+    semantics: OverflowSemantics,
+) -> Box<dyn ArithmeticExpression> {
+    match expr.node() {
+        ArithmeticNode::Numeral(n) => Box::new(n.clone()),
+        ArithmeticNode::Variable(v) => Box::new(v.clone()),
+        ArithmeticNode::Add(e) => add(
+            simplify_arithmetic(&*e.left, semantics),
+            simplify_arithmetic(&*e.right, semantics),
+            semantics,
+        ),
+        ArithmeticNode::Minus(e) => minus(
+            simplify_arithmetic(&*e.left, semantics),
+            simplify_arithmetic(&*e.right, semantics),
+            semantics,
+        ),
+        ArithmeticNode::Product(e) => product(
+            simplify_arithmetic(&*e.left, semantics),
+            simplify_arithmetic(&*e.right, semantics),
+            semantics,
+        ),
+        ArithmeticNode::Divide(e) => divide(
+            simplify_arithmetic(&*e.left, semantics),
+            simplify_arithmetic(&*e.right, semantics),
+            semantics,
+        ),
+        ArithmeticNode::Uminus(e) => uminus(simplify_arithmetic(&*e.right, semantics), semantics),
+    }
+}
+
+fn add(
+    left: Box<dyn ArithmeticExpression>,
+    right: Box<dyn ArithmeticExpression>,
+    semantics: OverflowSemantics,
+) -> Box<dyn ArithmeticExpression> {
+    match (numeral(&*left), numeral(&*right)) {
+        (Some(a), Some(b)) => {
+            if let Some(value) = semantics.add(a, b) {
+                return Box::new(Numeral(value));
+            }
+        }
+        (_, Some(0)) => return left,
+        (Some(0), _) => return right,
+        // (e + a) + b = e + (a + b) vale solo in aritmetica modulare:
+        // altrimenti e + a può andare in overflow anche se e + (a + b) no
+        (None, Some(b)) if semantics == OverflowSemantics::Wrapping => {
+            if let ArithmeticNode::Add(inner) = left.node() {
+                if let Some(a) = numeral(&*inner.right) {
+                    let constant = Box::new(Numeral(a.wrapping_add(b)));
+                    return add(inner.left.clone(), constant, semantics);
+                }
+            }
+        }
+        _ => {}
+    }
+    Box::new(Add { left, right })
+}
+
+fn minus(
+    left: Box<dyn ArithmeticExpression>,
+    right: Box<dyn ArithmeticExpression>,
+    semantics: OverflowSemantics,
+) -> Box<dyn ArithmeticExpression> {
+    match (numeral(&*left), numeral(&*right)) {
+        (Some(a), Some(b)) => {
+            if let Some(value) = semantics.minus(a, b) {
+                return Box::new(Numeral(value));
+            }
+        }
+        (_, Some(0)) => return left,
+        _ => {}
+    }
+    if *left == *right && cannot_fail(&*left, semantics) {
+        return Box::new(Numeral(0));
+    }
+    Box::new(Minus { left, right })
+}
+
+fn product(
+    left: Box<dyn ArithmeticExpression>,
+    right: Box<dyn ArithmeticExpression>,
+    semantics: OverflowSemantics,
+) -> Box<dyn ArithmeticExpression> {
+    match (numeral(&*left), numeral(&*right)) {
+        (Some(a), Some(b)) => {
+            if let Some(value) = semantics.product(a, b) {
+                return Box::new(Numeral(value));
+            }
+        }
+        (_, Some(1)) => return left,
+        (Some(1), _) => return right,
+        (_, Some(0)) if cannot_fail(&*left, semantics) => return right,
+        (Some(0), _) if cannot_fail(&*right, semantics) => return left,
+        _ => {}
+    }
+    Box::new(Product { left, right })
+}
+
+fn divide(
+    left: Box<dyn ArithmeticExpression>,
+    right: Box<dyn ArithmeticExpression>,
+    semantics: OverflowSemantics,
+) -> Box<dyn ArithmeticExpression> {
+    match (numeral(&*left), numeral(&*right)) {
+        (Some(a), Some(b)) => {
+            if let Some(value) = semantics.divide(a, b) {
+                return Box::new(Numeral(value));
+            }
+        }
+        (_, Some(1)) => return left,
+        _ => {}
+    }
+    Box::new(Divide { left, right })
+}
+
+fn uminus(
+    right: Box<dyn ArithmeticExpression>,
+    semantics: OverflowSemantics,
+) -> Box<dyn ArithmeticExpression> {
+    if let Some(value) = numeral(&*right).and_then(|a| semantics.uminus(a)) {
+        return Box::new(Numeral(value));
+    }
+    // -(-e) = e, ma se l'overflow è un errore -(-MIN) fallisce mentre MIN no
+    if semantics == OverflowSemantics::Wrapping {
+        if let ArithmeticNode::Uminus(inner) = right.node() {
+            return inner.right.clone();
+        }
+    }
+    Box::new(Uminus { right })
+}
+
+fn comparison(
+    op: Comparison,
+    left: Box<dyn ArithmeticExpression>,
+    right: Box<dyn ArithmeticExpression>,
+) -> Box<dyn BooleanExpression> {
+    match op {
+        Comparison::Equal => Box::new(Equal { left, right }),
+        Comparison::Less => Box::new(Less { left, right }),
+        Comparison::LessEqual => Box::new(LessEqual { left, right }),
+        Comparison::Great => Box::new(Great { left, right }),
+        Comparison::GreatEqual => Box::new(GreatEqual { left, right }),
+        Comparison::NotEqual => Box::new(Not {
+            expression: Box::new(Equal { left, right }),
+        }),
+    }
+}
+
+fn boolean(guard: &dyn BooleanExpression) -> Option<bool> {
+    match guard.node() {
+        BooleanNode::Boolean(b) => Some(b.0),
+        _ => None,
+    }
+}
+
+pub fn simplify_boolean(
+    guard: &dyn BooleanExpression,
+    semantics: OverflowSemantics,
+) -> Box<dyn BooleanExpression> {
+    if let Some((op, left, right)) = comparison_of(guard) {
+        let left = simplify_arithmetic(left, semantics);
+        let right = simplify_arithmetic(right, semantics);
+        if let (Some(l), Some(r)) = (numeral(&*left), numeral(&*right)) {
+            return Box::new(Boolean(op.holds(l as i64, r as i64)));
+        }
+        // e op e: il risultato dipende solo dall'operatore
+        if *left == *right && cannot_fail(&*left, semantics) {
+            return Box::new(Boolean(op.holds(0, 0)));
+        }
+        return comparison(op, left, right);
+    }
+    match guard.node() {
+        BooleanNode::Boolean(b) => Box::new(b.clone()),
+        BooleanNode::Not(n) => {
+            let expression = simplify_boolean(&*n.expression, semantics);
+            if let Some(b) = boolean(&*expression) {
+                return Box::new(Boolean(!b));
+            }
+            if let BooleanNode::Not(inner) = expression.node() {
+                return inner.expression.clone();
+            }
+            // la negazione di un confronto è il confronto opposto
+            if let Some((op, left, right)) = comparison_of(&*expression) {
+                return comparison(op.negate(), left.to_boxed(), right.to_boxed());
+            }
+            Box::new(Not { expression })
+        }
+        // && e || valutano la destra solo se serve, quindi la sinistra costante
+        // decide sempre; la destra costante invece può sparire solo se la
+        // sinistra non può dare errore
+        BooleanNode::And(a) => {
+            let left = simplify_boolean(&*a.left, semantics);
+            match boolean(&*left) {
+                Some(false) => return left,
+                Some(true) => return simplify_boolean(&*a.right, semantics),
+                None => {}
+            }
+            let right = simplify_boolean(&*a.right, semantics);
+            match boolean(&*right) {
+                Some(true) => return left,
+                Some(false) if cannot_fail_boolean(&*left, semantics) => return right,
+                _ => {}
+            }
+            if *left == *right {
+                return left;
+            }
+            Box::new(And { left, right })
+        }
+        BooleanNode::Or(o) => {
+            let left = simplify_boolean(&*o.left, semantics);
+            match boolean(&*left) {
+                Some(true) => return left,
+                Some(false) => return simplify_boolean(&*o.right, semantics),
+                None => {}
+            }
+            let right = simplify_boolean(&*o.right, semantics);
+            match boolean(&*right) {
+                Some(false) => return left,
+                Some(true) if cannot_fail_boolean(&*left, semantics) => return right,
+                _ => {}
+            }
+            if *left == *right {
+                return left;
+            }
+            Box::new(Or { left, right })
+        }
+        _ => unreachable!("i confronti sono già stati gestiti"),
+    }
+}
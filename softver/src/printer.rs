@@ -0,0 +1,210 @@
+use crate::analyzer::fixpoint::Solver;
+use crate::analyzer::AbstractState;
+use crate::ast::boolean::Not;
+use crate::ast::statement::{Statement, StatementNode};
+use crate::ast::State;
+use crate::cfg::{Action, Cfg, Point};
+
+const INDENT: &str = "    ";
+
+// Annotazioni prima e dopo uno statement
+type Annotation<'p> = &'p dyn Fn(&dyn Statement) -> Option<(String, String)>;
+
+// Stampa di uno statement con un'istruzione per riga e i blocchi indentati.
+// Con le annotazioni, ogni punto di programma è stampato una volta sola: lo stato
+// precede sulla stessa riga lo statement che parte da quel punto, l'ultimo stato
+// di un blocco va su una riga a sé.
+struct Printer<'p> {
+    output: String,
+    depth: usize,
+    annotation: Option<Annotation<'p>>,
+    // stato in attesa di essere stampato davanti alla prossima riga
+    pending: Option<String>,
+}
+
+impl Printer<'_> {
+    fn line(&mut self, text: &str) {
+        for _ in 0..self.depth {
+            self.output.push_str(INDENT);
+        }
+        if let Some(state) = self.pending.take() {
+            self.output.push_str(&state);
+            self.output.push(' ');
+        }
+        self.output.push_str(text);
+        self.output.push('\n');
+    }
+
+    fn annotate(&mut self, stmt: &dyn Statement, after: bool) {
+        if let Some(annotation) = self.annotation {
+            if let Some((before_state, after_state)) = annotation(stmt) {
+                self.pending = Some(if after { after_state } else { before_state });
+            }
+        }
+    }
+
+    // Una sequenza di statement separati da ';'
+    fn block(&mut self, stmt: &dyn Statement) {
+        let mut sequence = Vec::new();
+        flatten(stmt, &mut sequence);
+        self.annotate(sequence[0], false);
+        for (k, s) in sequence.iter().enumerate() {
+            let separator = if k + 1 < sequence.len() { ";" } else { "" };
+            self.statement(*s, separator);
+            self.annotate(*s, true);
+        }
+        if let Some(state) = self.pending.take() {
+            self.line(&state);
+        }
+    }
+
+    fn statement(&mut self, stmt: &dyn Statement, separator: &str) {
+        match stmt.node() {
+            StatementNode::Assign(a) => {
+                self.line(&format!("{} := {}{}", a.var_name, a.expr, separator))
+            }
+            StatementNode::Skip(_) => self.line(&format!("skip{}", separator)),
+            // block appiattisce solo la catena a destra: una sequenza in prima
+            // posizione va tra graffe, altrimenti la ristampa cambierebbe l'albero
+            StatementNode::Concat(_) => {
+                self.line("{");
+                self.depth += 1;
+                self.block(stmt);
+                self.depth -= 1;
+                self.line(&format!("}}{}", separator));
+            }
+            StatementNode::IfThenElse(i) => {
+                self.line(&format!("if {} then {{", i.guard));
+                self.depth += 1;
+                self.block(&*i.true_expr);
+                self.depth -= 1;
+                self.line("} else {");
+                self.depth += 1;
+                self.block(&*i.false_expr);
+                self.depth -= 1;
+                self.line(&format!("}}{}", separator));
+            }
+            StatementNode::While(w) => {
+                match &w.invariant {
+                    Some(invariant) => {
+                        self.line(&format!("while {} invariant {} {{", w.guard, invariant))
+                    }
+                    None => self.line(&format!("while {} {{", w.guard)),
+                }
+                self.depth += 1;
+                self.block(&*w.body);
+                self.depth -= 1;
+                self.line(&format!("}}{}", separator));
+            }
+            StatementNode::Assert(a) => {
+                self.line(&format!("assert({}){}", a.condition, separator))
+            }
+            StatementNode::Assume(a) => {
+                self.line(&format!("assume({}){}", a.condition, separator))
+            }
+        }
+    }
+}
+
+// Sequenza piatta degli statement di una catena di Concat annidati a destra,
+// come li costruisce il parser
+fn flatten<'a>(stmt: &'a dyn Statement, result: &mut Vec<&'a dyn Statement>) {
+    match stmt.node() {
+        StatementNode::Concat(c) => {
+            result.push(&*c.first);
+            flatten(&*c.second, result);
+        }
+        _ => result.push(stmt),
+    }
+}
+
+fn print(stmt: &dyn Statement, annotation: Option<Annotation>) -> String {
+    let mut printer = Printer {
+        output: String::new(),
+        depth: 0,
+        annotation,
+        pending: None,
+    };
+    printer.block(stmt);
+    printer.output
+}
+
+pub fn program(stmt: &dyn Statement) -> String {
+    print(stmt, None)
+}
+
+// Stato concreto nel formato dei file di stato iniziale, in ordine di nome
+pub fn state(state: &State) -> String {
+    let mut values: Vec<_> = state.iter().collect();
+    values.sort();
+    let values: Vec<String> = values
+        .iter()
+        .map(|(var, value)| format!("{} := {}", var, value))
+        .collect();
+    values.join("; ")
+}
+
+// Programma con lo stato astratto calcolato dal risolutore in ogni punto.
+// Davanti a un while lo stato è l'invariante in testa al ciclo.
+pub fn annotated_program<S: AbstractState>(
+    stmt: &dyn Statement,
+    cfg: &Cfg,
+    solver: &Solver<S>,
+) -> String {
+    annotated_points(stmt, cfg, &|point| solver.state_at(point).to_string())
+}
+
+// Programma con un'annotazione qualsiasi per ogni punto del grafo
+// (per esempio i fatti delle analisi dataflow)
+pub fn annotated_points(stmt: &dyn Statement, cfg: &Cfg, text: &dyn Fn(Point) -> String) -> String {
+    let annotation = |s: &dyn Statement| {
+        cfg.span_of(s)
+            .map(|(entry, exit)| (text(entry), text(exit)))
+    };
+    print(stmt, Some(&annotation))
+}
+
+fn action(action: &Action) -> String {
+    match action {
+        Action::Assign(a) => a.to_string(),
+        Action::Assume(guard, true) => format!("assume {}", guard),
+        Action::Assume(guard, false) => format!(
+            "assume {}",
+            Not {
+                expression: guard.to_boxed()
+            }
+        ),
+        Action::Skip => "skip".to_string(),
+    }
+}
+
+// Blocchi di base del grafo, ciascuno con i suoi punti e gli archi che ne
+// escono; accanto agli archi verso un altro blocco c'è il blocco di arrivo
+pub fn basic_blocks(cfg: &Cfg) -> String {
+    let mut result = String::new();
+    for (index, block) in cfg.basic_blocks().iter().enumerate() {
+        let points: Vec<String> = block.points.iter().map(|p| p.to_string()).collect();
+        result.push_str(&format!("B{} = [{}]", index, points.join(", ")));
+        if let Some(w) = cfg.loop_at(block.points[0]) {
+            result.push_str(&format!(" (head of while {})", w.guard));
+        }
+        result.push('\n');
+        for &point in &block.points {
+            for edge in cfg.successors(point) {
+                let target = match cfg.block_of(edge.to) {
+                    Some(target) if target != index => format!(" (B{})", target),
+                    _ => String::new(),
+                };
+                result.push_str(&format!(
+                    "{}{} -> {}{}: {}\n",
+                    INDENT,
+                    edge.from,
+                    edge.to,
+                    target,
+                    action(&edge.action)
+                ));
+            }
+        }
+    }
+    result
+}
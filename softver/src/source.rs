@@ -0,0 +1,71 @@
+use crate::ast::statement::{NodeId, Statement};
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+// Posizione nel sorgente, righe e colonne contate da 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+// Intervallo di sorgente occupato da un nodo: da start incluso a end escluso
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Span {
+    pub start: Location,
+    pub end: Location,
+}
+
+impl Display for Span {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.start, self.end)
+    }
+}
+
+// Identità di un'espressione, indipendente dal tipo concreto
+fn address<T: ?Sized>(node: &T) -> *const () {
+    node as *const T as *const ()
+}
+
+// Posizioni dei nodi prodotti dal parser. Gli statement si riconoscono dalla loro
+// identità, che sopravvive alle copie e alle riscritture; le espressioni
+// dall'indirizzo, quindi per loro la mappa vale finché l'AST non viene clonato.
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    expressions: HashMap<*const (), Span>,
+    statements: HashMap<NodeId, Span>,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        SourceMap {
+            expressions: HashMap::new(),
+            statements: HashMap::new(),
+        }
+    }
+
+    // Un nodo scartato dal parser (per esempio tornando indietro) può lasciare il
+    // suo indirizzo a uno nuovo: l'ultima posizione inserita è quella buona
+    pub fn insert<T: ?Sized>(&mut self, node: &T, span: Span) {
+        self.expressions.insert(address(node), span);
+    }
+
+    pub fn span_of<T: ?Sized>(&self, node: &T) -> Option<Span> {
+        self.expressions.get(&address(node)).copied()
+    }
+
+    pub fn insert_statement(&mut self, stmt: &dyn Statement, span: Span) {
+        self.statements.insert(stmt.id(), span);
+    }
+
+    pub fn span_of_statement(&self, stmt: &dyn Statement) -> Option<Span> {
+        self.statements.get(&stmt.id()).copied()
+    }
+}
@@ -0,0 +1,249 @@
+use crate::ast::arithmetic::ArithmeticExpression;
+use crate::ast::boolean::{And, Boolean, BooleanExpression, BooleanNode, Not};
+use crate::ast::statement::{Statement, StatementNode, While};
+use crate::ast::{OverflowSemantics, State};
+use crate::dataflow::{guard_operands, variables};
+use crate::hoare::lia::{check_validity, Validity};
+use crate::hoare::wp::{replace_arithmetic, replace_boolean};
+use crate::optimizer::simplify::{simplify_arithmetic, simplify_boolean};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathEnd {
+    Completed,
+    // l'assert con questa condizione fallisce alla fine del cammino
+    AssertionFailed(String),
+    // il cammino è stato troncato entrando nel while oltre il limite di iterazioni
+    LoopBound(String),
+}
+
+// Un cammino del programma: i valori sono espressioni sui valori iniziali
+// delle variabili, che hanno lo stesso nome delle variabili stesse
+#[derive(Debug, Clone)]
+pub struct Path {
+    // condizioni sui valori iniziali che fanno percorrere il cammino
+    pub condition: Vec<Box<dyn BooleanExpression>>,
+    // valore simbolico di ogni variabile assegnata lungo il cammino
+    pub store: BTreeMap<String, Box<dyn ArithmeticExpression>>,
+    // variabili lette prima di essere assegnate: gli ingressi del cammino
+    pub inputs: BTreeSet<String>,
+    pub end: PathEnd,
+    // stato iniziale concreto che percorre il cammino, se il decisore lo trova
+    pub test: Option<State>,
+}
+
+impl Path {
+    fn value(&self, expr: &dyn ArithmeticExpression) -> Box<dyn ArithmeticExpression> {
+        let value_of = |var: &str| self.store.get(var).cloned();
+        simplify_arithmetic(
+            &*replace_arithmetic(expr, &value_of),
+            OverflowSemantics::Error,
+        )
+    }
+
+    fn guard(&self, guard: &dyn BooleanExpression) -> Box<dyn BooleanExpression> {
+        let value_of = |var: &str| self.store.get(var).cloned();
+        simplify_boolean(
+            &*replace_boolean(guard, &value_of),
+            OverflowSemantics::Error,
+        )
+    }
+
+    fn read(&mut self, operands: Vec<&dyn ArithmeticExpression>) {
+        let mut read = BTreeSet::new();
+        for operand in operands {
+            variables(operand, &mut read);
+        }
+        self.inputs
+            .extend(read.into_iter().filter(|var| !self.store.contains_key(var)));
+    }
+
+    // Congiunzione delle condizioni del cammino
+    fn formula(&self) -> Box<dyn BooleanExpression> {
+        let mut result: Box<dyn BooleanExpression> = Box::new(Boolean(true));
+        for condition in self.condition.iter().rev() {
+            result = match result.node() {
+                BooleanNode::Boolean(Boolean(true)) => condition.clone(),
+                _ => Box::new(And {
+                    left: condition.clone(),
+                    right: result,
+                }),
+            };
+        }
+        result
+    }
+
+    // Il cammino con la condizione in più, se non è certamente impercorribile.
+    // La condizione è già espressa sui valori iniziali.
+    fn branch(&self, condition: Box<dyn BooleanExpression>) -> Option<Path> {
+        let mut path = self.clone();
+        match condition.node() {
+            BooleanNode::Boolean(Boolean(true)) => return Some(path),
+            BooleanNode::Boolean(Boolean(false)) => return None,
+            _ => path.condition.push(condition),
+        }
+        // percorribile se la negazione della condizione non è valida
+        let negation = Not {
+            expression: path.formula(),
+        };
+        match check_validity(&negation) {
+            Validity::Valid => None,
+            _ => Some(path),
+        }
+    }
+}
+
+impl Display for Path {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.end {
+            PathEnd::Completed => writeln!(f, "completed")?,
+            PathEnd::AssertionFailed(condition) => writeln!(f, "assert({}) fails", condition)?,
+            PathEnd::LoopBound(guard) => writeln!(f, "while {}: unrolling bound reached", guard)?,
+        }
+        writeln!(f, "  condition: {}", self.formula())?;
+        // gli ingressi mai assegnati conservano il loro valore iniziale
+        let mut state: BTreeMap<&String, String> = self
+            .inputs
+            .iter()
+            .map(|var| (var, var.clone()))
+            .collect();
+        state.extend(self.store.iter().map(|(var, value)| (var, value.to_string())));
+        let store: Vec<String> = state
+            .iter()
+            .map(|(var, value)| format!("{} = {}", var, value))
+            .collect();
+        write!(f, "  final state: {{ {} }}", store.join(", "))?;
+        match &self.test {
+            Some(test) => write!(f, "\n  test input: {}", crate::printer::state(test)),
+            None => write!(f, "\n  test input: unknown"),
+        }
+    }
+}
+
+// Esecuzione simbolica: ogni guardia divide il cammino in due, e i cammini
+// impercorribili secondo il decisore lineare vengono scartati. L'aritmetica
+// è quella degli interi matematici, come nelle condizioni di verifica.
+pub struct SymbolicExecutor {
+    // iterazioni di ogni while oltre le quali il cammino viene troncato
+    pub unroll_bound: usize,
+}
+
+impl Default for SymbolicExecutor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SymbolicExecutor {
+    pub fn new() -> Self {
+        SymbolicExecutor { unroll_bound: 4 }
+    }
+
+    fn statement(&self, stmt: &dyn Statement, mut path: Path) -> Vec<Path> {
+        match stmt.node() {
+            StatementNode::Assign(a) => {
+                path.read(vec![&*a.expr]);
+                let value = path.value(&*a.expr);
+                path.store.insert(a.var_name.clone(), value);
+                vec![path]
+            }
+            StatementNode::Skip(_) => vec![path],
+            StatementNode::Concat(c) => {
+                let mut result = Vec::new();
+                for path in self.statement(&*c.first, path) {
+                    if path.end == PathEnd::Completed {
+                        result.extend(self.statement(&*c.second, path));
+                    } else {
+                        result.push(path);
+                    }
+                }
+                result
+            }
+            StatementNode::IfThenElse(i) => {
+                path.read(guard_operands(&*i.guard));
+                let guard = path.guard(&*i.guard);
+                let mut result = Vec::new();
+                if let Some(then_path) = path.branch(guard.clone()) {
+                    result.extend(self.statement(&*i.true_expr, then_path));
+                }
+                if let Some(else_path) = path.branch(negate(guard)) {
+                    result.extend(self.statement(&*i.false_expr, else_path));
+                }
+                result
+            }
+            StatementNode::While(w) => self.iterate(w, path, 0),
+            StatementNode::Assert(a) => {
+                path.read(guard_operands(&*a.condition));
+                let condition = path.guard(&*a.condition);
+                let mut result = Vec::new();
+                if let Some(mut failed) = path.branch(negate(condition.clone())) {
+                    failed.end = PathEnd::AssertionFailed(a.condition.to_string());
+                    result.push(failed);
+                }
+                result.extend(path.branch(condition));
+                result
+            }
+            // i cammini che violano l'assunzione vengono scartati
+            StatementNode::Assume(a) => {
+                path.read(guard_operands(&*a.condition));
+                let condition = path.guard(&*a.condition);
+                path.branch(condition).into_iter().collect()
+            }
+        }
+    }
+
+    fn iterate(&self, w: &While, mut path: Path, iteration: usize) -> Vec<Path> {
+        path.read(guard_operands(&*w.guard));
+        let guard = path.guard(&*w.guard);
+        let mut result: Vec<Path> = path.branch(negate(guard.clone())).into_iter().collect();
+        if let Some(mut inside) = path.branch(guard) {
+            if iteration == self.unroll_bound {
+                inside.end = PathEnd::LoopBound(w.guard.to_string());
+                result.push(inside);
+            } else {
+                for path in self.statement(&*w.body, inside) {
+                    if path.end == PathEnd::Completed {
+                        result.extend(self.iterate(w, path, iteration + 1));
+                    } else {
+                        result.push(path);
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    // Tutti i cammini percorribili del programma, ciascuno con un ingresso
+    // concreto che lo percorre quando il decisore ne trova uno. Gli ingressi
+    // non vincolati dal cammino valgono 0.
+    pub fn execute(&self, program: &dyn Statement) -> Vec<Path> {
+        let start = Path {
+            condition: Vec::new(),
+            store: BTreeMap::new(),
+            inputs: BTreeSet::new(),
+            end: PathEnd::Completed,
+            test: None,
+        };
+        let mut paths = self.statement(program, start);
+        for path in &mut paths {
+            let negation = Not {
+                expression: path.formula(),
+            };
+            if let Validity::Invalid(model) = check_validity(&negation) {
+                let test = path
+                    .inputs
+                    .iter()
+                    .map(|var| (var.clone(), model.get(var).copied().unwrap_or(0)))
+                    .collect();
+                path.test = Some(test);
+            }
+        }
+        paths
+    }
+}
+
+fn negate(guard: Box<dyn BooleanExpression>) -> Box<dyn BooleanExpression> {
+    simplify_boolean(&Not { expression: guard }, OverflowSemantics::Error)
+}